@@ -0,0 +1,42 @@
+// What the about box says: which build of the plugin this is, from the
+// environment variables build.rs sets, and where to find out more.  The
+// logo that opens it is the Rust one, drawn lighter under the mouse and
+// darker while it's pressed.
+
+use crate::skin::image_texture;
+use conrod_core::image::{Id, Map};
+use glium::texture::Texture2d;
+use std::process::Command;
+
+pub const VERSION: &str = env!("CARGO_PKG_VERSION");
+pub const GIT_HASH: &str = env!("WHISPER_GIT_HASH");
+pub const BUILD_DATE: &str = env!("WHISPER_BUILD_DATE");
+
+pub const LINKS: [&str; 3] = [
+    "https://github.com/hatoo/vst-rs-example-conrod",
+    "https://github.com/RustAudio/vst-rs",
+    "https://github.com/PistonDevelopers/conrod",
+];
+
+#[derive(Clone, Copy)]
+pub struct Logo {
+    pub normal: Id,
+    pub hover: Id,
+    pub press: Id,
+}
+
+impl Logo {
+    pub fn load(display: &glium::Display, image_map: &mut Map<Texture2d>) -> Option<Self> {
+        let mut load = |bytes: &[u8]| Some(image_map.insert(image_texture(display, bytes)?));
+        Some(Self {
+            normal: load(include_bytes!("../assets/images/rust.png"))?,
+            hover: load(include_bytes!("../assets/images/rust_hover.png"))?,
+            press: load(include_bytes!("../assets/images/rust_press.png"))?,
+        })
+    }
+}
+
+// In the browser, the way Windows opens anything.
+pub fn open_link(url: &str) {
+    let _ = Command::new("explorer").arg(url).spawn();
+}
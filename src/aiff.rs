@@ -0,0 +1,149 @@
+// A small reader for AIFF files, the Mac's answer to WAV.  `hound` only
+// does WAV, and AIFF is simple enough to read by hand: a "FORM" container
+// holding a "COMM" chunk that describes the audio and an "SSND" chunk with
+// the samples themselves, all big-endian.
+
+pub struct Aiff {
+    pub channels: usize,
+    pub sample_rate: f32,
+    // Every channel's samples interleaved, from -1.0 to 1.0.
+    pub samples: Vec<f32>,
+}
+
+// How the samples are stored.  Plain AIFF is always big-endian integers,
+// the compressed AIFF-C variant adds a few more we know how to read.
+#[derive(Clone, Copy, PartialEq)]
+enum Encoding {
+    BigEndian,
+    LittleEndian,
+    Float,
+}
+
+struct Common {
+    channels: usize,
+    frames: usize,
+    bits: usize,
+    sample_rate: f32,
+    encoding: Encoding,
+}
+
+pub fn read(data: &[u8]) -> Result<Aiff, &'static str> {
+    if data.len() < 12 || &data[0..4] != b"FORM" {
+        return Err("not an AIFF file");
+    }
+    let compressed = match &data[8..12] {
+        b"AIFF" => false,
+        b"AIFC" => true,
+        _ => return Err("not an AIFF file"),
+    };
+
+    let mut common = None;
+    let mut sound = None;
+    let mut position = 12;
+    while position + 8 <= data.len() {
+        let id = &data[position..position + 4];
+        let size = read_u32(&data[position + 4..]) as usize;
+        let start = position + 8;
+        let end = (start + size).min(data.len());
+        match id {
+            b"COMM" => common = Some(read_common(&data[start..end], compressed)?),
+            b"SSND" => sound = Some(&data[start..end]),
+            _ => (),
+        }
+        // Chunks are padded to an even number of bytes.
+        position = start + size + (size & 1);
+    }
+
+    let common = common.ok_or("missing COMM chunk")?;
+    let sound = sound.ok_or("missing SSND chunk")?;
+    if sound.len() < 8 {
+        return Err("truncated SSND chunk");
+    }
+    // The samples can start a little way into the chunk, for alignment.
+    let offset = 8 + read_u32(&sound[0..]) as usize;
+    let sound = sound.get(offset..).ok_or("truncated SSND chunk")?;
+
+    let bytes = (common.bits + 7) / 8;
+    if common.channels == 0 || bytes == 0 || bytes > 4 {
+        return Err("unsupported sample format");
+    }
+    if common.encoding == Encoding::Float && bytes != 4 {
+        return Err("unsupported sample format");
+    }
+    let count = (common.frames * common.channels).min(sound.len() / bytes);
+    let samples = sound
+        .chunks(bytes)
+        .take(count)
+        .map(|sample| decode(sample, common.encoding))
+        .collect();
+
+    Ok(Aiff {
+        channels: common.channels,
+        sample_rate: common.sample_rate,
+        samples,
+    })
+}
+
+fn read_common(chunk: &[u8], compressed: bool) -> Result<Common, &'static str> {
+    if chunk.len() < 18 {
+        return Err("truncated COMM chunk");
+    }
+    let encoding = if !compressed {
+        Encoding::BigEndian
+    } else {
+        match chunk.get(18..22) {
+            Some(b"NONE") => Encoding::BigEndian,
+            Some(b"sowt") => Encoding::LittleEndian,
+            Some(b"fl32") | Some(b"FL32") => Encoding::Float,
+            _ => return Err("unsupported AIFF-C compression"),
+        }
+    };
+    Ok(Common {
+        channels: u16::from_be_bytes([chunk[0], chunk[1]]) as usize,
+        frames: read_u32(&chunk[2..]) as usize,
+        bits: u16::from_be_bytes([chunk[6], chunk[7]]) as usize,
+        sample_rate: read_extended(&chunk[8..18]) as f32,
+        encoding,
+    })
+}
+
+fn read_u32(bytes: &[u8]) -> u32 {
+    u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]])
+}
+
+// The sample rate is stored as an 80-bit extended precision float, which
+// Rust has no type for, so we take it apart: a sign bit, a 15-bit exponent
+// and a 64-bit mantissa with an explicit leading one.
+fn read_extended(bytes: &[u8]) -> f64 {
+    let sign_exponent = u16::from_be_bytes([bytes[0], bytes[1]]);
+    let mut mantissa = [0; 8];
+    mantissa.copy_from_slice(&bytes[2..10]);
+    let mantissa = u64::from_be_bytes(mantissa);
+
+    let exponent = i32::from(sign_exponent & 0x7fff) - 16383 - 63;
+    let value = mantissa as f64 * 2.0f64.powi(exponent);
+    if sign_exponent & 0x8000 != 0 {
+        -value
+    } else {
+        value
+    }
+}
+
+fn decode(sample: &[u8], encoding: Encoding) -> f32 {
+    let mut word = [0; 4];
+    match encoding {
+        Encoding::BigEndian | Encoding::Float => word[..sample.len()].copy_from_slice(sample),
+        // Reversed, so the most significant byte comes first like the others.
+        Encoding::LittleEndian => {
+            for (to, from) in word.iter_mut().zip(sample.iter().rev()) {
+                *to = *from;
+            }
+        }
+    }
+    match encoding {
+        Encoding::Float => f32::from_bits(u32::from_be_bytes(word)),
+        // Shorter samples end up in the top bytes, so every bit depth can
+        // be scaled the same way.
+        _ => i32::from_be_bytes(word) as f32 / 2_147_483_648.0,
+    }
+}
@@ -0,0 +1,416 @@
+use crate::midi::{midi_event, MidiMessage};
+use crate::tempo::Transport;
+use rand::random;
+use vst::event::MidiEvent;
+
+// The order the arpeggiator plays the held notes in.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ArpMode {
+    Up,
+    Down,
+    // Up and back down again, without playing the top and bottom notes twice.
+    UpDown,
+    Random,
+}
+
+impl ArpMode {
+    pub const ALL: [ArpMode; 4] = [ArpMode::Up, ArpMode::Down, ArpMode::UpDown, ArpMode::Random];
+
+    pub fn from_parameter(value: f32) -> Self {
+        let index = (value * Self::ALL.len() as f32) as usize;
+        Self::ALL[index.min(Self::ALL.len() - 1)]
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            ArpMode::Up => "Up",
+            ArpMode::Down => "Down",
+            ArpMode::UpDown => "Up/Down",
+            ArpMode::Random => "Random",
+        }
+    }
+
+    // Which note of a pattern `length` notes long the `count`th step plays.
+    fn index(self, count: usize, length: usize) -> usize {
+        match self {
+            ArpMode::Up => count % length,
+            ArpMode::Down => length - 1 - count % length,
+            ArpMode::UpDown if length < 2 => 0,
+            ArpMode::UpDown => {
+                let period = 2 * length - 2;
+                let position = count % period;
+                if position < length {
+                    position
+                } else {
+                    period - position
+                }
+            }
+            ArpMode::Random => ((random::<f32>() * length as f32) as usize).min(length - 1),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct ArpSettings {
+    pub mode: ArpMode,
+    // How long each step is, in quarter notes.
+    pub step: f64,
+    // How much of each step the note is held for, from 0.0 to 1.0.
+    pub gate: f64,
+    // How many octaves the pattern climbs through, starting with the held notes.
+    pub octaves: u8,
+    // Keep playing the notes after the keys are let go, until a new chord
+    // is played.
+    pub latch: bool,
+}
+
+// The most octaves the pattern can climb through.
+pub const MAX_OCTAVES: u8 = 4;
+
+// The most notes a pattern can have: every key, in every octave.
+const MAX_PATTERN: usize = 128 * MAX_OCTAVES as usize;
+
+// Turns the notes being held into a stream of single notes, one per step.
+// Notes go in as MIDI and come out as MIDI, so the synth plays them like
+// any other.
+pub struct Arpeggiator {
+    // The keys actually down right now.
+    held: [bool; 128],
+    held_count: usize,
+    // The velocity of every note in the pattern, which is the same as the
+    // held keys unless latched.
+    notes: [Option<u8>; 128],
+    // The notes in the order they are played going up, with their velocities.
+    pattern: Vec<(u8, u8)>,
+    // How many octaves the pattern was built for, and whether the notes
+    // have changed since.
+    octaves: u8,
+    changed: bool,
+    // How many steps have been played since the pattern started.
+    count: usize,
+    // Where we are, in quarter notes, and which step that was in.
+    beat: f64,
+    step: Option<i64>,
+    // The note sounding right now, if any.
+    playing: Option<u8>,
+    latch: bool,
+}
+
+impl Default for Arpeggiator {
+    fn default() -> Self {
+        Self {
+            held: [false; 128],
+            held_count: 0,
+            notes: [None; 128],
+            pattern: Vec::with_capacity(MAX_PATTERN),
+            octaves: 1,
+            changed: false,
+            count: 0,
+            beat: 0.0,
+            step: None,
+            playing: None,
+            latch: false,
+        }
+    }
+}
+
+impl Arpeggiator {
+    // Arpeggiate `samples` samples' worth of `input`, adding what should be
+    // played to `output`.  Anything that isn't a note goes straight through.
+    pub fn process(
+        &mut self,
+        settings: &ArpSettings,
+        transport: &Transport,
+        beats_per_sample: f64,
+        samples: usize,
+        input: &[MidiEvent],
+        output: &mut Vec<MidiEvent>,
+    ) {
+        if settings.latch != self.latch {
+            self.latch = settings.latch;
+            if !self.latch {
+                self.release_latched();
+            }
+        }
+
+        // While the host is playing, the steps line up with its bars.  When
+        // it isn't, we keep our own time.
+        if let (true, Some(position)) = (transport.playing, transport.position) {
+            self.beat = position;
+        }
+
+        let mut next = 0;
+        for i in 0..samples {
+            while next < input.len() && input[next].delta_frames.max(0) as usize <= i {
+                self.event(&input[next], output);
+                next += 1;
+            }
+            self.tick(settings, i as i32, output);
+            self.beat += beats_per_sample;
+        }
+        // Events past the end of the buffer shouldn't happen, but if they
+        // do they still count.
+        for ev in &input[next..] {
+            self.event(ev, output);
+        }
+    }
+
+    // Let go of the note playing and forget every key, for when the
+    // arpeggiator is switched off.
+    pub fn stop(&mut self, output: &mut Vec<MidiEvent>) {
+        self.release(0, output);
+        self.held = [false; 128];
+        self.held_count = 0;
+        self.notes = [None; 128];
+        self.pattern.clear();
+        self.changed = false;
+        self.step = None;
+    }
+
+    fn event(&mut self, ev: &MidiEvent, output: &mut Vec<MidiEvent>) {
+        if self.note(ev.data) {
+            self.changed = true;
+        } else {
+            output.push(*ev);
+        }
+    }
+
+    // Start the pattern over from its first note, keeping the notes in it.
+    pub fn restart(&mut self) {
+        self.count = 0;
+        self.beat = 0.0;
+        self.step = None;
+    }
+
+    // Take a note on or off for the pattern, returning whether it was one.
+    fn note(&mut self, data: [u8; 3]) -> bool {
+        match MidiMessage::parse(&data) {
+            Some(MidiMessage::NoteOn { note, velocity, .. }) => {
+                // A new chord after letting go of a latched one replaces it.
+                if self.latch && self.held_count == 0 {
+                    self.notes = [None; 128];
+                }
+                if !self.held[note as usize] {
+                    self.held[note as usize] = true;
+                    self.held_count += 1;
+                }
+                self.notes[note as usize] = Some(velocity);
+            }
+            Some(MidiMessage::NoteOff { note, .. }) => {
+                // Keys that went down before we were switched on are left
+                // for the synth to let go of.
+                if !self.held[note as usize] {
+                    return false;
+                }
+                self.held[note as usize] = false;
+                self.held_count -= 1;
+                if !self.latch {
+                    self.notes[note as usize] = None;
+                }
+            }
+            _ => return false,
+        }
+        true
+    }
+
+    fn release_latched(&mut self) {
+        for (note, held) in self.notes.iter_mut().zip(self.held.iter()) {
+            if !held {
+                *note = None;
+            }
+        }
+        self.changed = true;
+    }
+
+    fn tick(&mut self, settings: &ArpSettings, delta_frames: i32, output: &mut Vec<MidiEvent>) {
+        let was_empty = self.pattern.is_empty();
+        if self.changed || settings.octaves != self.octaves {
+            self.build_pattern(settings.octaves);
+        }
+        if self.pattern.is_empty() {
+            self.release(delta_frames, output);
+            self.step = None;
+            return;
+        }
+        // The first note of a new pattern plays straight away, rather than
+        // waiting for the next step to come around.
+        if was_empty {
+            self.count = 0;
+            self.step = None;
+        }
+
+        let position = self.beat / settings.step;
+        let step = position.floor() as i64;
+        if Some(step) != self.step {
+            self.release(delta_frames, output);
+            let (note, velocity) =
+                self.pattern[settings.mode.index(self.count, self.pattern.len())];
+            output.push(midi_event([0x90, note, velocity], delta_frames));
+            self.playing = Some(note);
+            self.count += 1;
+            self.step = Some(step);
+        } else if position - step as f64 >= settings.gate {
+            self.release(delta_frames, output);
+        }
+    }
+
+    // Rebuild the order of the notes, climbing through every octave.
+    fn build_pattern(&mut self, octaves: u8) {
+        self.octaves = octaves;
+        self.changed = false;
+        self.pattern.clear();
+        for octave in 0..octaves.max(1) {
+            for (note, velocity) in self.notes.iter().enumerate() {
+                let note = note + 12 * octave as usize;
+                if let (Some(velocity), true) = (velocity, note < 128) {
+                    self.pattern.push((note as u8, *velocity));
+                }
+            }
+        }
+    }
+
+    fn release(&mut self, delta_frames: i32, output: &mut Vec<MidiEvent>) {
+        if let Some(note) = self.playing.take() {
+            output.push(midi_event([0x80, note, 0], delta_frames));
+        }
+    }
+}
+
+// Map a 0.0 to 1.0 parameter onto 1 to 4 octaves.
+pub fn arp_octaves(value: f32) -> u8 {
+    1 + ((value * f32::from(MAX_OCTAVES)) as u8).min(MAX_OCTAVES - 1)
+}
+
+// Map a 0.0 to 1.0 parameter onto a gate of 5% to 100% of the step.
+pub fn arp_gate(value: f32) -> f64 {
+    f64::from(0.05 + value * 0.95)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn settings(mode: ArpMode) -> ArpSettings {
+        ArpSettings {
+            mode,
+            step: 1.0,
+            gate: 0.5,
+            octaves: 1,
+            latch: false,
+        }
+    }
+
+    // The notes started over `steps` steps of four samples each, with
+    // `input` coming in at the start.
+    fn played(
+        arpeggiator: &mut Arpeggiator,
+        settings: &ArpSettings,
+        input: &[MidiEvent],
+        steps: usize,
+    ) -> Vec<u8> {
+        let mut output = Vec::new();
+        arpeggiator.process(
+            settings,
+            &Transport::default(),
+            0.25,
+            steps * 4,
+            input,
+            &mut output,
+        );
+        output
+            .iter()
+            .filter(|ev| ev.data[0] == 0x90)
+            .map(|ev| ev.data[1])
+            .collect()
+    }
+
+    fn chord(notes: &[u8]) -> Vec<MidiEvent> {
+        notes
+            .iter()
+            .map(|&note| midi_event([0x90, note, 100], 0))
+            .collect()
+    }
+
+    #[test]
+    fn orders() {
+        let up_down: Vec<_> = (0..6)
+            .map(|count| ArpMode::UpDown.index(count, 3))
+            .collect();
+        assert_eq!(up_down, vec![0, 1, 2, 1, 0, 1]);
+        let down: Vec<_> = (0..4).map(|count| ArpMode::Down.index(count, 3)).collect();
+        assert_eq!(down, vec![2, 1, 0, 2]);
+        // With one note there's nowhere to go back down to.
+        assert!((0..4).all(|count| ArpMode::UpDown.index(count, 1) == 0));
+        assert!((0..100).all(|count| ArpMode::Random.index(count, 3) < 3));
+    }
+
+    #[test]
+    fn up_down() {
+        let mut arpeggiator = Arpeggiator::default();
+        let notes = played(
+            &mut arpeggiator,
+            &settings(ArpMode::UpDown),
+            &chord(&[67, 60, 64]),
+            6,
+        );
+        assert_eq!(notes, vec![60, 64, 67, 64, 60, 64]);
+
+        let mut arpeggiator = Arpeggiator::default();
+        let notes = played(
+            &mut arpeggiator,
+            &settings(ArpMode::UpDown),
+            &chord(&[60]),
+            3,
+        );
+        assert_eq!(notes, vec![60, 60, 60]);
+    }
+
+    #[test]
+    fn octaves() {
+        let mut arpeggiator = Arpeggiator::default();
+        let settings = ArpSettings {
+            octaves: 2,
+            ..settings(ArpMode::Up)
+        };
+        let notes = played(&mut arpeggiator, &settings, &chord(&[60, 64]), 5);
+        assert_eq!(notes, vec![60, 64, 72, 76, 60]);
+    }
+
+    #[test]
+    fn latch() {
+        let mut arpeggiator = Arpeggiator::default();
+        let settings = ArpSettings {
+            latch: true,
+            ..settings(ArpMode::Up)
+        };
+        played(&mut arpeggiator, &settings, &chord(&[60, 64]), 2);
+        // Letting go keeps the pattern going, and a new chord replaces it.
+        let released = [midi_event([0x80, 60, 0], 0), midi_event([0x80, 64, 0], 0)];
+        assert_eq!(
+            played(&mut arpeggiator, &settings, &released, 2),
+            vec![60, 64]
+        );
+        assert_eq!(
+            played(&mut arpeggiator, &settings, &chord(&[62]), 2),
+            vec![62, 62]
+        );
+    }
+
+    #[test]
+    fn stop_lets_go() {
+        let mut arpeggiator = Arpeggiator::default();
+        played(&mut arpeggiator, &settings(ArpMode::Up), &chord(&[60]), 1);
+        let mut output = Vec::new();
+        arpeggiator.stop(&mut output);
+        assert_eq!(output.len(), 0);
+        // Held on past the gate, the note is still sounding when stopped.
+        let settings = ArpSettings {
+            gate: 1.0,
+            ..settings(ArpMode::Up)
+        };
+        played(&mut arpeggiator, &settings, &chord(&[60]), 1);
+        arpeggiator.stop(&mut output);
+        assert_eq!(output.len(), 1);
+        assert_eq!(output[0].data, [0x80, 60, 0]);
+    }
+}
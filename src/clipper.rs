@@ -0,0 +1,6 @@
+// A soft clipper for the very end of the signal chain.  Quiet signals pass
+// through almost untouched, while loud ones are rounded off towards ±1.0
+// instead of being chopped flat, which is what the host would do to them.
+pub fn soft_clip(sample: f32) -> f32 {
+    sample.tanh()
+}
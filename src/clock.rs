@@ -0,0 +1,82 @@
+use crate::midi::MidiMessage;
+use crate::tempo::Transport;
+use vst::event::MidiEvent;
+
+// MIDI clock is sent 24 times every quarter note.
+const TICKS_PER_BEAT: f64 = 24.0;
+
+// How much every new tick moves our idea of the tempo.  Clocks jitter by a
+// sample or two, so we average rather than follow each one.
+const SMOOTHING: f64 = 0.1;
+
+// Follows MIDI clock, for hosts that won't tell us their tempo and position
+// themselves.  All we get is a tick every 24th of a beat, so the tempo is
+// worked out from how far apart they are, and the position by counting
+// them since the last start.
+#[derive(Default)]
+pub struct MidiClock {
+    playing: bool,
+    // Which tick of the song the last one was, counting from 0 at start.
+    // `None` between a start and its first tick.
+    tick: Option<u64>,
+    // When the last tick came, in samples since we were loaded.
+    last_tick: Option<u64>,
+    // The average number of samples between ticks.
+    interval: Option<f64>,
+}
+
+impl MidiClock {
+    // Follow the clock messages among `events`, `now` being when the buffer
+    // they're for starts.
+    pub fn process(&mut self, events: &[MidiEvent], now: u64, sample_rate: f32) {
+        for ev in events {
+            let time = now + ev.delta_frames.max(0) as u64;
+            match MidiMessage::parse(&ev.data) {
+                Some(MidiMessage::Clock) => self.tick(time, sample_rate),
+                Some(MidiMessage::Start) => {
+                    self.playing = true;
+                    self.tick = None;
+                }
+                Some(MidiMessage::Continue) => self.playing = true,
+                Some(MidiMessage::Stop) => self.playing = false,
+                _ => (),
+            }
+        }
+    }
+
+    fn tick(&mut self, time: u64, sample_rate: f32) {
+        if let Some(last_tick) = self.last_tick {
+            let interval = (time - last_tick) as f64;
+            // A long gap means the clock stopped for a while rather than
+            // the tempo dropping to a crawl.
+            if interval < f64::from(sample_rate) {
+                self.interval = Some(match self.interval {
+                    Some(average) => average + (interval - average) * SMOOTHING,
+                    None => interval,
+                });
+            }
+        }
+        self.last_tick = Some(time);
+        if self.playing {
+            self.tick = Some(self.tick.map_or(0, |tick| tick + 1));
+        }
+    }
+
+    // Where the clock says we are at `now`, once there's been enough of it
+    // to go on.  Between ticks, the position is guessed from the tempo.
+    pub fn transport(&self, now: u64, sample_rate: f32) -> Option<Transport> {
+        let interval = self.interval?.max(1.0);
+        let since = self
+            .last_tick
+            .map_or(0.0, |last_tick| now.saturating_sub(last_tick) as f64);
+        let position = match self.tick {
+            Some(tick) => (tick as f64 + (since / interval).min(1.0)) / TICKS_PER_BEAT,
+            None => 0.0,
+        };
+        Some(Transport {
+            tempo: 60.0 * f64::from(sample_rate) / (interval * TICKS_PER_BEAT),
+            position: Some(position),
+            playing: self.playing,
+        })
+    }
+}
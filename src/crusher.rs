@@ -0,0 +1,46 @@
+// The deepest bit depth the crusher goes to.  At this setting it's bypassed,
+// since 16 bits is already as clean as a CD.
+pub const MAX_BITS: u32 = 16;
+
+// Lo-fi by throwing information away: the bit depth rounds every sample to
+// fewer levels, and downsampling holds each sample for several in a row,
+// which folds everything above the new, lower Nyquist back down as aliasing.
+#[derive(Default)]
+pub struct Crusher {
+    // How many more samples the held ones are repeated for.
+    countdown: u32,
+    held_left: f32,
+    held_right: f32,
+}
+
+impl Crusher {
+    pub fn process(&mut self, left: &mut f32, right: &mut f32, bits: u32, downsample: u32) {
+        if self.countdown == 0 {
+            self.held_left = quantize(*left, bits);
+            self.held_right = quantize(*right, bits);
+            self.countdown = downsample.max(1);
+        }
+        self.countdown -= 1;
+        *left = self.held_left;
+        *right = self.held_right;
+    }
+}
+
+fn quantize(sample: f32, bits: u32) -> f32 {
+    if bits >= MAX_BITS {
+        return sample;
+    }
+    // One bit goes on the sign, the rest on how loud it is.
+    let levels = (1 << (bits.max(1) - 1)) as f32;
+    (sample * levels).round() / levels
+}
+
+// Map a 0.0 to 1.0 parameter onto 1 to `MAX_BITS` bits.
+pub fn bit_depth(value: f32) -> u32 {
+    1 + (value * (MAX_BITS - 1) as f32).round() as u32
+}
+
+// Map a 0.0 to 1.0 parameter onto holding every sample 1 to 32 times.
+pub fn downsample_factor(value: f32) -> u32 {
+    1 + (value * 31.0).round() as u32
+}
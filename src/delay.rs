@@ -0,0 +1,102 @@
+use crate::range::{Curve, Range};
+
+// The longest delay we keep room for.  A whole note at 60 BPM is four
+// seconds, so that's enough for any synced division at a sensible tempo.
+pub const MAX_DELAY_SECONDS: f32 = 4.0;
+
+// A stereo feedback delay, built on a pair of circular buffers.
+pub struct Delay {
+    left: Vec<f32>,
+    right: Vec<f32>,
+    // Where the next sample gets written.
+    position: usize,
+}
+
+impl Delay {
+    pub fn new(sample_rate: f32) -> Self {
+        let mut delay = Self {
+            left: Vec::new(),
+            right: Vec::new(),
+            position: 0,
+        };
+        delay.set_sample_rate(sample_rate);
+        delay
+    }
+
+    // The buffers are sized from the sample rate, so they have to be
+    // reallocated whenever it changes.  This also clears out the echoes.
+    pub fn set_sample_rate(&mut self, sample_rate: f32) {
+        let length = (MAX_DELAY_SECONDS * sample_rate) as usize + 2;
+        self.left = vec![0.0; length];
+        self.right = vec![0.0; length];
+        self.position = 0;
+    }
+
+    // Forget all the echoes.
+    pub fn clear(&mut self) {
+        for sample in self.left.iter_mut().chain(self.right.iter_mut()) {
+            *sample = 0.0;
+        }
+    }
+
+    // Feed one stereo sample through the delay.  `delay` is in samples and
+    // may be fractional, so it can be smoothly changed while playing.
+    pub fn process(
+        &mut self,
+        left: &mut f32,
+        right: &mut f32,
+        delay: f32,
+        feedback: f32,
+        mix: f32,
+    ) {
+        let length = self.left.len();
+        if length < 2 {
+            return;
+        }
+
+        let delay = delay.max(1.0).min((length - 2) as f32);
+        let whole = delay as usize;
+        let fraction = delay - whole as f32;
+
+        // Read between the two samples `delay` samples ago.
+        let newer = (self.position + length - whole) % length;
+        let older = (newer + length - 1) % length;
+        let read = |buffer: &[f32]| buffer[newer] + (buffer[older] - buffer[newer]) * fraction;
+        let delayed_left = read(&self.left);
+        let delayed_right = read(&self.right);
+
+        self.left[self.position] = *left + delayed_left * feedback;
+        self.right[self.position] = *right + delayed_right * feedback;
+        self.position = (self.position + 1) % length;
+
+        *left += (delayed_left - *left) * mix;
+        *right += (delayed_right - *right) * mix;
+    }
+}
+
+// How long echoes that come around every `period` seconds, each time at
+// `feedback` times the level before, take to die away by 60 dB.
+pub fn ring_time(period: f32, feedback: f32) -> f32 {
+    if feedback > 0.0 {
+        period * (1.0 + 0.001f32.ln() / feedback.ln())
+    } else {
+        period
+    }
+}
+
+// A delay time of 1 ms to 2 seconds.
+pub const DELAY_TIME: Range = Range {
+    min: 0.001,
+    max: 2.0,
+    curve: Curve::Power(2.0),
+};
+
+pub fn delay_time(value: f32) -> f32 {
+    DELAY_TIME.map(value)
+}
+
+// Map a 0.0 to 1.0 parameter onto a feedback amount, stopping short of 1.0
+// so the echoes always die out eventually.
+pub fn delay_feedback(value: f32) -> f32 {
+    value * 0.95
+}
@@ -0,0 +1,110 @@
+use crate::clipper::soft_clip;
+use crate::gain::db_to_gain;
+
+// The transfer curves the drive section can push the signal through.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum DriveCurve {
+    Off,
+    SoftClip,
+    HardClip,
+    // Anything past ±1.0 is folded back down instead of flattened, which
+    // gets brighter and brighter the harder it's driven.
+    Foldback,
+    // Rounds the signal to a handful of levels, like a very cheap converter.
+    Bitcrush,
+}
+
+impl DriveCurve {
+    pub const ALL: [DriveCurve; 5] = [
+        DriveCurve::Off,
+        DriveCurve::SoftClip,
+        DriveCurve::HardClip,
+        DriveCurve::Foldback,
+        DriveCurve::Bitcrush,
+    ];
+
+    pub fn from_parameter(value: f32) -> Self {
+        let index = (value * Self::ALL.len() as f32) as usize;
+        Self::ALL[index.min(Self::ALL.len() - 1)]
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            DriveCurve::Off => "Off",
+            DriveCurve::SoftClip => "Soft Clip",
+            DriveCurve::HardClip => "Hard Clip",
+            DriveCurve::Foldback => "Foldback",
+            DriveCurve::Bitcrush => "Bitcrush",
+        }
+    }
+
+    pub fn shape(self, sample: f32) -> f32 {
+        match self {
+            DriveCurve::Off => sample,
+            DriveCurve::SoftClip => soft_clip(sample),
+            DriveCurve::HardClip => sample.max(-1.0).min(1.0),
+            DriveCurve::Foldback => 1.0 - ((sample + 1.0).rem_euclid(4.0) - 2.0).abs(),
+            DriveCurve::Bitcrush => (sample.max(-1.0).min(1.0) * 8.0).round() / 8.0,
+        }
+    }
+}
+
+// Whether every voice is driven on its own, which keeps chords clean, or
+// the whole mix at once, where notes distort into each other.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum DriveRouting {
+    Voice,
+    Mix,
+}
+
+impl DriveRouting {
+    pub const ALL: [DriveRouting; 2] = [DriveRouting::Voice, DriveRouting::Mix];
+
+    pub fn from_parameter(value: f32) -> Self {
+        let index = (value * Self::ALL.len() as f32) as usize;
+        Self::ALL[index.min(Self::ALL.len() - 1)]
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            DriveRouting::Voice => "Per Voice",
+            DriveRouting::Mix => "Mix Bus",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Drive {
+    pub curve: DriveCurve,
+    // Linear gains going into and coming out of the curve.
+    pub drive: f32,
+    pub trim: f32,
+}
+
+impl Drive {
+    pub fn from_parameters(curve: f32, drive: f32, trim: f32) -> Self {
+        Self {
+            curve: DriveCurve::from_parameter(curve),
+            drive: db_to_gain(drive_db(drive)),
+            trim: db_to_gain(trim_db(trim)),
+        }
+    }
+
+    pub fn process(&self, sample: f32) -> f32 {
+        if self.curve == DriveCurve::Off {
+            return sample;
+        }
+        self.curve.shape(sample * self.drive) * self.trim
+    }
+}
+
+// Map a 0.0 to 1.0 parameter onto 0 to 36 dB of gain into the curve.
+pub fn drive_db(value: f32) -> f32 {
+    value * 36.0
+}
+
+// Map a 0.0 to 1.0 parameter onto -24 to 24 dB of output trim, with the
+// middle of the knob leaving the level alone.
+pub fn trim_db(value: f32) -> f32 {
+    (value * 2.0 - 1.0) * 24.0
+}
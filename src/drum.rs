@@ -0,0 +1,172 @@
+use crate::noise::Noise;
+use crate::oscillator::semitones_to_ratio;
+use std::f32::consts::PI;
+
+// Which drum a note plays.  The ranges follow General MIDI, where the kick
+// is on 35 and 36, the snares on 38 and 40, and the hi-hats from 42 up.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum DrumKind {
+    Kick,
+    Snare,
+    Hat,
+}
+
+impl DrumKind {
+    pub fn for_note(note: u8) -> Self {
+        if note < 38 {
+            DrumKind::Kick
+        } else if note < 42 {
+            DrumKind::Snare
+        } else {
+            DrumKind::Hat
+        }
+    }
+
+    // A sine that drops quickly in pitch, plus a burst of noise.  Times are
+    // in seconds and are how long each part takes to fall to about a third.
+    fn sound(self) -> DrumSound {
+        match self {
+            DrumKind::Kick => DrumSound {
+                start_frequency: 150.0,
+                end_frequency: 45.0,
+                pitch_decay: 0.04,
+                tone_level: 1.0,
+                tone_decay: 0.3,
+                noise_level: 0.1,
+                noise_decay: 0.005,
+                bright: false,
+            },
+            DrumKind::Snare => DrumSound {
+                start_frequency: 250.0,
+                end_frequency: 180.0,
+                pitch_decay: 0.02,
+                tone_level: 0.5,
+                tone_decay: 0.08,
+                noise_level: 0.7,
+                noise_decay: 0.1,
+                bright: false,
+            },
+            DrumKind::Hat => DrumSound {
+                start_frequency: 0.0,
+                end_frequency: 0.0,
+                pitch_decay: 0.01,
+                tone_level: 0.0,
+                tone_decay: 0.01,
+                noise_level: 0.8,
+                noise_decay: 0.04,
+                bright: true,
+            },
+        }
+    }
+}
+
+struct DrumSound {
+    start_frequency: f32,
+    end_frequency: f32,
+    pitch_decay: f32,
+    tone_level: f32,
+    tone_decay: f32,
+    noise_level: f32,
+    noise_decay: f32,
+    // Whether the noise has its low end taken out, for a thinner, hissier sound.
+    bright: bool,
+}
+
+// The drum settings shared by all voices.
+#[derive(Clone, Copy, Debug)]
+pub struct DrumSettings {
+    // How much longer or shorter than usual every drum rings.
+    pub decay: f32,
+    // How many semitones up or down every drum is tuned.
+    pub tune: f32,
+}
+
+// A one-shot drum hit, playing on a voice until it has died away.
+#[derive(Default)]
+pub struct Drum {
+    active: bool,
+    phase: f32,
+    // The pitch sweep: how far above its resting frequency the sine still
+    // is, and that resting frequency, both as a fraction of the sample rate.
+    sweep: f32,
+    resting: f32,
+    tone: f32,
+    noise: f32,
+    // What each of the above gets multiplied by every sample.
+    pitch_coefficient: f32,
+    tone_coefficient: f32,
+    noise_coefficient: f32,
+    bright: bool,
+    last_noise: f32,
+    white: Noise,
+}
+
+impl Drum {
+    pub fn is_active(&self) -> bool {
+        self.active
+    }
+
+    pub fn stop(&mut self) {
+        self.active = false;
+    }
+
+    pub fn seed(&mut self, seed: u32) {
+        self.white.seed(seed);
+    }
+
+    pub fn trigger(&mut self, kind: DrumKind, settings: &DrumSettings, sample_rate: f32) {
+        let sound = kind.sound();
+        let tune = semitones_to_ratio(settings.tune);
+        let decay = |seconds: f32| (-1.0 / (seconds * settings.decay * sample_rate)).exp();
+
+        self.active = true;
+        self.phase = 0.0;
+        self.resting = sound.end_frequency * tune / sample_rate;
+        self.sweep = (sound.start_frequency - sound.end_frequency) * tune / sample_rate;
+        self.tone = sound.tone_level;
+        self.noise = sound.noise_level;
+        self.pitch_coefficient = decay(sound.pitch_decay);
+        self.tone_coefficient = decay(sound.tone_decay);
+        self.noise_coefficient = decay(sound.noise_decay);
+        self.bright = sound.bright;
+    }
+
+    pub fn next(&mut self) -> f32 {
+        if !self.active {
+            return 0.0;
+        }
+
+        let tone = (self.phase * 2.0 * PI).sin() * self.tone;
+        self.phase = (self.phase + self.resting + self.sweep).fract();
+
+        let mut noise = self.white.white();
+        if self.bright {
+            // The difference between one sample and the last only keeps
+            // what changes quickly, which is the highs.
+            let difference = (noise - self.last_noise) * 0.5;
+            self.last_noise = noise;
+            noise = difference;
+        }
+        let noise = noise * self.noise;
+
+        self.sweep *= self.pitch_coefficient;
+        self.tone *= self.tone_coefficient;
+        self.noise *= self.noise_coefficient;
+        // Once both parts are down by 60 dB, the hit is over.
+        if self.tone < 0.001 && self.noise < 0.001 {
+            self.active = false;
+        }
+
+        tone + noise
+    }
+}
+
+// Map a 0.0 to 1.0 parameter onto a quarter to four times the usual decay.
+pub fn drum_decay(value: f32) -> f32 {
+    4.0f32.powf(value * 2.0 - 1.0)
+}
+
+// Map a 0.0 to 1.0 parameter onto a tuning of an octave down to an octave up.
+pub fn drum_tune(value: f32) -> f32 {
+    ((value * 2.0 - 1.0) * 12.0).round()
+}
@@ -0,0 +1,1253 @@
+// The editor: a window of our own inside the host's, drawn by conrod on a
+// thread of its own.  `GUIWrapper` is what the host talks to, and it passes
+// everything on to that thread, where `GUI` keeps the window and sets its
+// widgets, page by page, in pages.rs.
+
+mod pages;
+
+use crate::about::{Logo, LINKS};
+use crate::fonts::Fonts;
+use crate::history::History;
+use crate::keyboard::{key_character, Keyboard};
+use crate::language::Language;
+use crate::matrix::MATRIX_SLOTS;
+use crate::params;
+use crate::preset::PresetEntry;
+use crate::settings::Settings;
+use crate::skin::Skin;
+use crate::spectrum::Analyzer;
+use crate::support;
+use crate::telemetry::Telemetry;
+use crate::theme::Theme;
+use crate::widgets::Grip;
+use crate::WhisperParameters;
+use conrod_core::{widget, Colorable, Labelable, Positionable, Sizeable, Ui, Widget};
+use conrod_glium::Renderer;
+use glium::glutin::event_loop::EventLoop;
+use glium::glutin::window::WindowBuilder;
+use glium::Surface;
+use pages::{
+    about_box, browser_page, file_boxes, knob_page, matrix_page, pad_and_envelope, preset_bar,
+    program_buttons, settings_page, signal_views, status_line, steps_page, tooltip, Page,
+};
+use std::mem;
+use std::os::raw::c_void;
+use std::path::Path;
+use std::ptr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+use vst::editor::{Editor, Key, KeyCode};
+use vst::host::{Host, OpCode};
+use vst::plugin::HostCallback;
+use winapi::shared::windef::HWND;
+use winapi::um::winuser::{PeekMessageW, MSG, PM_NOREMOVE, PM_QS_SENDMESSAGE};
+use winit::event_loop::ControlFlow;
+use winit::platform::desktop::EventLoopExtDesktop;
+use winit::platform::windows::{EventLoopExtWindows, WindowBuilderExtWindows};
+
+// The smallest the editor goes, and how big it starts.  Anything more it's
+// given goes to the panel.  These are logical pixels, like every size here;
+// the monitor's scale factor only comes in when it's drawn, in support.rs.
+const WIDTH: u32 = 560;
+const HEIGHT: u32 = 560;
+const GRIP_SIZE: f64 = 12.0;
+
+// Every parameter has a knob in the panel across the middle, which scrolls
+// to fit them all.  Its widgets are made from the parameter list, so a new
+// parameter shows up there without anything to add here.
+const PANEL_WIDTH: f64 = 380.0;
+const PANEL_HEIGHT: f64 = 256.0;
+const PANEL_COLUMNS: usize = 4;
+const ROW_HEIGHT: f64 = 76.0;
+const KNOB_SIZE: f64 = 40.0;
+
+// The pages of the editor, picked from the tabs along the top.  Each has
+// the knobs for some of the parts of the synth.
+#[derive(Clone, Copy, PartialEq)]
+enum Tab {
+    Synth,
+    Fx,
+    Mod,
+    Steps,
+    Matrix,
+    Settings,
+}
+
+impl Tab {
+    const ALL: [Tab; 6] = [
+        Tab::Synth,
+        Tab::Fx,
+        Tab::Mod,
+        Tab::Steps,
+        Tab::Matrix,
+        Tab::Settings,
+    ];
+
+    fn name(self) -> &'static str {
+        match self {
+            Tab::Synth => "Synth",
+            Tab::Fx => "FX",
+            Tab::Mod => "Mod",
+            Tab::Steps => "Steps",
+            Tab::Matrix => "Matrix",
+            Tab::Settings => "Settings",
+        }
+    }
+
+    // The page a parameter's knob is on.  The step and matrix pages have
+    // their own widgets instead of knobs, so none of them are on those.
+    fn of(category: params::Category) -> Self {
+        use params::Category::*;
+        match category {
+            Master | Oscillator | Amp | Filter | Voice | Sampler | Drums => Tab::Synth,
+            Effects => Tab::Fx,
+            Modulation | Arpeggiator | Sequencer => Tab::Mod,
+            Midi => Tab::Settings,
+        }
+    }
+}
+
+const TAB_WIDTH: f64 = 55.0;
+
+// How far the arrow keys move a knob without steps.
+const NUDGE: f32 = 0.01;
+
+const MIDI_LIGHT_TIME: Duration = Duration::from_millis(150);
+
+// What a file dropped on the editor gets loaded as, going by its extension.
+#[derive(Clone, Copy, PartialEq)]
+enum Dropped {
+    Preset,
+    Sample,
+    Other,
+}
+
+impl Dropped {
+    fn of(path: &Path) -> Self {
+        let extension = path
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .unwrap_or_default()
+            .to_lowercase();
+        match extension.as_str() {
+            "json" => Dropped::Preset,
+            "wav" | "aif" | "aiff" | "aifc" => Dropped::Sample,
+            _ => Dropped::Other,
+        }
+    }
+
+    fn description(self) -> &'static str {
+        match self {
+            Dropped::Preset => "Drop to load the preset",
+            Dropped::Sample => "Drop to load the sample",
+            Dropped::Other => "Only presets and samples can be dropped here",
+        }
+    }
+}
+
+// The knob the mouse is resting on, since when, and whether its tooltip has
+// been drawn yet.
+struct Hover {
+    parameter: usize,
+    since: Instant,
+    shown: bool,
+}
+
+// The scope and the spectrum go under the panel.  The scope shows this many
+// of the samples it's sent.
+const SCOPE_LENGTH: usize = 512;
+const SCOPE_HEIGHT: f64 = 72.0;
+
+// On the synth's page, one cycle of the oscillator goes between them.
+const CYCLE_LENGTH: usize = 256;
+
+// The meters go to the right of them.
+const METER_WIDTH: f64 = 30.0;
+
+// How many of the newest samples are kept for the spectrum.
+const TAP_LENGTH: usize = 2048;
+
+// How many times in a row the window is made again after losing its
+// context, before the editor gives up on it.
+const MAX_RECREATIONS: usize = 3;
+
+widget_ids!(struct Ids { background, panel, panel_scrollbar, knobs[], knob_readings[], sample_path, sample_status, tuning_path, tuning_status, preset_name, preset_save, preset_load, preset_status, panic, init, randomize, compare, copy_a_to_b, undo, redo, telemetry, xy_pad, xy_pad_x, xy_pad_y, envelope, scope, spectrum, meter, browse, preset_list, browser_load, browser_rename, browser_delete, tabs[], grip, ui_scale_label, ui_scale, theme_label, theme, tooltip_delay_label, tooltip_delay, tooltip, tooltip_text, value_entry, octave, drop_highlight, drop_text, midi_light, cycle, step_grid, language_label, language, frame_rate_label, frame_rate, logo, about_shade, about_panel, about_title, about_build, about_audio, about_links[], about_close, matrix_numbers[], matrix_sources[], matrix_destinations[], matrix_amounts[] });
+
+pub struct GUIWrapper {
+    params: Arc<WhisperParameters>,
+    // The editor's own thread, while it's open.
+    editor: Option<EditorThread>,
+    // For telling the host about edits made there.
+    host: HostCallback,
+    // Kept here rather than in `GUI`, so closing the editor and opening it
+    // again doesn't lose it.  The editor's thread has it while it's open,
+    // and hands it back when it's done.
+    history: History,
+    shared: Arc<Shared>,
+}
+
+// What the host's thread and the editor's both need.
+struct Shared {
+    // How big the window's been made, in its own units, which are the UI
+    // scale times bigger than the widgets'.  Hosts ask before the editor's
+    // open, and the editor changes it.
+    size: Mutex<(i32, i32)>,
+    // The computer's keyboard, which plays from the host's keys and from
+    // the window's own.
+    keyboard: Mutex<Keyboard>,
+    // Whether the keys are going into a text box rather than being played,
+    // and whether there's a knob for the arrow keys to move, so the host
+    // can be told straight away whether a key was taken.
+    typing: AtomicBool,
+    focused: AtomicBool,
+}
+
+struct EditorThread {
+    thread: thread::JoinHandle<History>,
+    requests: mpsc::Receiver<HostRequest>,
+    messages: mpsc::Sender<EditorMessage>,
+    // Whether its window could be made, and then it's hung up when the
+    // thread's done.
+    status: mpsc::Receiver<bool>,
+}
+
+// What the editor's thread needs from the host.  The host is only spoken to
+// from its own thread, so these wait there for `idle`.
+enum HostRequest {
+    BeginEdit(i32),
+    Automate(i32, f32),
+    EndEdit(i32),
+    // A new size for the host's window around the editor.  The editor waits
+    // to hear whether it was made.
+    SizeWindow(i32, i32),
+    // The window was closed from its own side.
+    Closed,
+}
+
+// And what the host's thread tells the editor's.
+enum EditorMessage {
+    // The size asked for, and whether the host's window is that size now.
+    Sized(i32, i32, bool),
+    // One of the arrow keys the host passed on, up or down.
+    Nudge(bool),
+    // Something changed that the window's events don't say, like a note
+    // played from the host's keys.
+    Refresh,
+    Close,
+}
+
+// Stands in for the host on the editor's thread, so `History` can tell it
+// about edits the same way from there.
+struct HostProxy(mpsc::Sender<HostRequest>);
+
+impl HostProxy {
+    fn request(&self, request: HostRequest) {
+        // The host's side is only gone once the editor's been closed.
+        let _ = self.0.send(request);
+    }
+}
+
+impl Host for HostProxy {
+    fn automate(&self, index: i32, value: f32) {
+        self.request(HostRequest::Automate(index, value));
+    }
+
+    fn begin_edit(&self, index: i32) {
+        self.request(HostRequest::BeginEdit(index));
+    }
+
+    fn end_edit(&self, index: i32) {
+        self.request(HostRequest::EndEdit(index));
+    }
+}
+
+// Everything to do with the graphics card, which goes with the window
+// it's drawn in.  It's all made again together when the context's lost,
+// and put away in the order it's in here: the textures while there's still
+// a context for them, and then the window.
+struct Graphics {
+    renderer: Renderer,
+    // The image map describing each of our widget->image mappings, which
+    // are whatever the skin has pictures for.
+    image_map: conrod_core::image::Map<glium::texture::Texture2d>,
+    skin: Skin,
+    logo: Option<Logo>,
+    display: support::GliumDisplayWinitWrapper,
+}
+
+impl Graphics {
+    // A new window in the host's, `size` in its own units, with nothing
+    // drawn in it yet.
+    fn new(parent: HWND, size: (i32, i32), scale: f64, event_loop: &EventLoop<()>) -> Option<Self> {
+        let window = WindowBuilder::new()
+            .with_title("A fantastic window!")
+            .with_decorations(false)
+            .with_resizable(false)
+            .with_parent_window(parent)
+            .with_inner_size((size.0 as u32, size.1 as u32).into());
+
+        let context = glium::glutin::ContextBuilder::new();
+
+        let display = glium::Display::new(window, context, event_loop).ok()?;
+        let display = support::GliumDisplayWinitWrapper(display, scale);
+
+        let renderer = Renderer::new(&display.0).ok()?;
+
+        let mut image_map = conrod_core::image::Map::<glium::texture::Texture2d>::new();
+        let skin = Skin::load(&display.0, &mut image_map);
+        let logo = Logo::load(&display.0, &mut image_map);
+
+        Some(Self {
+            renderer,
+            image_map,
+            skin,
+            logo,
+            display,
+        })
+    }
+}
+
+struct GUI {
+    // Its windows go with it, so it's the last thing put away.
+    event_loop: EventLoop<()>,
+    // The host's window, for a new one of ours to go in.
+    parent: HWND,
+    graphics: Graphics,
+    // Whether drawing failed, which is usually the context being lost when
+    // the graphics driver's been reset.
+    lost: bool,
+    // How many times in a row it's all been made again without a frame
+    // being drawn since.
+    recreations: usize,
+    ids: Ids,
+    ui: Ui,
+    fonts: Fonts,
+    // What's typed into the sample path box, and how loading it went.
+    sample_path: String,
+    sample_status: String,
+    // The same for the tuning file.
+    tuning_path: String,
+    tuning_status: String,
+    // The name of the preset to save or load, and how that went.
+    preset_name: String,
+    preset_status: String,
+    // What the audio thread last measured.
+    telemetry: Telemetry,
+    // The newest samples played, and the spectrum of them.  The scope gets
+    // twice as many as it shows, so it has room to find where to start.
+    tap: Vec<f32>,
+    analyzer: Analyzer,
+    scope: Vec<f32>,
+    // Which parameters were last changed by the host rather than here.
+    automated: Vec<bool>,
+    // Whether the preset browser is showing instead of the knobs, what's
+    // in it, and which of them is picked.
+    browsing: bool,
+    presets: Vec<PresetEntry>,
+    selected: Option<usize>,
+    settings: Settings,
+    hover: Option<Hover>,
+    // The parameter whose value is being typed in, and what's typed so far.
+    editing: Option<(usize, String)>,
+    // The knob that was last touched, for the arrow keys to move.
+    focused: Option<usize>,
+    // Whether something's changed that the window wouldn't otherwise know
+    // about, like a key the host passed on.
+    refresh: bool,
+    // What a file being dragged over the window would be loaded as.
+    hovering: Option<Dropped>,
+    // When MIDI last came in.
+    midi_seen: Option<Instant>,
+    // When the editor was last drawn, or would have been if nothing had
+    // changed.
+    last_frame: Instant,
+    // Whether the about box is open, over everything else.
+    about: bool,
+    // A UI scale that's waiting on the host to make its window fit it.
+    rescaling: Option<u32>,
+}
+
+impl GUI {
+    fn new(
+        parent: HWND,
+        size: (i32, i32),
+        sample_path: String,
+        tuning_path: String,
+        preset_name: String,
+        count: usize,
+    ) -> Option<Self> {
+        // It's made on the editor's own thread rather than the host's, which
+        // winit only allows when it's asked to.
+        let event_loop = EventLoop::new_any_thread();
+
+        let settings = Settings::load();
+        let scale = settings.scale();
+        let graphics = Graphics::new(parent, size, scale, &event_loop)?;
+
+        let mut ui =
+            conrod_core::UiBuilder::new([f64::from(size.0) / scale, f64::from(size.1) / scale])
+                .build();
+        let mut ids = Ids::new(ui.widget_id_generator());
+        ids.knobs.resize(count, &mut ui.widget_id_generator());
+        ids.knob_readings
+            .resize(count, &mut ui.widget_id_generator());
+        ids.tabs
+            .resize(Tab::ALL.len(), &mut ui.widget_id_generator());
+        ids.about_links
+            .resize(LINKS.len(), &mut ui.widget_id_generator());
+        ids.matrix_numbers
+            .resize(MATRIX_SLOTS, &mut ui.widget_id_generator());
+        ids.matrix_sources
+            .resize(MATRIX_SLOTS, &mut ui.widget_id_generator());
+        ids.matrix_destinations
+            .resize(MATRIX_SLOTS, &mut ui.widget_id_generator());
+        ids.matrix_amounts
+            .resize(MATRIX_SLOTS, &mut ui.widget_id_generator());
+
+        let fonts = Fonts::load(&mut ui.fonts);
+
+        Some(Self {
+            event_loop,
+            parent,
+            graphics,
+            lost: false,
+            recreations: 0,
+            ids,
+            ui,
+            fonts,
+            sample_path,
+            sample_status: String::new(),
+            tuning_path,
+            tuning_status: String::new(),
+            preset_name,
+            preset_status: String::new(),
+            telemetry: Telemetry::default(),
+            tap: vec![0.0; TAP_LENGTH],
+            analyzer: Analyzer::default(),
+            scope: vec![0.0; SCOPE_LENGTH * 2],
+            automated: vec![false; count],
+            browsing: false,
+            presets: Vec::new(),
+            selected: None,
+            settings,
+            hover: None,
+            editing: None,
+            focused: None,
+            refresh: false,
+            hovering: None,
+            midi_seen: None,
+            last_frame: Instant::now(),
+            about: false,
+            rescaling: None,
+        })
+    }
+
+    // The editor's thread, until the host closes the editor or the window's
+    // closed.  Frames come at the rate picked on the settings page, however
+    // often the host calls `idle`, and everything that happens in between
+    // waits for the next: the window's events in its queue, and the host's
+    // changes and the audio thread's readings in theirs.
+    fn run(
+        &mut self,
+        params: &WhisperParameters,
+        history: &mut History,
+        host: &HostProxy,
+        shared: &Shared,
+        messages: &mpsc::Receiver<EditorMessage>,
+    ) {
+        loop {
+            let frame_time = Duration::from_secs(1) / self.settings.frame_rate.max(1);
+            let elapsed = self.last_frame.elapsed();
+            if elapsed < frame_time {
+                thread::sleep(frame_time - elapsed);
+            }
+            self.last_frame = Instant::now();
+
+            for message in messages.try_iter() {
+                match message {
+                    EditorMessage::Sized(width, height, sized) => self.sized(width, height, sized),
+                    EditorMessage::Nudge(up) => self.nudge(params, history, host, up),
+                    EditorMessage::Refresh => self.refresh = true,
+                    EditorMessage::Close => {
+                        history.end_gesture(host);
+                        return;
+                    }
+                }
+            }
+            let closed = self.frame(params, history, host, shared);
+            if closed || (self.lost && !self.recreate(shared)) {
+                history.end_gesture(host);
+                host.request(HostRequest::Closed);
+                return;
+            }
+        }
+    }
+
+    // When the context's been lost, nothing on the graphics card can be
+    // trusted, so it's all made again in a new window: the renderer and its
+    // glyph cache that the fonts are drawn from, and the skin's and the
+    // logo's textures.  Whether that worked.
+    fn recreate(&mut self, shared: &Shared) -> bool {
+        // A driver that's gone for good would have it making new windows
+        // every frame, so after a few tries the editor gives up and closes.
+        if self.recreations >= MAX_RECREATIONS {
+            return false;
+        }
+        self.recreations += 1;
+        let size = *shared.size.lock().unwrap();
+        let scale = self.graphics.display.1;
+        match Graphics::new(self.parent, size, scale, &self.event_loop) {
+            Some(graphics) => {
+                self.graphics = graphics;
+                self.lost = false;
+                self.ui.needs_redraw();
+                self.refresh = true;
+                true
+            }
+            None => false,
+        }
+    }
+
+    // Put it all away in order, rather than leaving it to however the
+    // fields happen to be dropped: the graphics first, while the window's
+    // context is still there for them, and the event loop the window
+    // belongs to last.
+    fn close(self) {
+        let GUI {
+            event_loop,
+            graphics,
+            ..
+        } = self;
+        drop(graphics);
+        drop(event_loop);
+    }
+
+    // The host's answer about a new size for its window.  The editor only
+    // follows it when it's been made, so it always fits, and that goes for
+    // a new UI scale too.
+    fn sized(&mut self, width: i32, height: i32, sized: bool) {
+        let rescaling = self.rescaling.take();
+        if !sized {
+            return;
+        }
+        if let Some(scale) = rescaling {
+            self.graphics.display.1 = f64::from(scale) / 100.0;
+            self.settings.scale = scale;
+            self.settings.save();
+        }
+        self.graphics
+            .display
+            .0
+            .gl_window()
+            .window()
+            .set_inner_size((width as u32, height as u32).into());
+    }
+
+    // Move the knob that was last touched by a step, or for parameters
+    // without steps, by `NUDGE` of the way.  Each one can be undone.
+    fn nudge(
+        &mut self,
+        params: &WhisperParameters,
+        history: &mut History,
+        host: &HostProxy,
+        up: bool,
+    ) {
+        let index = match self.focused {
+            Some(index) => index,
+            None => return,
+        };
+        let step = match params.defs[index].steps {
+            Some(steps) if steps > 1 => 1.0 / (steps - 1) as f32,
+            _ => NUDGE,
+        };
+        let parameter = index as i32;
+        let value = params.get_parameter(parameter) + if up { step } else { -step };
+        history.edit(params, host, parameter, value.max(0.0).min(1.0));
+        history.end_gesture(host);
+        self.automated[index] = false;
+        self.refresh = true;
+    }
+
+    // Handle everything that's happened since the last frame, and draw it if
+    // it needs drawing.  This is whether the window was closed.
+    fn frame(
+        &mut self,
+        params: &WhisperParameters,
+        history: &mut History,
+        host: &HostProxy,
+        shared: &Shared,
+    ) -> bool {
+        use winit::event;
+
+        let mut closed = false;
+        let display = &mut self.graphics.display;
+        let ui = &mut self.ui;
+        let ids = &mut self.ids;
+        let renderer = &mut self.graphics.renderer;
+        let image_map = &mut self.graphics.image_map;
+        let sample_path = &mut self.sample_path;
+        let sample_status = &mut self.sample_status;
+        let tuning_path = &mut self.tuning_path;
+        let tuning_status = &mut self.tuning_status;
+        let preset_name = &mut self.preset_name;
+        let preset_status = &mut self.preset_status;
+        // The meters and the scope move whenever something's played,
+        // and whatever the host changed has to be drawn too, even when
+        // nothing has happened in the window itself.
+        let mut redraw = mem::replace(&mut self.refresh, false);
+        if let Some(telemetry) = params.telemetry() {
+            if telemetry.midi {
+                self.midi_seen = Some(Instant::now());
+            }
+            // Silence doesn't need drawing again once the spectrum's
+            // peaks have fallen.
+            let still = telemetry.is_silent()
+                && self.telemetry.is_silent()
+                && telemetry.step == self.telemetry.step
+                && self.analyzer.settled();
+            self.telemetry = telemetry;
+            redraw |= !still;
+        }
+        // It has to go out again even if nothing else comes in.
+        let midi_lit = self
+            .midi_seen
+            .map_or(false, |seen| seen.elapsed() < MIDI_LIGHT_TIME);
+        redraw |= midi_lit;
+        let telemetry = &self.telemetry;
+        params.tap(&mut self.tap);
+        let extra = self.tap.len().saturating_sub(TAP_LENGTH);
+        self.tap.drain(..extra);
+        // The FFT is only worth doing for a frame that's drawn.
+        if redraw {
+            self.analyzer.analyze(&self.tap, params.sample_rate.get());
+        }
+        params.scope(&mut self.scope);
+        let extra = self.scope.len().saturating_sub(SCOPE_LENGTH * 2);
+        self.scope.drain(..extra);
+        let scope = &self.scope;
+        let analyzer = &self.analyzer;
+        for index in 0..params.parameter_count() {
+            if params.take_change(index) {
+                redraw = true;
+                self.automated[index as usize] = true;
+            }
+        }
+        let automated = &mut self.automated;
+        let browsing = &mut self.browsing;
+        let presets = &mut self.presets;
+        let selected = &mut self.selected;
+        // A tooltip that's due has to be drawn without anything
+        // happening, since the point is the mouse staying still.
+        let delay = Duration::from_millis(self.settings.tooltip_delay.into());
+        if let Some(hover) = &self.hover {
+            if !hover.shown && hover.since.elapsed() >= delay {
+                redraw = true;
+            }
+        }
+        let hover = &mut self.hover;
+        let editing = &mut self.editing;
+        let focused = &mut self.focused;
+        let hovering = &mut self.hovering;
+        let keyboard = &shared.keyboard;
+        let settings = &mut self.settings;
+        let skin = &self.graphics.skin;
+        let fonts = &self.fonts;
+        let logo = self.graphics.logo;
+        let lost = &mut self.lost;
+        let recreations = &mut self.recreations;
+        let about = &mut self.about;
+        let size = &shared.size;
+        let rescaling = &mut self.rescaling;
+        let end = &mut closed;
+        self.event_loop
+            .run_return(move |event, _, control_flow| match event {
+                event::Event::WindowEvent {
+                    event: event::WindowEvent::CloseRequested,
+                    window_id,
+                } if window_id == display.0.gl_window().window().id() => {
+                    *end = true;
+                    *control_flow = ControlFlow::Exit
+                }
+                event::Event::EventsCleared if !redraw => *control_flow = ControlFlow::Exit,
+                _ => {
+                    if let event::Event::EventsCleared = event {
+                        *control_flow = ControlFlow::Exit;
+                        redraw = false;
+                    } else {
+                        match &event {
+                            event::Event::WindowEvent {
+                                event: event::WindowEvent::Resized(new_size),
+                                ..
+                            } => {
+                                *size.lock().unwrap() =
+                                    (new_size.width as i32, new_size.height as i32)
+                            }
+                            // When the window has the focus itself, the
+                            // host never hears about the keys, so they're
+                            // played from here.
+                            event::Event::WindowEvent {
+                                event: event::WindowEvent::KeyboardInput { input, .. },
+                                ..
+                            } => {
+                                let typing = ui
+                                    .global_input()
+                                    .current
+                                    .widget_capturing_keyboard
+                                    .is_some();
+                                match input.virtual_keycode.and_then(key_character) {
+                                    Some(key) if !typing => {
+                                        match input.state {
+                                            event::ElementState::Pressed => {
+                                                keyboard.lock().unwrap().press(params, key)
+                                            }
+                                            event::ElementState::Released => {
+                                                keyboard.lock().unwrap().release(params, key)
+                                            }
+                                        };
+                                        redraw = true;
+                                    }
+                                    _ => {}
+                                }
+                            }
+                            // Files can be dragged in from outside, with a
+                            // highlight over everything while they're over
+                            // the window saying what dropping one will do.
+                            event::Event::WindowEvent {
+                                event: event::WindowEvent::HoveredFile(path),
+                                ..
+                            } => {
+                                *hovering = Some(Dropped::of(path));
+                                redraw = true;
+                            }
+                            event::Event::WindowEvent {
+                                event: event::WindowEvent::HoveredFileCancelled,
+                                ..
+                            } => {
+                                *hovering = None;
+                                redraw = true;
+                            }
+                            event::Event::WindowEvent {
+                                event: event::WindowEvent::DroppedFile(path),
+                                ..
+                            } => {
+                                let language = Language::named(&settings.language);
+                                match Dropped::of(path) {
+                                    Dropped::Preset => history.edit_all(params, host, || {
+                                        *preset_status = match params.load_preset_file(path) {
+                                            Ok(()) => language.text("Preset loaded").to_string(),
+                                            Err(error) => error.to_string(),
+                                        };
+                                    }),
+                                    Dropped::Sample => {
+                                        *sample_path = path.to_string_lossy().into_owned();
+                                        *sample_status = match params.load_sample(sample_path) {
+                                            Ok(()) => language.text("Sample loaded").to_string(),
+                                            Err(error) => error.to_string(),
+                                        };
+                                    }
+                                    Dropped::Other => {}
+                                }
+                                *hovering = None;
+                                redraw = true;
+                            }
+                            // Moving to a monitor with another scale factor
+                            // leaves the logical size alone, but there are
+                            // more or fewer pixels to draw it all with, so
+                            // the text is drawn again from scratch, with a
+                            // glyph cache big enough for it.  A renderer that
+                            // can't be made is a lost context like any other.
+                            event::Event::WindowEvent {
+                                event: event::WindowEvent::HiDpiFactorChanged(_),
+                                ..
+                            } => {
+                                match Renderer::new(&display.0) {
+                                    Ok(new_renderer) => *renderer = new_renderer,
+                                    Err(_) => *lost = true,
+                                }
+                                ui.needs_redraw();
+                                redraw = true;
+                            }
+                            _ => {}
+                        }
+                        let input = match support::convert_event(event, display) {
+                            None => return,
+                            Some(input) => input,
+                        };
+
+                        // Handle the input with the `Ui`.  The widgets are
+                        // set once it's all in, instead of after every
+                        // event, so a burst of mouse moves costs one frame.
+                        ui.handle_event(support::scale_input(input, display.1));
+                        redraw = true;
+                        return;
+                    }
+
+                    // Conrod's widgets go by the theme too, so it's handed
+                    // on whenever it's been changed, and the first time.
+                    // Its font is whichever the language's own name can be
+                    // written in, which has everything else in it too.
+                    let language = Language::named(&settings.language);
+                    let theme = Theme::named(&settings.theme);
+                    let font = fonts.for_text(language.name());
+                    if ui.theme.name != theme.name || ui.theme.font_id != Some(font) {
+                        ui.theme = theme.conrod();
+                        ui.theme.font_id = Some(font);
+                        ui.needs_redraw();
+                    }
+
+                    // Set the widgets.
+                    let ui = &mut ui.set_widgets();
+
+                    // The skin's background goes first, so it's under the
+                    // rest.
+                    if let Some(background) = skin.background {
+                        widget::Image::new(background)
+                            .wh_of(ui.window)
+                            .middle_of(ui.window)
+                            .graphics_for(ui.window)
+                            .set(ids.background, ui);
+                    }
+
+                    let tab = Tab::ALL.get(settings.tab).cloned().unwrap_or(Tab::Synth);
+                    for (place, page) in Tab::ALL.iter().enumerate() {
+                        let button = widget::Button::new()
+                            .label(language.text(page.name()))
+                            .label_font_size(theme.font_size)
+                            .w_h(TAB_WIDTH, 24.0)
+                            .color(if *page == tab {
+                                theme.accent
+                            } else {
+                                theme.button
+                            });
+                        let button = match place {
+                            0 => button.top_left_with_margins_on(ui.window, 78.0, 10.0),
+                            _ => button.right_from(ids.tabs[place - 1], 4.0),
+                        };
+                        for _click in button.set(ids.tabs[place], ui) {
+                            settings.tab = place;
+                            settings.save();
+                        }
+                    }
+
+                    // The logo at the other end opens the about box.
+                    // A question mark does instead if it didn't load.
+                    let clicked = match logo {
+                        Some(logo) => widget::Button::image(logo.normal)
+                            .hover_image(logo.hover)
+                            .press_image(logo.press)
+                            .w_h(24.0, 24.0)
+                            .top_right_with_margins_on(ui.window, 78.0, 10.0)
+                            .set(ids.logo, ui)
+                            .was_clicked(),
+                        None => widget::Button::new()
+                            .label("?")
+                            .label_font_size(theme.font_size)
+                            .w_h(24.0, 24.0)
+                            .top_right_with_margins_on(ui.window, 78.0, 10.0)
+                            .set(ids.logo, ui)
+                            .was_clicked(),
+                    };
+                    if clicked {
+                        *about = true;
+                    }
+
+                    // Z and X move it.
+                    let octave = keyboard.lock().unwrap().octave;
+                    widget::Text::new(&language.fill("Octave {}", &[&octave]))
+                        .right_from(ids.tabs[Tab::ALL.len() - 1], 10.0)
+                        .color(theme.text)
+                        .font_size(theme.font_size)
+                        .set(ids.octave, ui);
+
+                    // The panel takes up whatever the editor has been
+                    // made bigger by.
+                    let mut page = Page {
+                        params,
+                        history: &mut *history,
+                        host,
+                        ids: &*ids,
+                        automated: &mut automated[..],
+                        language,
+                        theme,
+                        fonts,
+                        skin,
+                        width: PANEL_WIDTH + ui.win_w - f64::from(WIDTH),
+                        height: PANEL_HEIGHT + ui.win_h - f64::from(HEIGHT),
+                    };
+
+                    if !*browsing && tab == Tab::Steps {
+                        *hover = None;
+                        *editing = None;
+                        steps_page(ui, &mut page, telemetry.step);
+                    } else if !*browsing && tab == Tab::Matrix {
+                        *hover = None;
+                        *editing = None;
+                        matrix_page(ui, &mut page);
+                    } else if !*browsing {
+                        knob_page(ui, &mut page, tab, hover, editing, focused);
+                    } else {
+                        *hover = None;
+                        *editing = None;
+                        browser_page(ui, &mut page, presets, selected, preset_name, preset_status);
+                    }
+
+                    signal_views(ui, &page, tab, scope, analyzer, telemetry);
+                    if tab == Tab::Synth {
+                        pad_and_envelope(ui, &mut page);
+                    }
+                    if tab == Tab::Settings {
+                        redraw |= settings_page(ui, &page, settings, size, rescaling);
+                    }
+
+                    file_boxes(
+                        ui,
+                        &page,
+                        sample_path,
+                        sample_status,
+                        tuning_path,
+                        tuning_status,
+                    );
+                    preset_bar(
+                        ui,
+                        &mut page,
+                        preset_name,
+                        preset_status,
+                        presets,
+                        selected,
+                        browsing,
+                    );
+                    program_buttons(ui, &mut page);
+
+                    // A drag is over once the mouse button comes up, and
+                    // everything it did is one step to undo.
+                    if ui.global_input().current.mouse.buttons.left().is_up() {
+                        page.history.end_gesture(host);
+                    }
+
+                    status_line(ui, &page, preset_status, telemetry, midi_lit);
+
+                    // Dragging the grip resizes the window, and the host's
+                    // window around it, and the widgets follow when the
+                    // window says it's been resized.
+                    if let Some([width, height]) = Grip::new()
+                        .w_h(GRIP_SIZE, GRIP_SIZE)
+                        .bottom_right_with_margin_on(ui.window, 2.0)
+                        .set(ids.grip, ui)
+                    {
+                        let (width, height) = scaled(
+                            width.max(f64::from(WIDTH)),
+                            height.max(f64::from(HEIGHT)),
+                            display.1,
+                        );
+                        if (width, height) != *size.lock().unwrap() {
+                            host.request(HostRequest::SizeWindow(width, height));
+                        }
+                    }
+
+                    if let Some(dropped) = *hovering {
+                        widget::Rectangle::fill_with(
+                            [ui.win_w, ui.win_h],
+                            theme.accent.alpha(0.25),
+                        )
+                        .middle_of(ui.window)
+                        .graphics_for(ui.window)
+                        .set(ids.drop_highlight, ui);
+                        widget::Text::new(language.text(dropped.description()))
+                            .middle_of(ids.drop_highlight)
+                            .color(theme.text)
+                            .font_size(theme.font_size + 4)
+                            .graphics_for(ui.window)
+                            .set(ids.drop_text, ui);
+                    }
+
+                    if *about {
+                        about_box(ui, &page, about);
+                    }
+
+                    // Set last, so it's over everything.
+                    tooltip(ui, &page, hover, delay);
+
+                    // Draw the `Ui` if it has changed.
+                    if let Some(primitives) = ui.draw_if_changed() {
+                        renderer.fill(display, primitives, image_map);
+                        let mut target = display.0.draw();
+                        let [red, green, blue, alpha] = theme.background.to_fsa();
+                        target.clear_color(red, green, blue, alpha);
+                        // The frame has to be finished even when drawing
+                        // it didn't work, and then it's tried again with
+                        // everything made anew.
+                        let drawn = renderer.draw(&display.0, &mut target, &image_map);
+                        let finished = target.finish();
+                        if drawn.is_err() || finished.is_err() {
+                            *lost = true;
+                        } else {
+                            *recreations = 0;
+                        }
+                    }
+                }
+            });
+        // For the host's keys, which it needs an answer about right away.
+        let input = self.ui.global_input();
+        let typing = input.current.widget_capturing_keyboard.is_some();
+        shared.typing.store(typing, Ordering::Relaxed);
+        shared
+            .focused
+            .store(self.focused.is_some(), Ordering::Relaxed);
+        closed
+    }
+}
+
+// Ask the host to make its window around the editor a new size.  There's
+// nothing for it on `HostCallback`, so it goes straight to the callback the
+// host gave us.  Hosts that can't do it say so, and then the editor stays
+// the size it is, rather than not fitting its window.
+fn size_window(host: &HostCallback, width: i32, height: i32) -> bool {
+    match host.raw_callback() {
+        Some(callback) => {
+            let opcode = OpCode::SizeWindow as i32;
+            let effect = host.raw_effect();
+            callback(effect, opcode, width, height as isize, ptr::null_mut(), 0.0) != 0
+        }
+        None => false,
+    }
+}
+
+// Wait for something from the editor's thread, or for it to hang up.  Its
+// window is a child of the host's, so making it and putting it away both
+// send the host's window messages and wait for them to be handled, and they
+// can only be handled on the host's thread, which is the one waiting here.
+// So it keeps looking for them while it waits.  Only messages sent like
+// that are handled: anything posted is left for the host to get to.
+fn wait_for<T>(receiver: &mpsc::Receiver<T>) -> Option<T> {
+    loop {
+        match receiver.recv_timeout(Duration::from_millis(1)) {
+            Ok(item) => return Some(item),
+            Err(mpsc::RecvTimeoutError::Disconnected) => return None,
+            Err(mpsc::RecvTimeoutError::Timeout) => unsafe {
+                let mut message: MSG = mem::zeroed();
+                let remove = PM_NOREMOVE | PM_QS_SENDMESSAGE;
+                PeekMessageW(&mut message, ptr::null_mut(), 0, 0, remove);
+            },
+        }
+    }
+}
+
+// The size of the window for widgets taking up `width` by `height`.
+fn scaled(width: f64, height: f64, scale: f64) -> (i32, i32) {
+    (
+        (width * scale).round() as i32,
+        (height * scale).round() as i32,
+    )
+}
+
+impl GUIWrapper {
+    pub fn new(params: Arc<WhisperParameters>, host: HostCallback) -> Self {
+        let size = scaled(WIDTH as f64, HEIGHT as f64, Settings::load().scale());
+        Self {
+            params,
+            host,
+            editor: None,
+            history: History::default(),
+            shared: Arc::new(Shared {
+                size: Mutex::new(size),
+                keyboard: Mutex::new(Keyboard::default()),
+                typing: AtomicBool::new(false),
+                focused: AtomicBool::new(false),
+            }),
+        }
+    }
+
+    // Pass on something the editor's thread asked for.  This is whether it
+    // was to say the window's been closed.
+    fn forward(&self, editor: &EditorThread, request: HostRequest) -> bool {
+        match request {
+            HostRequest::BeginEdit(index) => self.host.begin_edit(index),
+            HostRequest::Automate(index, value) => self.host.automate(index, value),
+            HostRequest::EndEdit(index) => self.host.end_edit(index),
+            HostRequest::SizeWindow(width, height) => {
+                let sized = size_window(&self.host, width, height);
+                let _ = editor
+                    .messages
+                    .send(EditorMessage::Sized(width, height, sized));
+            }
+            HostRequest::Closed => return true,
+        }
+        false
+    }
+
+    // Whether the editor's open to hear it.
+    fn send(&self, message: EditorMessage) -> bool {
+        self.editor
+            .as_ref()
+            .map_or(false, |editor| editor.messages.send(message).is_ok())
+    }
+
+    // The editor moves the knob, but whether there's one to move is known
+    // here, so a key with nothing to do goes back to the host.
+    fn nudge(&self, up: bool) -> bool {
+        self.shared.focused.load(Ordering::Relaxed) && self.send(EditorMessage::Nudge(up))
+    }
+
+    fn typing(&self) -> bool {
+        self.editor.is_some() && self.shared.typing.load(Ordering::Relaxed)
+    }
+
+    // The octave's shown in the window.
+    fn refresh(&self) {
+        self.send(EditorMessage::Refresh);
+    }
+}
+
+impl Editor for GUIWrapper {
+    // Hosts ask before the editor is open, to make a window for it.  This is
+    // in logical pixels, as winit gives them, which the host scales up for
+    // the monitor the same way it does its own windows.
+    fn size(&self) -> (i32, i32) {
+        *self.shared.size.lock().unwrap()
+    }
+
+    fn position(&self) -> (i32, i32) {
+        (0, 0)
+    }
+
+    // The editor draws itself on its own thread, at its own pace, so this
+    // only passes on what that's asked the host for.
+    fn idle(&mut self) {
+        let mut closed = false;
+        if let Some(editor) = &self.editor {
+            for request in editor.requests.try_iter() {
+                closed |= self.forward(editor, request);
+            }
+        }
+        if closed {
+            self.close();
+        }
+    }
+
+    // The editor's thread puts its window away before it stops, so once
+    // this is done there's nothing of it left, and opening it again starts
+    // from scratch.
+    fn close(&mut self) {
+        if let Some(editor) = self.editor.take() {
+            let _ = editor.messages.send(EditorMessage::Close);
+            while wait_for(&editor.status).is_some() {}
+            if let Ok(history) = editor.thread.join() {
+                self.history = history;
+            }
+            // It finishes whatever edit it was in the middle of on the way
+            // out, so the host isn't left waiting for the end of it.
+            for request in editor.requests.try_iter() {
+                self.forward(&editor, request);
+            }
+        }
+        self.shared.typing.store(false, Ordering::Relaxed);
+        self.shared.focused.store(false, Ordering::Relaxed);
+        self.shared
+            .keyboard
+            .lock()
+            .unwrap()
+            .release_all(&self.params);
+    }
+
+    fn open(&mut self, parent: *mut c_void) -> bool {
+        // Some hosts open it again without closing it first, and the old
+        // window has to be put away before there's a new one.
+        self.close();
+        // Whatever was measured while the GUI was closed is old news.
+        let _ = self.params.telemetry();
+        self.params.tap(&mut Vec::new());
+        let sample_path = self.params.sample_path.lock().unwrap().clone();
+        let tuning_path = self.params.tuning_path.lock().unwrap().clone();
+        let program = self.params.get_preset_name(self.params.get_preset_num());
+        let size = self.size();
+
+        // The window and everything drawn in it belong to the editor's own
+        // thread.  What it needs from the host comes back here, and the
+        // host's window handle goes over as a number, since it isn't `Send`.
+        let (requests, from_editor) = mpsc::channel();
+        let (messages, to_editor) = mpsc::channel();
+        let (opened, status) = mpsc::channel();
+        let parent = parent as usize;
+        let params = Arc::clone(&self.params);
+        let shared = Arc::clone(&self.shared);
+        let mut history = mem::replace(&mut self.history, History::default());
+        let thread = thread::spawn(move || {
+            let host = HostProxy(requests);
+            let gui = GUI::new(
+                parent as HWND,
+                size,
+                sample_path.unwrap_or_default(),
+                tuning_path.unwrap_or_default(),
+                program,
+                params.defs.len(),
+            );
+            let _ = opened.send(gui.is_some());
+            if let Some(mut gui) = gui {
+                gui.run(&params, &mut history, &host, &shared, &to_editor);
+                gui.close();
+            }
+            history
+        });
+        let editor = EditorThread {
+            thread,
+            requests: from_editor,
+            messages,
+            status,
+        };
+        // The host has to hear straight away if there's no editor after all.
+        if wait_for(&editor.status) == Some(true) {
+            self.editor = Some(editor);
+            return true;
+        }
+        if let Ok(history) = editor.thread.join() {
+            self.history = history;
+        }
+        false
+    }
+
+    fn is_open(&mut self) -> bool {
+        self.editor.is_some()
+    }
+
+    // Keys the host passes on while the editor has focus.  Returning false
+    // hands one back to the host, as space always is, for its transport.
+    fn key_down(&mut self, keycode: KeyCode) -> bool {
+        match keycode.key {
+            Key::Space => false,
+            _ if self.typing() => false,
+            Key::Up | Key::Right => self.nudge(true),
+            Key::Down | Key::Left => self.nudge(false),
+            _ => {
+                let taken = self
+                    .shared
+                    .keyboard
+                    .lock()
+                    .unwrap()
+                    .press(&self.params, keycode.character);
+                self.refresh();
+                taken
+            }
+        }
+    }
+
+    // Whatever was taken when it went down is taken when it comes up too.
+    fn key_up(&mut self, keycode: KeyCode) -> bool {
+        match keycode.key {
+            Key::Space => false,
+            Key::Up | Key::Right | Key::Down | Key::Left => {
+                self.shared.focused.load(Ordering::Relaxed)
+                    && self.editor.is_some()
+                    && !self.typing()
+            }
+            _ => self
+                .shared
+                .keyboard
+                .lock()
+                .unwrap()
+                .release(&self.params, keycode.character),
+        }
+    }
+}
@@ -0,0 +1,1117 @@
+// The editor's pages, and everything around them that's set every frame,
+// each by a function of its own.  They're set in the order they're called
+// in, so whatever comes later is drawn over what came before.
+
+use super::{
+    scaled, HostProxy, HostRequest, Hover, Ids, Tab, CYCLE_LENGTH, KNOB_SIZE, METER_WIDTH,
+    PANEL_COLUMNS, PANEL_WIDTH, ROW_HEIGHT, SCOPE_HEIGHT,
+};
+use crate::about::{open_link, BUILD_DATE, GIT_HASH, LINKS, VERSION};
+use crate::fonts::Fonts;
+use crate::gain::{format_db, gain_to_db};
+use crate::history::History;
+use crate::language::Language;
+use crate::matrix::{ModDestination, ModSource, MATRIX_SLOTS};
+use crate::preset::{delete_preset, list_presets, PresetEntry};
+use crate::sequencer::SEQUENCER_STEPS;
+use crate::settings::{Settings, FRAME_RATES, SCALES, TOOLTIP_DELAYS};
+use crate::skin::Skin;
+use crate::spectrum::Analyzer;
+use crate::telemetry::Telemetry;
+use crate::theme::{Theme, THEMES};
+use crate::widgets::{
+    Adsr, Cycle, EnvelopeEditor, GridStep, Knob, Meter, Scope, Spectrum, StepEdit, StepGrid,
+};
+use crate::{Command, WhisperParameters};
+use conrod_core::{
+    widget, Borderable, Colorable, Labelable, Positionable, Sizeable, UiCell, Widget,
+};
+use std::sync::atomic::Ordering;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+// The matrix page's rows, one for each slot, with the lists of sources and
+// destinations side by side and the amount after them.
+const MATRIX_ROW_HEIGHT: f64 = 28.0;
+const MATRIX_LIST_WIDTH: f64 = 110.0;
+
+// Which of a stepped parameter's choices a value is, and back again, the
+// same way the parameter snaps to them.
+fn choice_of(value: f32, choices: usize) -> usize {
+    ((value * choices as f32) as usize).min(choices - 1)
+}
+
+fn choice_value(choice: usize, choices: usize) -> f32 {
+    choice as f32 / (choices - 1).max(1) as f32
+}
+
+const TOOLTIP_WIDTH: f64 = 160.0;
+
+const ABOUT_WIDTH: f64 = 320.0;
+const ABOUT_HEIGHT: f64 = 220.0;
+
+// The XY pad goes to the right of the panel, and the envelope under it.
+const XY_PAD_SIZE: f64 = 150.0;
+const ENVELOPE_HEIGHT: f64 = 54.0;
+
+// The parameters the envelope editor moves.
+const ENVELOPE: [&str; 4] = ["attack", "decay", "sustain", "release"];
+
+// What every page gets to set its widgets with, made again for every frame.
+pub struct Page<'a> {
+    pub params: &'a WhisperParameters,
+    pub history: &'a mut History,
+    pub host: &'a HostProxy,
+    pub ids: &'a Ids,
+    // Which parameters were last changed by the host rather than here.
+    pub automated: &'a mut [bool],
+    pub language: Language,
+    pub theme: Theme,
+    pub fonts: &'a Fonts,
+    pub skin: &'a Skin,
+    // The size of the panel across the middle, which takes up whatever
+    // the editor has been made bigger by.
+    pub width: f64,
+    pub height: f64,
+}
+
+impl Page<'_> {
+    // Set a parameter from one of the page's widgets, through the history
+    // so it can be undone.  It's this that changed it, not the host.
+    pub fn edit(&mut self, parameter: i32, value: f32) {
+        self.history.edit(self.params, self.host, parameter, value);
+        self.automated[parameter as usize] = false;
+    }
+}
+
+// The sequencer's steps as a grid of bars to draw on, with the one playing
+// lit up.  The bars are the steps' own parameters, so they're automated and
+// undone like the knobs, and picked up by the sequencer at its next buffer.
+pub fn steps_page(ui: &mut UiCell, page: &mut Page, playing: Option<usize>) {
+    let (params, ids, theme) = (page.params, page.ids, page.theme);
+    let parts = ["pitch", "gate", "velocity"];
+    let indices: Vec<[i32; 3]> = (1..=SEQUENCER_STEPS)
+        .filter_map(|number| {
+            let index = |part| params.parameter_index(&format!("step {} {}", number, part));
+            Some([index(parts[0])?, index(parts[1])?, index(parts[2])?])
+        })
+        .collect();
+    let steps: Vec<GridStep> = indices
+        .iter()
+        .map(|&[pitch, gate, velocity]| GridStep {
+            pitch: params.get_parameter(pitch),
+            velocity: params.get_parameter(velocity),
+            gate: params.get_parameter(gate) > 0.0,
+        })
+        .collect();
+    let edits = StepGrid::new(&steps)
+        .playing(playing)
+        .w_h(page.width, page.height)
+        .top_left_with_margins_on(ui.window, 112.0, 10.0)
+        .color(theme.accent)
+        .set(ids.step_grid, ui);
+    // A rest is a gate of nothing, and a step that isn't gets the gate it
+    // starts with.
+    for edit in edits {
+        let (parameter, value) = match edit {
+            StepEdit::Pitch(step, value) => (indices[step][0], value),
+            StepEdit::Velocity(step, value) => (indices[step][2], value),
+            StepEdit::Gate(step) => {
+                let gate = indices[step][1];
+                if params.get_parameter(gate) > 0.0 {
+                    (gate, 0.0)
+                } else {
+                    (gate, params.defs[gate as usize].default)
+                }
+            }
+        };
+        page.edit(parameter, value);
+    }
+}
+
+// A row for each slot of the mod matrix, with where it comes from, where it
+// goes and how much of it, either way from the middle.  They're the matrix's
+// own parameters, so they're automated and undone like the knobs.
+pub fn matrix_page(ui: &mut UiCell, page: &mut Page) {
+    let (params, ids, theme) = (page.params, page.ids, page.theme);
+    widget::Canvas::new()
+        .w_h(page.width, page.height)
+        .top_left_with_margins_on(ui.window, 112.0, 10.0)
+        .color(match page.skin.background {
+            Some(_) => conrod_core::color::TRANSPARENT,
+            None => theme.background,
+        })
+        .border(0.0)
+        .scroll_kids_vertically()
+        .set(ids.panel, ui);
+
+    let sources: Vec<&str> = ModSource::ALL.iter().map(|source| source.name()).collect();
+    let destinations: Vec<&str> = ModDestination::ALL
+        .iter()
+        .map(|destination| destination.name())
+        .collect();
+    let amount_left = 32.0 + 2.0 * MATRIX_LIST_WIDTH + 8.0;
+    let amount_width = (page.width - amount_left - 16.0).max(40.0);
+
+    // From the bottom up, so each row's lists open over the rows under it.
+    for slot in (0..MATRIX_SLOTS).rev() {
+        let number = slot + 1;
+        let top = slot as f64 * MATRIX_ROW_HEIGHT + 4.0;
+        widget::Text::new(&number.to_string())
+            .top_left_with_margins_on(ids.panel, top + 3.0, 8.0)
+            .color(theme.text)
+            .font_size(theme.small_font_size)
+            .parent(ids.panel)
+            .set(ids.matrix_numbers[slot], ui);
+
+        let lists = [
+            ("source", &sources, ids.matrix_sources[slot], 32.0),
+            (
+                "destination",
+                &destinations,
+                ids.matrix_destinations[slot],
+                36.0 + MATRIX_LIST_WIDTH,
+            ),
+        ];
+        for &(part, names, list, left) in lists.iter() {
+            let name = format!("mod {} {}", number, part);
+            let parameter = match params.parameter_index(&name) {
+                Some(parameter) => parameter,
+                None => continue,
+            };
+            let picked = choice_of(params.get_parameter(parameter), names.len());
+            if let Some(choice) = widget::DropDownList::new(names, Some(picked))
+                .w_h(MATRIX_LIST_WIDTH, 20.0)
+                .top_left_with_margins_on(ids.panel, top, left)
+                .max_visible_items(6)
+                .label_font_size(theme.small_font_size)
+                .parent(ids.panel)
+                .set(list, ui)
+            {
+                let value = choice_value(choice, names.len());
+                page.edit(parameter, value);
+            }
+        }
+
+        let name = format!("mod {} amount", number);
+        if let Some(parameter) = params.parameter_index(&name) {
+            let def = &params.defs[parameter as usize];
+            let label = format!("{} {}", def.text(params), def.label);
+            if let Some(value) = widget::Slider::new(params.get_parameter(parameter), 0.0, 1.0)
+                .w_h(amount_width, 20.0)
+                .top_left_with_margins_on(ids.panel, top, amount_left)
+                .color(theme.accent)
+                .label(&label)
+                .label_font_size(theme.small_font_size)
+                .label_color(theme.text)
+                .parent(ids.panel)
+                .set(ids.matrix_amounts[slot], ui)
+            {
+                page.edit(parameter, value);
+            }
+        }
+    }
+
+    widget::Scrollbar::y_axis(ids.panel)
+        .auto_hide(true)
+        .set(ids.panel_scrollbar, ui);
+}
+
+// A knob for every parameter, each with its name and value under it, the
+// value in blue while it's being automated.  Edits go through the history, so
+// they can be undone.
+pub fn knob_page(
+    ui: &mut UiCell,
+    page: &mut Page,
+    tab: Tab,
+    hover: &mut Option<Hover>,
+    editing: &mut Option<(usize, String)>,
+    focused: &mut Option<usize>,
+) {
+    let (params, ids, theme, language) = (page.params, page.ids, page.theme, page.language);
+    // More knobs to a row as the panel gets wider.
+    let column_width = PANEL_WIDTH / PANEL_COLUMNS as f64;
+    let columns = ((page.width / column_width) as usize).max(1);
+    let column_width = page.width / columns as f64;
+
+    widget::Canvas::new()
+        .w_h(page.width, page.height)
+        .top_left_with_margins_on(ui.window, 112.0, 10.0)
+        // Letting the skin's background show through.
+        .color(match page.skin.background {
+            Some(_) => conrod_core::color::TRANSPARENT,
+            None => theme.background,
+        })
+        .border(0.0)
+        .scroll_kids_vertically()
+        .set(ids.panel, ui);
+
+    let shown = params
+        .defs
+        .iter()
+        .enumerate()
+        .filter(|(_, def)| Tab::of(def.category) == tab);
+    let mut hovered = None;
+    let mut entered = false;
+    for (place, (index, def)) in shown.enumerate() {
+        let (row, column) = (place / columns, place % columns);
+        let knob = ids.knobs[index];
+        let parameter = index as i32;
+        if let Some(value) = Knob::new(params.get_parameter(parameter), 0.0, 1.0)
+            .default_value(def.default)
+            .steps(def.steps)
+            .film_strip(page.skin.knob)
+            .color(theme.accent)
+            .w_h(KNOB_SIZE, KNOB_SIZE)
+            .top_left_with_margins_on(
+                ids.panel,
+                row as f64 * ROW_HEIGHT + 4.0,
+                (column as f64 + 0.5) * column_width - KNOB_SIZE / 2.0,
+            )
+            .parent(ids.panel)
+            .set(knob, ui)
+        {
+            page.edit(parameter, value);
+        }
+        if ui
+            .widget_input(knob)
+            .presses()
+            .mouse()
+            .left()
+            .next()
+            .is_some()
+        {
+            *focused = Some(index);
+        }
+        if ui.global_input().current.widget_under_mouse == Some(knob) {
+            hovered = Some(index);
+        }
+
+        // Clicking the reading swaps it for a box to type a value into, read
+        // the same way as values the host has typed in.
+        match editing.as_mut() {
+            Some((editing_index, text)) if *editing_index == index => {
+                for event in widget::TextBox::new(text)
+                    .w_h(column_width - 8.0, 18.0)
+                    .down_from(knob, 2.0)
+                    .align_middle_x_of(knob)
+                    .font_size(theme.small_font_size)
+                    .parent(ids.panel)
+                    .set(ids.value_entry, ui)
+                {
+                    match event {
+                        widget::text_box::Event::Update(new_text) => *text = new_text,
+                        widget::text_box::Event::Enter => {
+                            if let Some(value) = def.parse(params, text) {
+                                page.edit(parameter, value);
+                            }
+                            entered = true;
+                        }
+                    }
+                }
+            }
+            _ => {
+                let reading = format!(
+                    "{}\n{} {}",
+                    language.parameter_name(&def.name),
+                    def.text(params),
+                    def.label
+                );
+                let reading_id = ids.knob_readings[index];
+                widget::Text::new(reading.trim_end())
+                    .down_from(knob, 2.0)
+                    .align_middle_x_of(knob)
+                    .center_justify()
+                    .color(if page.automated[index] {
+                        theme.accent
+                    } else {
+                        theme.text
+                    })
+                    .font_size(theme.small_font_size)
+                    .parent(ids.panel)
+                    .set(reading_id, ui);
+                if ui.widget_input(reading_id).clicks().left().next().is_some() {
+                    let text = format!("{} {}", def.text(params), def.label);
+                    *editing = Some((index, text.trim_end().to_string()));
+                }
+            }
+        }
+    }
+
+    // Pressing enter is the end of it, and so is clicking anywhere else.
+    let elsewhere = ui.global_input().current.mouse.buttons.left().is_down()
+        && ui.global_input().current.widget_under_mouse != Some(ids.value_entry);
+    if entered || elsewhere {
+        *editing = None;
+    }
+
+    widget::Scrollbar::y_axis(ids.panel)
+        .auto_hide(true)
+        .set(ids.panel_scrollbar, ui);
+
+    // The wait starts again whenever the mouse goes to another knob, and a
+    // knob being turned doesn't need telling what it is.
+    if ui.global_input().current.mouse.buttons.left().is_down() {
+        hovered = None;
+    }
+    match (hover.as_ref(), hovered) {
+        (Some(hover), Some(parameter)) if hover.parameter == parameter => {}
+        (_, hovered) => {
+            *hover = hovered.map(|parameter| Hover {
+                parameter,
+                since: Instant::now(),
+                shown: false,
+            })
+        }
+    }
+}
+
+// The preset browser takes the panel's place.  Clicking a preset picks it,
+// and puts its name in the box at the top, ready to be renamed.
+pub fn browser_page(
+    ui: &mut UiCell,
+    page: &mut Page,
+    presets: &mut Vec<PresetEntry>,
+    selected: &mut Option<usize>,
+    preset_name: &mut String,
+    preset_status: &mut String,
+) {
+    let (params, ids, theme, language) = (page.params, page.ids, page.theme, page.language);
+    let (mut items, scrollbar) = widget::List::flow_down(presets.len())
+        .item_size(20.0)
+        .scrollbar_on_top()
+        .w_h(page.width, page.height - 34.0)
+        .top_left_with_margins_on(ui.window, 112.0, 10.0)
+        .set(ids.preset_list, ui);
+    while let Some(item) = items.next(ui) {
+        let preset = &presets[item.i];
+        let label = if preset.factory {
+            language.fill("{} (factory)", &[&preset.name])
+        } else {
+            preset.name.clone()
+        };
+        // Named by the user, so in any language.
+        let button = widget::Button::new()
+            .label(&label)
+            .label_font_id(page.fonts.for_text(&label))
+            .label_font_size(theme.font_size)
+            .label_color(theme.text)
+            .color(if *selected == Some(item.i) {
+                theme.accent
+            } else {
+                theme.panel
+            });
+        for _click in item.set(button, ui) {
+            *selected = Some(item.i);
+            *preset_name = preset.name.clone();
+        }
+    }
+    if let Some(scrollbar) = scrollbar {
+        scrollbar.set(ui);
+    }
+
+    let picked = (*selected).and_then(|index| presets.get(index));
+    let mut changed = false;
+
+    for _click in widget::Button::new()
+        .label(language.text("Load"))
+        .label_font_size(theme.font_size)
+        .w_h(60.0, 24.0)
+        .top_left_with_margins_on(ui.window, 112.0 + page.height - 24.0, 10.0)
+        .set(ids.browser_load, ui)
+    {
+        if let Some(preset) = picked {
+            page.history.edit_all(params, page.host, || {
+                *preset_status = match params.load_preset_file(&preset.path) {
+                    Ok(()) => language.text("Preset loaded").to_string(),
+                    Err(error) => error.to_string(),
+                };
+            });
+        }
+    }
+
+    // Renaming goes to whatever's in the box.
+    for _click in widget::Button::new()
+        .label(language.text("Rename"))
+        .label_font_size(theme.font_size)
+        .w_h(60.0, 24.0)
+        .right_from(ids.browser_load, 10.0)
+        .set(ids.browser_rename, ui)
+    {
+        *preset_status = match picked {
+            Some(preset) if preset.factory => language
+                .text("Factory presets can't be changed")
+                .to_string(),
+            Some(preset) => match params.rename_preset(preset, preset_name) {
+                Ok(()) => {
+                    changed = true;
+                    language.text("Preset renamed").to_string()
+                }
+                Err(error) => error.to_string(),
+            },
+            None => continue,
+        };
+    }
+
+    for _click in widget::Button::new()
+        .label(language.text("Delete"))
+        .label_font_size(theme.font_size)
+        .w_h(60.0, 24.0)
+        .right_from(ids.browser_rename, 10.0)
+        .set(ids.browser_delete, ui)
+    {
+        *preset_status = match picked {
+            Some(preset) if preset.factory => language
+                .text("Factory presets can't be changed")
+                .to_string(),
+            Some(preset) => match delete_preset(&preset.path) {
+                Ok(()) => {
+                    changed = true;
+                    language.text("Preset deleted").to_string()
+                }
+                Err(error) => error.to_string(),
+            },
+            None => continue,
+        };
+    }
+
+    if changed {
+        *presets = list_presets();
+        *selected = None;
+    }
+}
+
+// The scope, the single cycle when it's the synth's page, the spectrum and
+// the meter, side by side under the panel.
+pub fn signal_views(
+    ui: &mut UiCell,
+    page: &Page,
+    tab: Tab,
+    scope: &[f32],
+    analyzer: &Analyzer,
+    telemetry: &Telemetry,
+) {
+    let (params, ids, theme) = (page.params, page.ids, page.theme);
+    let parts = if tab == Tab::Synth { 3.0 } else { 2.0 };
+    let part = (page.width - 10.0 * (parts - 1.0)) / parts;
+    Scope::new(scope)
+        .w_h(part, SCOPE_HEIGHT)
+        .top_left_with_margins_on(ui.window, 120.0 + page.height, 10.0)
+        .color(theme.signal)
+        .set(ids.scope, ui);
+
+    let before_spectrum = if tab == Tab::Synth {
+        Cycle::new(&params.cycle(CYCLE_LENGTH))
+            .w_h(part, SCOPE_HEIGHT)
+            .right_from(ids.scope, 10.0)
+            .color(theme.accent)
+            .set(ids.cycle, ui);
+        ids.cycle
+    } else {
+        ids.scope
+    };
+
+    Spectrum::new(&analyzer.levels, &analyzer.peaks)
+        .w_h(part, SCOPE_HEIGHT)
+        .right_from(before_spectrum, 10.0)
+        .color(theme.signal)
+        .set(ids.spectrum, ui);
+
+    Meter::new(telemetry.rms, telemetry.peak)
+        .w_h(METER_WIDTH, SCOPE_HEIGHT)
+        .top_right_with_margins_on(ui.window, 120.0 + page.height, 10.0)
+        .set(ids.meter, ui);
+}
+
+// The XY pad and the envelope, which go beside the synth's own knobs.
+pub fn pad_and_envelope(ui: &mut UiCell, page: &mut Page) {
+    let (params, ids, theme, language) = (page.params, page.ids, page.theme, page.language);
+    // The XY pad moves two parameters at once, across and up, picked from the
+    // lists under it.
+    let (x, y) = *params.xy_pad.lock().unwrap();
+    if let Some((new_x, new_y)) = widget::XYPad::new(
+        params.get_parameter(x),
+        0.0,
+        1.0,
+        params.get_parameter(y),
+        0.0,
+        1.0,
+    )
+    .w_h(XY_PAD_SIZE, XY_PAD_SIZE)
+    .top_right_with_margins_on(ui.window, 112.0, 10.0)
+    .color(theme.panel)
+    .value_font_size(theme.small_font_size)
+    .set(ids.xy_pad, ui)
+    {
+        page.edit(x, new_x);
+        page.edit(y, new_y);
+    }
+
+    // Set before the lists, so they open over it.
+    let envelope: Vec<i32> = ENVELOPE
+        .iter()
+        .filter_map(|name| params.parameter_index(name))
+        .collect();
+    if let [attack, decay, sustain, release] = envelope[..] {
+        let adsr = Adsr {
+            attack: params.get_parameter(attack),
+            decay: params.get_parameter(decay),
+            sustain: params.get_parameter(sustain),
+            release: params.get_parameter(release),
+        };
+        if let Some(new_adsr) = EnvelopeEditor::new(adsr)
+            .w_h(XY_PAD_SIZE, ENVELOPE_HEIGHT)
+            .top_right_with_margins_on(ui.window, 318.0, 10.0)
+            .color(theme.accent)
+            .set(ids.envelope, ui)
+        {
+            page.edit(attack, new_adsr.attack);
+            page.edit(decay, new_adsr.decay);
+            page.edit(sustain, new_adsr.sustain);
+            page.edit(release, new_adsr.release);
+        }
+    }
+
+    let names: Vec<String> = params
+        .parameter_names()
+        .iter()
+        .map(|name| language.parameter_name(name))
+        .collect();
+    if let Some(new_x) = widget::DropDownList::new(&names, Some(x as usize))
+        .w_h(XY_PAD_SIZE, 20.0)
+        .down_from(ids.xy_pad, 4.0)
+        .max_visible_items(8)
+        .label_font_size(theme.small_font_size)
+        .set(ids.xy_pad_x, ui)
+    {
+        params.xy_pad.lock().unwrap().0 = new_x as i32;
+    }
+
+    if let Some(new_y) = widget::DropDownList::new(&names, Some(y as usize))
+        .w_h(XY_PAD_SIZE, 20.0)
+        .down_from(ids.xy_pad_x, 4.0)
+        .max_visible_items(8)
+        .label_font_size(theme.small_font_size)
+        .set(ids.xy_pad_y, ui)
+    {
+        params.xy_pad.lock().unwrap().1 = new_y as i32;
+    }
+}
+
+// The lists for what goes with the user, kept in their settings rather than
+// the plugin's.  The UI scale makes the window bigger and leaves the widgets'
+// size the same, so everything is drawn at that many times the size.
+// Whether the window needs drawing again straight away comes back.
+pub fn settings_page(
+    ui: &mut UiCell,
+    page: &Page,
+    settings: &mut Settings,
+    size: &Mutex<(i32, i32)>,
+    rescaling: &mut Option<u32>,
+) -> bool {
+    let (ids, theme, language) = (page.ids, page.theme, page.language);
+    let mut redraw = false;
+    // Set first, so the lists above open over them.
+    widget::Text::new(language.text("Frame rate"))
+        .top_right_with_margins_on(ui.window, 328.0, 10.0)
+        .w(XY_PAD_SIZE)
+        .color(theme.text)
+        .font_size(theme.font_size)
+        .set(ids.frame_rate_label, ui);
+
+    let names: Vec<String> = FRAME_RATES
+        .iter()
+        .map(|rate| format!("{} fps", rate))
+        .collect();
+    let picked = FRAME_RATES
+        .iter()
+        .position(|&rate| rate == settings.frame_rate);
+    if let Some(new_rate) = widget::DropDownList::new(&names, picked)
+        .w_h(XY_PAD_SIZE, 20.0)
+        .down_from(ids.frame_rate_label, 4.0)
+        .label_font_size(theme.small_font_size)
+        .set(ids.frame_rate, ui)
+    {
+        settings.frame_rate = FRAME_RATES[new_rate];
+        settings.save();
+    }
+
+    widget::Text::new(language.text("Language"))
+        .top_right_with_margins_on(ui.window, 274.0, 10.0)
+        .w(XY_PAD_SIZE)
+        .color(theme.text)
+        .font_size(theme.font_size)
+        .set(ids.language_label, ui);
+
+    let names: Vec<&str> = Language::ALL
+        .iter()
+        .map(|language| language.name())
+        .collect();
+    let picked = Language::ALL.iter().position(|&other| other == language);
+    if let Some(new_language) = widget::DropDownList::new(&names, picked)
+        .w_h(XY_PAD_SIZE, 20.0)
+        .down_from(ids.language_label, 4.0)
+        .label_font_size(theme.small_font_size)
+        .set(ids.language, ui)
+    {
+        settings.language = Language::ALL[new_language].name().to_string();
+        settings.save();
+        redraw = true;
+    }
+
+    widget::Text::new(language.text("Tooltips after"))
+        .top_right_with_margins_on(ui.window, 220.0, 10.0)
+        .w(XY_PAD_SIZE)
+        .color(theme.text)
+        .font_size(theme.font_size)
+        .set(ids.tooltip_delay_label, ui);
+
+    let names: Vec<String> = TOOLTIP_DELAYS
+        .iter()
+        .map(|delay| format!("{} ms", delay))
+        .collect();
+    let picked = TOOLTIP_DELAYS
+        .iter()
+        .position(|&delay| delay == settings.tooltip_delay);
+    if let Some(new_delay) = widget::DropDownList::new(&names, picked)
+        .w_h(XY_PAD_SIZE, 20.0)
+        .down_from(ids.tooltip_delay_label, 4.0)
+        .label_font_size(theme.small_font_size)
+        .set(ids.tooltip_delay, ui)
+    {
+        settings.tooltip_delay = TOOLTIP_DELAYS[new_delay];
+        settings.save();
+    }
+
+    widget::Text::new(language.text("Theme"))
+        .top_right_with_margins_on(ui.window, 166.0, 10.0)
+        .w(XY_PAD_SIZE)
+        .color(theme.text)
+        .font_size(theme.font_size)
+        .set(ids.theme_label, ui);
+
+    let names: Vec<&str> = THEMES
+        .iter()
+        .map(|theme| language.text(theme.name))
+        .collect();
+    let picked = THEMES.iter().position(|other| other.name == theme.name);
+    if let Some(new_theme) = widget::DropDownList::new(&names, picked)
+        .w_h(XY_PAD_SIZE, 20.0)
+        .down_from(ids.theme_label, 4.0)
+        .label_font_size(theme.small_font_size)
+        .set(ids.theme, ui)
+    {
+        settings.theme = THEMES[new_theme].name.to_string();
+        settings.save();
+        // Drawn again with it straight away.
+        redraw = true;
+    }
+
+    widget::Text::new(language.text("UI scale"))
+        .top_right_with_margins_on(ui.window, 112.0, 10.0)
+        .w(XY_PAD_SIZE)
+        .color(theme.text)
+        .font_size(theme.font_size)
+        .set(ids.ui_scale_label, ui);
+
+    let names: Vec<String> = SCALES.iter().map(|scale| format!("{}%", scale)).collect();
+    let picked = SCALES.iter().position(|&scale| scale == settings.scale);
+    if let Some(new_scale) = widget::DropDownList::new(&names, picked)
+        .w_h(XY_PAD_SIZE, 20.0)
+        .down_from(ids.ui_scale_label, 4.0)
+        .label_font_size(theme.small_font_size)
+        .set(ids.ui_scale, ui)
+    {
+        let scale = f64::from(SCALES[new_scale]) / 100.0;
+        let (width, height) = scaled(ui.win_w, ui.win_h, scale);
+        // It's only used once the host's window has been made to fit.
+        if (width, height) != *size.lock().unwrap() {
+            *rescaling = Some(SCALES[new_scale]);
+            page.host.request(HostRequest::SizeWindow(width, height));
+        }
+    }
+    redraw
+}
+
+// The boxes for the sample and the tuning file, along the bottom.
+pub fn file_boxes(
+    ui: &mut UiCell,
+    page: &Page,
+    sample_path: &mut String,
+    sample_status: &mut String,
+    tuning_path: &mut String,
+    tuning_status: &mut String,
+) {
+    let (params, ids, theme, language) = (page.params, page.ids, page.theme, page.language);
+    // Type the path of a WAV or AIFF file and press enter to load it into the
+    // sampler.
+    for event in widget::TextBox::new(sample_path)
+        .w_h(ui.win_w - 20.0, 24.0)
+        .mid_bottom_with_margin_on(ui.window, 30.0)
+        .font_id(page.fonts.for_text(sample_path))
+        .font_size(theme.font_size)
+        .set(ids.sample_path, ui)
+    {
+        match event {
+            widget::text_box::Event::Update(text) => *sample_path = text,
+            widget::text_box::Event::Enter => {
+                *sample_status = match params.load_sample(sample_path) {
+                    Ok(()) => language.text("Sample loaded").to_string(),
+                    Err(error) => error.to_string(),
+                };
+            }
+        }
+    }
+
+    widget::Text::new(sample_status)
+        .mid_bottom_with_margin_on(ui.window, 8.0)
+        .color(theme.text)
+        .font_id(page.fonts.for_text(sample_status))
+        .font_size(theme.font_size)
+        .set(ids.sample_status, ui);
+
+    // And a Scala or AnaMark file to tune the keys with.  Clearing the box
+    // goes back to the usual tuning.
+    for event in widget::TextBox::new(tuning_path)
+        .w_h(ui.win_w - 20.0, 24.0)
+        .mid_bottom_with_margin_on(ui.window, 80.0)
+        .font_id(page.fonts.for_text(tuning_path))
+        .font_size(theme.font_size)
+        .set(ids.tuning_path, ui)
+    {
+        match event {
+            widget::text_box::Event::Update(text) => *tuning_path = text,
+            widget::text_box::Event::Enter => {
+                *tuning_status = match params.load_tuning(tuning_path) {
+                    Ok(()) => language.text("Tuning loaded").to_string(),
+                    Err(error) => error.to_string(),
+                };
+            }
+        }
+    }
+
+    widget::Text::new(tuning_status)
+        .mid_bottom_with_margin_on(ui.window, 58.0)
+        .color(theme.text)
+        .font_id(page.fonts.for_text(tuning_status))
+        .font_size(theme.font_size)
+        .set(ids.tuning_status, ui);
+}
+
+// Presets are saved to and loaded from files by name, in the folder given in
+// preset.rs.
+pub fn preset_bar(
+    ui: &mut UiCell,
+    page: &mut Page,
+    preset_name: &mut String,
+    preset_status: &mut String,
+    presets: &mut Vec<PresetEntry>,
+    selected: &mut Option<usize>,
+    browsing: &mut bool,
+) {
+    let (params, ids, theme, language) = (page.params, page.ids, page.theme, page.language);
+    for event in widget::TextBox::new(preset_name)
+        .w_h(ui.win_w - 230.0, 24.0)
+        .top_left_with_margin_on(ui.window, 10.0)
+        .font_id(page.fonts.for_text(preset_name))
+        .font_size(theme.font_size)
+        .set(ids.preset_name, ui)
+    {
+        if let widget::text_box::Event::Update(text) = event {
+            *preset_name = text;
+        }
+    }
+
+    for _click in widget::Button::new()
+        .label(language.text("Save"))
+        .label_font_size(theme.font_size)
+        .w_h(60.0, 24.0)
+        .right_from(ids.preset_name, 10.0)
+        .set(ids.preset_save, ui)
+    {
+        *preset_status = match params.save_preset(preset_name) {
+            Ok(()) => language.text("Preset saved").to_string(),
+            Err(error) => error.to_string(),
+        };
+        *presets = list_presets();
+        *selected = None;
+    }
+
+    for _click in widget::Button::new()
+        .label(language.text("Load"))
+        .label_font_size(theme.font_size)
+        .w_h(60.0, 24.0)
+        .right_from(ids.preset_save, 10.0)
+        .set(ids.preset_load, ui)
+    {
+        page.history.edit_all(params, page.host, || {
+            *preset_status = match params.load_preset(preset_name) {
+                Ok(()) => language.text("Preset loaded").to_string(),
+                Err(error) => error.to_string(),
+            };
+        });
+    }
+
+    for _click in widget::Button::new()
+        .label(language.text(if *browsing { "Knobs" } else { "Presets" }))
+        .label_font_size(theme.font_size)
+        .w_h(60.0, 24.0)
+        .right_from(ids.preset_load, 10.0)
+        .set(ids.browse, ui)
+    {
+        *browsing = !*browsing;
+        *presets = list_presets();
+        *selected = None;
+    }
+}
+
+// The buttons under the preset bar: panic, init and random, A/B, and undo and
+// redo.
+pub fn program_buttons(ui: &mut UiCell, page: &mut Page) {
+    let (params, ids, theme, language) = (page.params, page.ids, page.theme, page.language);
+    for _click in widget::Button::new()
+        .label(language.text("Panic"))
+        .label_font_size(theme.font_size)
+        .w_h(60.0, 24.0)
+        .down_from(ids.preset_load, 10.0)
+        .set(ids.panic, ui)
+    {
+        params.panic();
+    }
+
+    for _click in widget::Button::new()
+        .label(language.text("Init"))
+        .label_font_size(theme.font_size)
+        .w_h(60.0, 24.0)
+        .left_from(ids.panic, 10.0)
+        .set(ids.init, ui)
+    {
+        page.history
+            .edit_all(params, page.host, || params.run(Command::Init));
+    }
+
+    for _click in widget::Button::new()
+        .label(language.text("Random"))
+        .label_font_size(theme.font_size)
+        .w_h(60.0, 24.0)
+        .left_from(ids.init, 10.0)
+        .set(ids.randomize, ui)
+    {
+        page.history
+            .edit_all(params, page.host, || params.run(Command::Randomize));
+    }
+
+    // The A/B button shows which of them is being played, and swaps to the
+    // other when clicked.
+    for _click in widget::Button::new()
+        .label(if params.on_b() { "B" } else { "A" })
+        .label_font_size(theme.font_size)
+        .w_h(60.0, 24.0)
+        .down_from(ids.panic, 10.0)
+        .set(ids.compare, ui)
+    {
+        page.history
+            .edit_all(params, page.host, || params.toggle_compare());
+    }
+
+    for _click in widget::Button::new()
+        .label(language.text("A to B"))
+        .label_font_size(theme.font_size)
+        .w_h(60.0, 24.0)
+        .left_from(ids.compare, 10.0)
+        .set(ids.copy_a_to_b, ui)
+    {
+        page.history
+            .edit_all(params, page.host, || params.copy_a_to_b());
+    }
+
+    for _click in widget::Button::new()
+        .label(language.text("Undo"))
+        .label_font_size(theme.font_size)
+        .w_h(60.0, 24.0)
+        .left_from(ids.copy_a_to_b, 10.0)
+        .set(ids.undo, ui)
+    {
+        page.history.undo(params, page.host);
+    }
+
+    for _click in widget::Button::new()
+        .label(language.text("Redo"))
+        .label_font_size(theme.font_size)
+        .w_h(60.0, 24.0)
+        .left_from(ids.undo, 10.0)
+        .set(ids.redo, ui)
+    {
+        page.history.redo(params, page.host);
+    }
+}
+
+// How the last thing done to a preset went, and what the audio thread last
+// measured, with a light that shows MIDI coming in.
+pub fn status_line(
+    ui: &mut UiCell,
+    page: &Page,
+    preset_status: &str,
+    telemetry: &Telemetry,
+    midi_lit: bool,
+) {
+    let (ids, theme, language) = (page.ids, page.theme, page.language);
+    widget::Text::new(preset_status)
+        .down_from(ids.preset_name, 4.0)
+        .color(theme.text)
+        .font_id(page.fonts.for_text(preset_status))
+        .font_size(theme.font_size)
+        .set(ids.preset_status, ui);
+
+    let peak = telemetry.peak[0].max(telemetry.peak[1]);
+    widget::Text::new(&language.fill(
+        "{} voices, CPU {}%, peak {}",
+        &[
+            &telemetry.voices,
+            &(telemetry.load * 100.0).round(),
+            &format_db(gain_to_db(peak)),
+        ],
+    ))
+    .down_from(ids.preset_status, 4.0)
+    .color(theme.text)
+    .font_size(theme.font_size)
+    .set(ids.telemetry, ui);
+
+    // Lit for a moment whenever MIDI comes in.
+    widget::Circle::fill(4.0)
+        .right_from(ids.telemetry, 8.0)
+        .color(if midi_lit { theme.signal } else { theme.button })
+        .set(ids.midi_light, ui);
+}
+
+// The about box, over everything but the tooltips, which it keeps from
+// showing anyway by being under the mouse.  Clicking around it closes it too.
+pub fn about_box(ui: &mut UiCell, page: &Page, about: &mut bool) {
+    let (params, ids, theme, language) = (page.params, page.ids, page.theme, page.language);
+    widget::Rectangle::fill_with([ui.win_w, ui.win_h], theme.background.alpha(0.7))
+        .middle_of(ui.window)
+        .set(ids.about_shade, ui);
+    if ui
+        .widget_input(ids.about_shade)
+        .clicks()
+        .left()
+        .next()
+        .is_some()
+    {
+        *about = false;
+    }
+
+    widget::Canvas::new()
+        .w_h(ABOUT_WIDTH, ABOUT_HEIGHT)
+        .middle_of(ui.window)
+        .color(theme.panel)
+        .border(1.0)
+        .border_color(theme.button)
+        .set(ids.about_panel, ui);
+
+    widget::Text::new(&format!("Whisper {}", VERSION))
+        .mid_top_with_margin_on(ids.about_panel, 12.0)
+        .color(theme.text)
+        .font_size(theme.font_size + 4)
+        .parent(ids.about_panel)
+        .set(ids.about_title, ui);
+
+    widget::Text::new(&language.fill("Built {} from {}", &[&BUILD_DATE, &GIT_HASH]))
+        .down_from(ids.about_title, 8.0)
+        .align_middle_x_of(ids.about_panel)
+        .color(theme.text)
+        .font_size(theme.font_size)
+        .parent(ids.about_panel)
+        .set(ids.about_build, ui);
+
+    widget::Text::new(&language.fill(
+        "Running at {} Hz, up to {} samples at a time",
+        &[
+            &params.sample_rate.get().round(),
+            &params.block_size.load(Ordering::Relaxed),
+        ],
+    ))
+    .down_from(ids.about_build, 4.0)
+    .align_middle_x_of(ids.about_panel)
+    .color(theme.text)
+    .font_size(theme.font_size)
+    .parent(ids.about_panel)
+    .set(ids.about_audio, ui);
+
+    for (place, link) in LINKS.iter().enumerate() {
+        let button = widget::Button::new()
+            .label(link)
+            .label_font_size(theme.small_font_size)
+            .w_h(ABOUT_WIDTH - 20.0, 20.0)
+            .color(theme.button);
+        let button = match place {
+            0 => button.down_from(ids.about_audio, 12.0),
+            _ => button.down_from(ids.about_links[place - 1], 4.0),
+        };
+        for _click in button
+            .align_middle_x_of(ids.about_panel)
+            .parent(ids.about_panel)
+            .set(ids.about_links[place], ui)
+        {
+            open_link(link);
+        }
+    }
+
+    for _click in widget::Button::new()
+        .label(language.text("Close"))
+        .label_font_size(theme.font_size)
+        .w_h(60.0, 24.0)
+        .mid_bottom_with_margin_on(ids.about_panel, 12.0)
+        .parent(ids.about_panel)
+        .set(ids.about_close, ui)
+    {
+        *about = false;
+    }
+}
+
+// The tooltip for the knob the mouse has stayed on.  It goes beside the
+// mouse, on whichever side there's room.
+pub fn tooltip(ui: &mut UiCell, page: &Page, hover: &mut Option<Hover>, delay: Duration) {
+    let (params, ids, theme, language) = (page.params, page.ids, page.theme, page.language);
+    if let Some(hover) = hover
+        .as_mut()
+        .filter(|hover| hover.since.elapsed() >= delay)
+    {
+        let def = &params.defs[hover.parameter];
+        let text = format!(
+            "{}\n{} {}\n{}",
+            language.parameter_name(&def.name),
+            def.text(params),
+            def.label,
+            language.fill(
+                "Default {} {}",
+                &[&def.format(params, def.default), &def.label]
+            )
+        );
+        let height = 3.0 * f64::from(theme.font_size + 4) + 8.0;
+        let mouse = ui.global_input().current.mouse.xy;
+        let x = if mouse[0] + 12.0 + TOOLTIP_WIDTH > ui.win_w / 2.0 {
+            mouse[0] - 12.0 - TOOLTIP_WIDTH / 2.0
+        } else {
+            mouse[0] + 12.0 + TOOLTIP_WIDTH / 2.0
+        };
+        let y = if mouse[1] - 12.0 - height < -ui.win_h / 2.0 {
+            mouse[1] + 12.0 + height / 2.0
+        } else {
+            mouse[1] - 12.0 - height / 2.0
+        };
+        let knob = ids.knobs[hover.parameter];
+        widget::Rectangle::fill_with([TOOLTIP_WIDTH, height], theme.panel)
+            .x_y(x, y)
+            .graphics_for(knob)
+            .set(ids.tooltip, ui);
+        widget::Text::new(&text)
+            .w(TOOLTIP_WIDTH - 8.0)
+            .middle_of(ids.tooltip)
+            .color(theme.text)
+            .font_size(theme.font_size)
+            .graphics_for(knob)
+            .set(ids.tooltip_text, ui);
+        hover.shown = true;
+    }
+}
@@ -0,0 +1,136 @@
+use crate::range::{Curve, Range};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Stage {
+    Idle,
+    Attack,
+    Decay,
+    Sustain,
+    Release,
+}
+
+// Attack, decay and release are lengths in samples, sustain is a level from
+// 0.0 to 1.0.
+#[derive(Clone, Copy, Debug)]
+pub struct EnvelopeSettings {
+    pub attack: f32,
+    pub decay: f32,
+    pub sustain: f32,
+    pub release: f32,
+}
+
+impl EnvelopeSettings {
+    // Build the settings from the raw 0.0 to 1.0 parameter values.  The
+    // lengths depend on the sample rate, so these have to be rebuilt when it
+    // changes.
+    pub fn from_parameters(
+        attack: f32,
+        decay: f32,
+        sustain: f32,
+        release: f32,
+        sample_rate: f32,
+    ) -> Self {
+        Self {
+            attack: (envelope_time(attack) * sample_rate).max(1.0),
+            decay: (envelope_time(decay) * sample_rate).max(1.0),
+            sustain,
+            release: (envelope_time(release) * sample_rate).max(1.0),
+        }
+    }
+}
+
+pub struct Envelope {
+    stage: Stage,
+    level: f32,
+    // How much the level drops per sample once we are releasing.
+    release_step: f32,
+}
+
+impl Default for Envelope {
+    fn default() -> Self {
+        Self {
+            stage: Stage::Idle,
+            level: 0.0,
+            release_step: 0.0,
+        }
+    }
+}
+
+impl Envelope {
+    // Start (or restart) the envelope from wherever it currently is, so a
+    // retriggered note doesn't jump back to zero and click.
+    pub fn note_on(&mut self) {
+        self.stage = Stage::Attack;
+    }
+
+    pub fn note_off(&mut self) {
+        if self.stage != Stage::Idle {
+            self.stage = Stage::Release;
+            self.release_step = 0.0;
+        }
+    }
+
+    // Stop dead, without a release.
+    pub fn reset(&mut self) {
+        self.stage = Stage::Idle;
+        self.level = 0.0;
+    }
+
+    // Whether the envelope is still producing anything other than silence.
+    pub fn is_active(&self) -> bool {
+        self.stage != Stage::Idle
+    }
+
+    // Where the envelope is now, from 0.0 to 1.0.
+    pub fn level(&self) -> f32 {
+        self.level
+    }
+
+    pub fn next(&mut self, settings: &EnvelopeSettings) -> f32 {
+        match self.stage {
+            Stage::Idle => {}
+            Stage::Attack => {
+                self.level += 1.0 / settings.attack;
+                if self.level >= 1.0 {
+                    self.level = 1.0;
+                    self.stage = Stage::Decay;
+                }
+            }
+            Stage::Decay => {
+                self.level -= (1.0 - settings.sustain) / settings.decay;
+                if self.level <= settings.sustain {
+                    self.level = settings.sustain;
+                    self.stage = Stage::Sustain;
+                }
+            }
+            // Follow the sustain level, so turning the knob while holding a
+            // note is audible.
+            Stage::Sustain => self.level = settings.sustain,
+            Stage::Release => {
+                // The release time is how long it takes to fall from wherever
+                // the key was let go, so we work out the step on the first sample.
+                if self.release_step == 0.0 {
+                    self.release_step = self.level / settings.release;
+                }
+                self.level -= self.release_step;
+                if self.level <= 0.0 {
+                    self.level = 0.0;
+                    self.stage = Stage::Idle;
+                }
+            }
+        }
+        self.level
+    }
+}
+
+// 1 ms to 5 s.  Squaring the parameter gives the short times, where small
+// changes matter most, more of the knob's travel.
+pub const ENVELOPE_TIME: Range = Range {
+    min: 0.001,
+    max: 5.0,
+    curve: Curve::Power(2.0),
+};
+
+pub fn envelope_time(value: f32) -> f32 {
+    ENVELOPE_TIME.map(value)
+}
@@ -0,0 +1,198 @@
+use crate::range::{Curve, Range};
+use std::f32::consts::PI;
+
+// How far each band can boost or cut.
+pub const MAX_EQ_DB: f32 = 15.0;
+
+// The peaking band is about an octave and a half wide.
+const MID_Q: f32 = 1.0;
+
+// Biquad coefficients, already divided through by a0.  The formulas are the
+// ones from Robert Bristow-Johnson's "Audio EQ Cookbook".
+#[derive(Clone, Copy, Debug)]
+struct Coefficients {
+    b0: f32,
+    b1: f32,
+    b2: f32,
+    a1: f32,
+    a2: f32,
+}
+
+impl Coefficients {
+    fn new(b0: f32, b1: f32, b2: f32, a0: f32, a1: f32, a2: f32) -> Self {
+        Self {
+            b0: b0 / a0,
+            b1: b1 / a0,
+            b2: b2 / a0,
+            a1: a1 / a0,
+            a2: a2 / a0,
+        }
+    }
+
+    fn low_shelf(frequency: f32, db: f32, sample_rate: f32) -> Self {
+        let (a, cos, alpha) = shelf_terms(frequency, db, sample_rate);
+        let root = 2.0 * a.sqrt() * alpha;
+        Self::new(
+            a * ((a + 1.0) - (a - 1.0) * cos + root),
+            2.0 * a * ((a - 1.0) - (a + 1.0) * cos),
+            a * ((a + 1.0) - (a - 1.0) * cos - root),
+            (a + 1.0) + (a - 1.0) * cos + root,
+            -2.0 * ((a - 1.0) + (a + 1.0) * cos),
+            (a + 1.0) + (a - 1.0) * cos - root,
+        )
+    }
+
+    fn peak(frequency: f32, db: f32, sample_rate: f32) -> Self {
+        let a = 10.0f32.powf(db / 40.0);
+        let (cos, sin) = angle(frequency, sample_rate);
+        let alpha = sin / (2.0 * MID_Q);
+        Self::new(
+            1.0 + alpha * a,
+            -2.0 * cos,
+            1.0 - alpha * a,
+            1.0 + alpha / a,
+            -2.0 * cos,
+            1.0 - alpha / a,
+        )
+    }
+
+    fn high_shelf(frequency: f32, db: f32, sample_rate: f32) -> Self {
+        let (a, cos, alpha) = shelf_terms(frequency, db, sample_rate);
+        let root = 2.0 * a.sqrt() * alpha;
+        Self::new(
+            a * ((a + 1.0) + (a - 1.0) * cos + root),
+            -2.0 * a * ((a - 1.0) + (a + 1.0) * cos),
+            a * ((a + 1.0) + (a - 1.0) * cos - root),
+            (a + 1.0) - (a - 1.0) * cos + root,
+            2.0 * ((a - 1.0) - (a + 1.0) * cos),
+            (a + 1.0) - (a - 1.0) * cos - root,
+        )
+    }
+}
+
+// The cosine and sine of the band's frequency as an angle per sample.  It's
+// kept safely below Nyquist, where the formulas stop making sense.
+fn angle(frequency: f32, sample_rate: f32) -> (f32, f32) {
+    let frequency = frequency.min(sample_rate * 0.45);
+    let w0 = 2.0 * PI * frequency / sample_rate;
+    (w0.cos(), w0.sin())
+}
+
+// The terms both shelves share, with the steepest slope that doesn't bump
+// up or down before the shelf.
+fn shelf_terms(frequency: f32, db: f32, sample_rate: f32) -> (f32, f32, f32) {
+    let a = 10.0f32.powf(db / 40.0);
+    let (cos, sin) = angle(frequency, sample_rate);
+    (a, cos, sin / 2.0 * 2.0f32.sqrt())
+}
+
+// The filter itself, in transposed direct form II, which only needs two
+// numbers of state.
+#[derive(Clone, Copy, Debug, Default)]
+struct Biquad {
+    z1: f32,
+    z2: f32,
+}
+
+impl Biquad {
+    fn process(&mut self, input: f32, c: &Coefficients) -> f32 {
+        let output = input * c.b0 + self.z1;
+        self.z1 = input * c.b1 - output * c.a1 + self.z2;
+        self.z2 = input * c.b2 - output * c.a2;
+        output
+    }
+}
+
+// A band's centre or corner frequency in Hz and its gain in dB.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Band {
+    pub frequency: f32,
+    pub db: f32,
+}
+
+// A low shelf, a peak in the middle and a high shelf, in series.
+pub struct Equalizer {
+    bands: [Band; 3],
+    sample_rate: f32,
+    coefficients: [Coefficients; 3],
+    left: [Biquad; 3],
+    right: [Biquad; 3],
+}
+
+impl Default for Equalizer {
+    fn default() -> Self {
+        let flat = Coefficients::new(1.0, 0.0, 0.0, 1.0, 0.0, 0.0);
+        Self {
+            bands: [Band {
+                frequency: 0.0,
+                db: 0.0,
+            }; 3],
+            sample_rate: 0.0,
+            coefficients: [flat; 3],
+            left: [Biquad::default(); 3],
+            right: [Biquad::default(); 3],
+        }
+    }
+}
+
+impl Equalizer {
+    // Working out the coefficients takes a few trig functions, so it's only
+    // done when a band or the sample rate actually changed.
+    pub fn set(&mut self, low: Band, mid: Band, high: Band, sample_rate: f32) {
+        if self.bands == [low, mid, high] && self.sample_rate == sample_rate {
+            return;
+        }
+        self.bands = [low, mid, high];
+        self.sample_rate = sample_rate;
+        self.coefficients = [
+            Coefficients::low_shelf(low.frequency, low.db, sample_rate),
+            Coefficients::peak(mid.frequency, mid.db, sample_rate),
+            Coefficients::high_shelf(high.frequency, high.db, sample_rate),
+        ];
+    }
+
+    pub fn process(&mut self, left: &mut f32, right: &mut f32) {
+        for (i, coefficients) in self.coefficients.iter().enumerate() {
+            *left = self.left[i].process(*left, coefficients);
+            *right = self.right[i].process(*right, coefficients);
+        }
+    }
+}
+
+// Map a 0.0 to 1.0 parameter onto a cut or boost of up to `MAX_EQ_DB`,
+// flat in the middle.
+pub fn eq_db(value: f32) -> f32 {
+    (value * 2.0 - 1.0) * MAX_EQ_DB
+}
+
+// Each band's range of frequencies, spread evenly over the octaves in
+// between.
+pub const LOW_FREQUENCY: Range = Range {
+    min: 30.0,
+    max: 600.0,
+    curve: Curve::Log,
+};
+
+pub const MID_FREQUENCY: Range = Range {
+    min: 200.0,
+    max: 8000.0,
+    curve: Curve::Log,
+};
+
+pub const HIGH_FREQUENCY: Range = Range {
+    min: 1000.0,
+    max: 16000.0,
+    curve: Curve::Log,
+};
+
+pub fn low_frequency(value: f32) -> f32 {
+    LOW_FREQUENCY.map(value)
+}
+
+pub fn mid_frequency(value: f32) -> f32 {
+    MID_FREQUENCY.map(value)
+}
+
+pub fn high_frequency(value: f32) -> f32 {
+    HIGH_FREQUENCY.map(value)
+}
@@ -0,0 +1,89 @@
+use crate::range::{Curve, Range};
+use std::f32::consts::PI;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum FilterMode {
+    LowPass,
+    HighPass,
+    BandPass,
+}
+
+impl FilterMode {
+    pub const ALL: [FilterMode; 3] = [
+        FilterMode::LowPass,
+        FilterMode::HighPass,
+        FilterMode::BandPass,
+    ];
+
+    pub fn from_parameter(value: f32) -> Self {
+        let index = (value * Self::ALL.len() as f32) as usize;
+        Self::ALL[index.min(Self::ALL.len() - 1)]
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            FilterMode::LowPass => "Low Pass",
+            FilterMode::HighPass => "High Pass",
+            FilterMode::BandPass => "Band Pass",
+        }
+    }
+}
+
+// A state-variable filter, in the "topology-preserving transform" form
+// described by Andrew Simper.  It stays stable while the cutoff moves, which
+// matters since the cutoff will be modulated on every sample.
+#[derive(Default)]
+pub struct Filter {
+    ic1eq: f32,
+    ic2eq: f32,
+}
+
+impl Filter {
+    pub fn reset(&mut self) {
+        self.ic1eq = 0.0;
+        self.ic2eq = 0.0;
+    }
+
+    pub fn process(
+        &mut self,
+        input: f32,
+        mode: FilterMode,
+        cutoff: f32,
+        resonance: f32,
+        sample_rate: f32,
+    ) -> f32 {
+        // Keep the cutoff safely below Nyquist, where `tan` blows up.
+        let cutoff = cutoff.min(sample_rate * 0.49);
+        let g = (PI * cutoff / sample_rate).tan();
+        // Full resonance gets very close to, but never reaches, self-oscillation.
+        let k = 2.0 - 1.98 * resonance;
+
+        let a1 = 1.0 / (1.0 + g * (g + k));
+        let a2 = g * a1;
+        let a3 = g * a2;
+
+        let v3 = input - self.ic2eq;
+        let v1 = a1 * self.ic1eq + a2 * v3;
+        let v2 = self.ic2eq + a2 * self.ic1eq + a3 * v3;
+        self.ic1eq = 2.0 * v1 - self.ic1eq;
+        self.ic2eq = 2.0 * v2 - self.ic2eq;
+
+        match mode {
+            FilterMode::LowPass => v2,
+            FilterMode::HighPass => input - k * v1 - v2,
+            FilterMode::BandPass => v1,
+        }
+    }
+}
+
+// 20 Hz to 20 kHz.  Hearing is logarithmic, so each tenth of the knob is the
+// same musical interval.
+pub const CUTOFF: Range = Range {
+    min: 20.0,
+    max: 20000.0,
+    curve: Curve::Log,
+};
+
+pub fn cutoff_frequency(value: f32) -> f32 {
+    CUTOFF.map(value)
+}
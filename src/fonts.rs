@@ -0,0 +1,67 @@
+// The fonts the editor's text is drawn in.  Noto Sans is built in, but it
+// only has Latin, Greek and Cyrillic letters, so for text with anything
+// else in it, like a preset named in Japanese, we look for one of the fonts
+// Windows comes with that has them.  Conrod draws each piece of text in a
+// single font, so the fallback is by the piece rather than by the glyph:
+// it's the first font with every one of its characters, and these all have
+// the Latin letters as well.  Text mixing writing no one font has, like
+// Japanese with Korean, can't all come out, and misses as little as it can.
+
+use conrod_core::text::{font, Font, FontCollection};
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+
+// For each kind of writing, the fonts that might have it, best first.
+// Only the first one of each that's there is loaded, since they're big.
+const FALLBACKS: [&[&str]; 3] = [
+    // Japanese.
+    &["YuGothM.ttc", "meiryo.ttc", "msgothic.ttc"],
+    // Chinese.
+    &["msyh.ttc", "simsun.ttc"],
+    // Korean.
+    &["malgun.ttf", "gulim.ttc"],
+];
+
+pub struct Fonts {
+    // Noto Sans, and then whichever fallbacks were found.
+    fonts: Vec<(font::Id, Font)>,
+}
+
+impl Fonts {
+    pub fn load(map: &mut font::Map) -> Self {
+        let regular: &[u8] = include_bytes!("../assets/fonts/NotoSans/NotoSans-Regular.ttf");
+        let regular = Font::from_bytes(regular).unwrap();
+        let mut fonts = vec![(map.insert(regular.clone()), regular)];
+        if let Some(directory) =
+            env::var_os("WINDIR").map(|windows| PathBuf::from(windows).join("Fonts"))
+        {
+            for names in FALLBACKS.iter() {
+                let found = names.iter().find_map(|name| {
+                    let data = fs::read(directory.join(name)).ok()?;
+                    // A .ttc holds several, and the first is the usual one.
+                    FontCollection::from_bytes(data).ok()?.font_at(0).ok()
+                });
+                if let Some(font) = found {
+                    fonts.push((map.insert(font.clone()), font));
+                }
+            }
+        }
+        Self { fonts }
+    }
+
+    // The font to draw `text` in.  When none of them have all of it, it's
+    // the one missing the fewest characters, Noto Sans winning ties.
+    pub fn for_text(&self, text: &str) -> font::Id {
+        self.fonts
+            .iter()
+            .min_by_key(|(_, font)| text.chars().filter(|&c| !has(font, c)).count())
+            .unwrap_or(&self.fonts[0])
+            .0
+    }
+}
+
+// Glyph 0 is the box drawn for characters a font hasn't got.
+fn has(font: &Font, c: char) -> bool {
+    c.is_whitespace() || c.is_control() || font.glyph(c).id().0 != 0
+}
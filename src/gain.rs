@@ -0,0 +1,44 @@
+// The volume parameter covers -60 dB to +6 dB, with the very bottom of its
+// range being complete silence.
+pub const MIN_DB: f32 = -60.0;
+pub const MAX_DB: f32 = 6.0;
+
+pub fn db_to_gain(db: f32) -> f32 {
+    10.0f32.powf(db / 20.0)
+}
+
+// Silence comes out as -inf dB.
+pub fn gain_to_db(gain: f32) -> f32 {
+    20.0 * gain.log10()
+}
+
+// Map a 0.0 to 1.0 parameter onto decibels.
+pub fn volume_db(value: f32) -> f32 {
+    if value <= 0.0 {
+        std::f32::NEG_INFINITY
+    } else {
+        MIN_DB + (MAX_DB - MIN_DB) * value.min(1.0)
+    }
+}
+
+// The inverse of `volume_db`, for setting the parameter to a level in dB.
+pub fn volume_parameter(db: f32) -> f32 {
+    ((db - MIN_DB) / (MAX_DB - MIN_DB)).max(0.0).min(1.0)
+}
+
+// Map a 0.0 to 1.0 parameter onto the gain the audio is multiplied by.
+pub fn volume_gain(value: f32) -> f32 {
+    if value <= 0.0 {
+        0.0
+    } else {
+        db_to_gain(volume_db(value))
+    }
+}
+
+pub fn format_db(db: f32) -> String {
+    if db == std::f32::NEG_INFINITY {
+        "-inf dB".to_string()
+    } else {
+        format!("{:.1} dB", db)
+    }
+}
@@ -0,0 +1,164 @@
+use crate::noise::Noise;
+use crate::oscillator::semitones_to_ratio;
+use crate::pan::equal_power;
+use crate::range::{Curve, Range};
+use crate::sample::{Sample, SamplerSettings};
+use std::f32::consts::{PI, SQRT_2};
+
+// The most grains a single voice has playing at once.  Any more than that
+// and new ones are skipped until an old one finishes.
+pub const MAX_GRAINS: usize = 32;
+
+// A short snippet of sound with a smooth fade in and out.
+#[derive(Clone, Copy, Default)]
+struct Grain {
+    active: bool,
+    // Where the grain is reading from in the sample, and how fast.
+    position: f64,
+    step: f64,
+    age: usize,
+    length: usize,
+    left: f32,
+    right: f32,
+}
+
+// Everything that shapes the cloud of grains, shared by all voices.
+#[derive(Clone, Copy, Debug)]
+pub struct GrainSettings {
+    // How long each grain lasts, in seconds.
+    pub size: f32,
+    // How many grains start every second.
+    pub density: f32,
+    // How far, in semitones, each grain's pitch may stray from the note.
+    pub pitch_spray: f32,
+    // How far towards the sides each grain may be panned, from 0.0 to 1.0.
+    pub stereo_spray: f32,
+}
+
+#[derive(Default)]
+pub struct GrainCloud {
+    grains: [Grain; MAX_GRAINS],
+    // Samples left until the next grain starts.
+    countdown: f32,
+    random: Noise,
+}
+
+impl GrainCloud {
+    pub fn reset(&mut self) {
+        for grain in self.grains.iter_mut() {
+            grain.active = false;
+        }
+        self.countdown = 0.0;
+    }
+
+    pub fn seed(&mut self, seed: u32) {
+        self.random.seed(seed);
+    }
+
+    // Render one stereo sample of the cloud.  Grains read from `source` if
+    // a sample is loaded, stepping `step` frames per sample at the note's
+    // pitch, or are made of noise if not.
+    pub fn next(
+        &mut self,
+        settings: &GrainSettings,
+        source: Option<(&Sample, &SamplerSettings)>,
+        step: f64,
+        sample_rate: f32,
+    ) -> (f32, f32) {
+        self.countdown -= 1.0;
+        if self.countdown <= 0.0 {
+            self.countdown += sample_rate / settings.density.max(0.1);
+            self.spawn(settings, source, step, sample_rate);
+        }
+
+        // Grains overlap more the longer and denser they are, so that has to
+        // be allowed for or the level would jump around with the settings.
+        let overlap = settings.size * settings.density;
+        let level = 1.0 / overlap.max(1.0).sqrt();
+
+        let mut left = 0.0;
+        let mut right = 0.0;
+        for grain in self.grains.iter_mut().filter(|grain| grain.active) {
+            let value = match source {
+                Some((sample, sampler)) => {
+                    let (l, r) = sampler.play(sample, &mut grain.position, grain.step);
+                    (l + r) * 0.5
+                }
+                None => self.random.white(),
+            };
+            // A Hann window, so grains fade in and out instead of clicking.
+            let window = 0.5 - 0.5 * (2.0 * PI * grain.age as f32 / grain.length as f32).cos();
+            left += value * window * grain.left;
+            right += value * window * grain.right;
+
+            grain.age += 1;
+            if grain.age >= grain.length {
+                grain.active = false;
+            }
+        }
+        (left * level, right * level)
+    }
+
+    fn spawn(
+        &mut self,
+        settings: &GrainSettings,
+        source: Option<(&Sample, &SamplerSettings)>,
+        step: f64,
+        sample_rate: f32,
+    ) {
+        let grain = match self.grains.iter_mut().find(|grain| !grain.active) {
+            Some(grain) => grain,
+            None => return,
+        };
+
+        // Each grain starts somewhere random in the sample's region.
+        let position = match source {
+            Some((sample, sampler)) => {
+                let (start, end) = sampler.region(sample);
+                start + (end - start) * f64::from(self.random.unit())
+            }
+            None => 0.0,
+        };
+        let random = &mut self.random;
+        let mut spray = || random.white();
+        // Scaled so a grain in the middle is at unity gain.
+        let (left, right) = equal_power(spray() * settings.stereo_spray);
+
+        *grain = Grain {
+            active: true,
+            position,
+            step: step * f64::from(semitones_to_ratio(spray() * settings.pitch_spray)),
+            age: 0,
+            length: ((settings.size * sample_rate) as usize).max(1),
+            left: left * SQRT_2,
+            right: right * SQRT_2,
+        };
+    }
+}
+
+// A grain size of 5 to 500 ms.
+pub const GRAIN_SIZE: Range = Range {
+    min: 0.005,
+    max: 0.5,
+    curve: Curve::Power(2.0),
+};
+
+// 1 to 200 grains a second.
+pub const GRAIN_DENSITY: Range = Range {
+    min: 1.0,
+    max: 200.0,
+    curve: Curve::Log,
+};
+
+pub fn grain_size(value: f32) -> f32 {
+    GRAIN_SIZE.map(value)
+}
+
+pub fn grain_density(value: f32) -> f32 {
+    GRAIN_DENSITY.map(value)
+}
+
+// Map a 0.0 to 1.0 parameter onto up to 24 semitones of pitch spray.
+pub fn pitch_spray(value: f32) -> f32 {
+    value * 24.0
+}
@@ -0,0 +1,147 @@
+// Undo and redo for what's done in the GUI.  Every change to a parameter is
+// kept with the value it had before, and the changes made while the mouse
+// button is held down, like all the steps of dragging a slider, are undone
+// together as one.  The host's own automation doesn't go in here; it's only
+// what the GUI does, put back through the same parameters the host sees.
+//
+// The host is told about all of it as well, so it can record automation:
+// `begin_edit` when a drag starts, `automate` for every step of it, and
+// `end_edit` when it's let go.  None of it counts as a change from outside,
+// which the GUI would otherwise show as automation.  It goes to whatever
+// stands in for the host on the editor's thread.
+
+use crate::WhisperParameters;
+use vst::host::Host;
+use vst::plugin::PluginParameters;
+
+// How many steps back there are to go before the oldest are forgotten.
+const HISTORY_LENGTH: usize = 100;
+
+#[derive(Clone, Copy)]
+struct Change {
+    parameter: i32,
+    before: f32,
+    after: f32,
+}
+
+#[derive(Default)]
+pub struct History {
+    undo: Vec<Vec<Change>>,
+    redo: Vec<Vec<Change>>,
+    // The changes since the mouse button went down, which aren't a step of
+    // their own until it comes up again.
+    gesture: Vec<Change>,
+}
+
+impl History {
+    // Set a parameter, remembering what it was.
+    pub fn edit(
+        &mut self,
+        parameters: &WhisperParameters,
+        host: &impl Host,
+        parameter: i32,
+        value: f32,
+    ) {
+        let before = parameters.get_parameter(parameter);
+        parameters.edit_parameter(parameter, value);
+        let after = parameters.get_parameter(parameter);
+        if before == after {
+            return;
+        }
+        if !self.in_gesture(parameter) {
+            host.begin_edit(parameter);
+        }
+        host.automate(parameter, after);
+        self.record(parameter, before, after);
+    }
+
+    // Do something that might change any number of parameters, like loading
+    // a preset, as one step.
+    pub fn edit_all(
+        &mut self,
+        parameters: &WhisperParameters,
+        host: &impl Host,
+        edit: impl FnOnce(),
+    ) {
+        self.end_gesture(host);
+        let before = parameters.parameter_values();
+        edit();
+        for (parameter, &before) in before.iter().enumerate() {
+            let parameter = parameter as i32;
+            let after = parameters.get_parameter(parameter);
+            // Whatever it did went through the host's own way in.
+            let _ = parameters.take_change(parameter);
+            if before != after {
+                host.begin_edit(parameter);
+                host.automate(parameter, after);
+                self.record(parameter, before, after);
+            }
+        }
+        self.end_gesture(host);
+    }
+
+    fn in_gesture(&self, parameter: i32) -> bool {
+        self.gesture
+            .iter()
+            .any(|change| change.parameter == parameter)
+    }
+
+    fn record(&mut self, parameter: i32, before: f32, after: f32) {
+        self.redo.clear();
+        // Dragging the same slider again and again only needs where it
+        // started from and where it ended up.
+        match self
+            .gesture
+            .iter_mut()
+            .find(|change| change.parameter == parameter)
+        {
+            Some(change) => change.after = after,
+            None => self.gesture.push(Change {
+                parameter,
+                before,
+                after,
+            }),
+        }
+    }
+
+    // Called whenever the mouse button isn't down.
+    pub fn end_gesture(&mut self, host: &impl Host) {
+        if self.gesture.is_empty() {
+            return;
+        }
+        for change in self.gesture.iter() {
+            host.end_edit(change.parameter);
+        }
+        self.undo.push(self.gesture.split_off(0));
+        if self.undo.len() > HISTORY_LENGTH {
+            self.undo.remove(0);
+        }
+    }
+
+    pub fn undo(&mut self, parameters: &WhisperParameters, host: &impl Host) {
+        self.end_gesture(host);
+        if let Some(step) = self.undo.pop() {
+            for change in step.iter().rev() {
+                put_back(parameters, host, change.parameter, change.before);
+            }
+            self.redo.push(step);
+        }
+    }
+
+    pub fn redo(&mut self, parameters: &WhisperParameters, host: &impl Host) {
+        if let Some(step) = self.redo.pop() {
+            for change in step.iter() {
+                put_back(parameters, host, change.parameter, change.after);
+            }
+            self.undo.push(step);
+        }
+    }
+}
+
+// Undoing and redoing are a gesture of their own for every parameter.
+fn put_back(parameters: &WhisperParameters, host: &impl Host, parameter: i32, value: f32) {
+    host.begin_edit(parameter);
+    parameters.edit_parameter(parameter, value);
+    host.automate(parameter, value);
+    host.end_edit(parameter);
+}
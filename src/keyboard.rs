@@ -0,0 +1,122 @@
+// The computer's keyboard as a piano keyboard, for trying out sounds with
+// nothing plugged in.  The middle row plays the white keys from C, with the
+// black keys in the row above, the way they sit on a piano:
+//
+//      W E   T Y U   O P
+//     A S D F G H J K L ;
+//
+// Z and X move it all down and up an octave.  Notes go to the audio thread
+// the same way the GUI's other messages do.
+
+use crate::WhisperParameters;
+use winit::event::VirtualKeyCode;
+
+// The keys, from C up.
+const KEYS: &str = "awsedftgyhujkolp;";
+
+const VELOCITY: u8 = 100;
+
+// With middle C in octave 4.
+const DEFAULT_OCTAVE: i32 = 4;
+const MIN_OCTAVE: i32 = -1;
+const MAX_OCTAVE: i32 = 8;
+
+pub struct Keyboard {
+    // The octave the A key plays the C of.
+    pub octave: i32,
+    // The keys being held down and the notes they started, so a note stops
+    // even after the octave's been moved, and keys repeating while they're
+    // held don't start it again.
+    held: Vec<(char, u8)>,
+}
+
+impl Default for Keyboard {
+    fn default() -> Self {
+        Self {
+            octave: DEFAULT_OCTAVE,
+            held: Vec::new(),
+        }
+    }
+}
+
+impl Keyboard {
+    // Whether it was one of the keys this plays with.  Keys are given as
+    // the character on them, which is what the host passes on.
+    pub fn press(&mut self, parameters: &WhisperParameters, key: char) -> bool {
+        let key = key.to_ascii_lowercase();
+        match key {
+            'z' => self.shift_octave(-1),
+            'x' => self.shift_octave(1),
+            _ => match self.note(key) {
+                Some(note) => {
+                    if self.held.iter().all(|&(held, _)| held != key) {
+                        parameters.play_note(note, VELOCITY);
+                        self.held.push((key, note));
+                    }
+                }
+                None => return KEYS.contains(key),
+            },
+        }
+        true
+    }
+
+    pub fn release(&mut self, parameters: &WhisperParameters, key: char) -> bool {
+        let key = key.to_ascii_lowercase();
+        if let Some(place) = self.held.iter().position(|&(held, _)| held == key) {
+            let (_, note) = self.held.remove(place);
+            parameters.play_note(note, 0);
+        }
+        key == 'z' || key == 'x' || KEYS.contains(key)
+    }
+
+    // Stop everything that's still held, for when the editor closes and the
+    // keys coming up would never be heard.
+    pub fn release_all(&mut self, parameters: &WhisperParameters) {
+        for (_, note) in self.held.drain(..) {
+            parameters.play_note(note, 0);
+        }
+    }
+
+    fn shift_octave(&mut self, by: i32) {
+        self.octave = (self.octave + by).max(MIN_OCTAVE).min(MAX_OCTAVE);
+    }
+
+    // Keys off the top of MIDI's range play nothing.
+    fn note(&self, key: char) -> Option<u8> {
+        let semitone = KEYS.find(key)? as i32;
+        let note = (self.octave + 1) * 12 + semitone;
+        if (0..128).contains(&note) {
+            Some(note as u8)
+        } else {
+            None
+        }
+    }
+}
+
+// The character on one of the keys, from the window's own keyboard events.
+pub fn key_character(key: VirtualKeyCode) -> Option<char> {
+    use VirtualKeyCode::*;
+    let character = match key {
+        A => 'a',
+        W => 'w',
+        S => 's',
+        E => 'e',
+        D => 'd',
+        F => 'f',
+        T => 't',
+        G => 'g',
+        Y => 'y',
+        H => 'h',
+        U => 'u',
+        J => 'j',
+        K => 'k',
+        O => 'o',
+        L => 'l',
+        P => 'p',
+        Semicolon => ';',
+        Z => 'z',
+        X => 'x',
+        _ => return None,
+    };
+    Some(character)
+}
@@ -0,0 +1,250 @@
+// The words on the editor, in whichever language is picked on the settings
+// page.  Everything is written in English to begin with, and looked up in
+// the other languages' tables by that, so anything a table is missing just
+// stays in English.  Parameters are only renamed on screen: the host, the
+// presets and the saved chunks all go by their English names, so those
+// can't ever change.
+
+use std::fmt::Display;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Language {
+    English,
+    Japanese,
+}
+
+impl Language {
+    pub const ALL: [Language; 2] = [Language::English, Language::Japanese];
+
+    // In the language itself, so it can be found by someone who can't
+    // read the rest.
+    pub fn name(self) -> &'static str {
+        match self {
+            Language::English => "English",
+            Language::Japanese => "日本語",
+        }
+    }
+
+    // A language that isn't there any more is English.
+    pub fn named(name: &str) -> Self {
+        Self::ALL
+            .iter()
+            .find(|language| language.name() == name)
+            .cloned()
+            .unwrap_or(Language::English)
+    }
+
+    pub fn text(self, english: &'static str) -> &'static str {
+        self.lookup(english).unwrap_or(english)
+    }
+
+    // The same for some text with values in it, one for each `{}`, which
+    // every language has in the same order.
+    pub fn fill(self, english: &'static str, values: &[&dyn Display]) -> String {
+        let mut filled = String::new();
+        for (place, piece) in self.text(english).split("{}").enumerate() {
+            if place > 0 {
+                if let Some(value) = values.get(place - 1) {
+                    filled.push_str(&value.to_string());
+                }
+            }
+            filled.push_str(piece);
+        }
+        filled
+    }
+
+    // The numbered ones, like "mod 3 source", are in the tables with a `{}`
+    // for the number.
+    pub fn parameter_name(self, name: &str) -> String {
+        let start = match name.find(|c: char| c.is_ascii_digit()) {
+            Some(start) => start,
+            None => return self.lookup(name).unwrap_or(name).to_string(),
+        };
+        let end = name[start..]
+            .find(|c: char| !c.is_ascii_digit())
+            .map_or(name.len(), |end| start + end);
+        let template = format!("{}{{}}{}", &name[..start], &name[end..]);
+        match self.lookup(&template) {
+            Some(translated) => translated.replacen("{}", &name[start..end], 1),
+            None => name.to_string(),
+        }
+    }
+
+    fn lookup(self, english: &str) -> Option<&'static str> {
+        let table = match self {
+            Language::English => return None,
+            Language::Japanese => JAPANESE,
+        };
+        table
+            .iter()
+            .find(|(from, _)| *from == english)
+            .map(|&(_, to)| to)
+    }
+}
+
+const JAPANESE: &[(&str, &str)] = &[
+    // The tabs.
+    ("Synth", "シンセ"),
+    ("FX", "エフェクト"),
+    ("Mod", "変調"),
+    ("Steps", "ステップ"),
+    ("Matrix", "マトリクス"),
+    ("Settings", "設定"),
+    // Buttons, and what they say when they're done.
+    ("Save", "保存"),
+    ("Load", "読込"),
+    ("Presets", "プリセット"),
+    ("Knobs", "ノブ"),
+    ("Panic", "パニック"),
+    ("Init", "初期化"),
+    ("Random", "ランダム"),
+    ("A to B", "A→B"),
+    ("Undo", "元に戻す"),
+    ("Redo", "やり直す"),
+    ("Rename", "名前変更"),
+    ("Delete", "削除"),
+    ("Close", "閉じる"),
+    ("Preset saved", "プリセットを保存しました"),
+    ("Preset loaded", "プリセットを読み込みました"),
+    ("Preset renamed", "プリセットの名前を変えました"),
+    ("Preset deleted", "プリセットを削除しました"),
+    (
+        "Factory presets can't be changed",
+        "ファクトリープリセットは変更できません",
+    ),
+    ("Sample loaded", "サンプルを読み込みました"),
+    ("Tuning loaded", "チューニングを読み込みました"),
+    ("{} (factory)", "{}（ファクトリー）"),
+    (
+        "Drop to load the preset",
+        "ドロップしてプリセットを読み込む",
+    ),
+    ("Drop to load the sample", "ドロップしてサンプルを読み込む"),
+    (
+        "Only presets and samples can be dropped here",
+        "ここにはプリセットとサンプルしかドロップできません",
+    ),
+    // Readings.
+    ("Octave {}", "オクターブ {}"),
+    (
+        "{} voices, CPU {}%, peak {}",
+        "ボイス {}、CPU {}%、ピーク {}",
+    ),
+    ("Default {} {}", "初期値 {} {}"),
+    // The settings page.
+    ("Tooltips after", "ツールチップの表示まで"),
+    ("Theme", "テーマ"),
+    ("Dark", "ダーク"),
+    ("Light", "ライト"),
+    ("UI scale", "UIの大きさ"),
+    ("Language", "言語"),
+    ("Frame rate", "フレームレート"),
+    // The about box.
+    ("Built {} from {}", "{} に {} からビルド"),
+    (
+        "Running at {} Hz, up to {} samples at a time",
+        "{} Hz で動作中、一度に最大 {} サンプル",
+    ),
+    // The parameters.
+    ("volume", "音量"),
+    ("waveform", "波形"),
+    ("attack", "アタック"),
+    ("decay", "ディケイ"),
+    ("sustain", "サステイン"),
+    ("release", "リリース"),
+    ("polyphony", "同時発音数"),
+    ("bend range", "ベンド幅"),
+    ("mod wheel", "モジュレーションホイール"),
+    ("cutoff", "カットオフ"),
+    ("resonance", "レゾナンス"),
+    ("filter mode", "フィルターモード"),
+    ("filter attack", "フィルター アタック"),
+    ("filter decay", "フィルター ディケイ"),
+    ("filter sustain", "フィルター サステイン"),
+    ("filter release", "フィルター リリース"),
+    ("filter env amount", "フィルター エンベロープ量"),
+    ("env {} attack", "エンベロープ{} アタック"),
+    ("env {} decay", "エンベロープ{} ディケイ"),
+    ("env {} sustain", "エンベロープ{} サステイン"),
+    ("env {} release", "エンベロープ{} リリース"),
+    ("glide", "グライド"),
+    ("voice mode", "ボイスモード"),
+    ("note priority", "ノート優先"),
+    ("unison voices", "ユニゾン数"),
+    ("unison detune", "ユニゾン デチューン"),
+    ("stereo spread", "ステレオの広がり"),
+    ("sub waveform", "サブ波形"),
+    ("sub level", "サブの音量"),
+    ("ring mod", "リングモジュレーション"),
+    ("synthesis mode", "合成方式"),
+    ("fm ratio", "FM 比率"),
+    ("fm index", "FM 深さ"),
+    ("lfo rate", "LFO 速さ"),
+    ("lfo depth", "LFO 深さ"),
+    ("lfo shape", "LFO 波形"),
+    ("lfo destination", "LFO 送り先"),
+    ("lfo sync", "LFO 同期"),
+    ("lfo division", "LFO 音価"),
+    ("lfo {} rate", "LFO {} 速さ"),
+    ("lfo {} shape", "LFO {} 波形"),
+    ("random rate", "ランダム 速さ"),
+    ("random sync", "ランダム 同期"),
+    ("random division", "ランダム 音価"),
+    ("mod {} source", "変調{} 送り元"),
+    ("mod {} destination", "変調{} 送り先"),
+    ("mod {} amount", "変調{} 量"),
+    ("delay time", "ディレイタイム"),
+    ("delay feedback", "ディレイ フィードバック"),
+    ("delay mix", "ディレイ ミックス"),
+    ("delay sync", "ディレイ 同期"),
+    ("delay division", "ディレイ 音価"),
+    ("reverb size", "リバーブ 大きさ"),
+    ("reverb damping", "リバーブ ダンピング"),
+    ("reverb mix", "リバーブ ミックス"),
+    ("drive", "ドライブ"),
+    ("drive curve", "ドライブ カーブ"),
+    ("drive trim", "ドライブ トリム"),
+    ("drive routing", "ドライブ 経路"),
+    ("bit depth", "ビット深度"),
+    ("downsample", "ダウンサンプル"),
+    ("eq low freq", "EQ 低域 周波数"),
+    ("eq low gain", "EQ 低域 ゲイン"),
+    ("eq mid freq", "EQ 中域 周波数"),
+    ("eq mid gain", "EQ 中域 ゲイン"),
+    ("eq high freq", "EQ 高域 周波数"),
+    ("eq high gain", "EQ 高域 ゲイン"),
+    ("limiter", "リミッター"),
+    ("pan", "パン"),
+    ("smoothing", "スムージング"),
+    ("humanize", "ヒューマナイズ"),
+    ("humanize seed", "ヒューマナイズ シード"),
+    ("velocity curve", "ベロシティカーブ"),
+    ("scale", "スケール"),
+    ("scale root", "スケール ルート"),
+    ("sample start", "サンプル 開始"),
+    ("sample end", "サンプル 終了"),
+    ("sample loop", "サンプル ループ"),
+    ("sample root", "サンプル ルート"),
+    ("grain size", "グレイン 大きさ"),
+    ("grain density", "グレイン 密度"),
+    ("pitch spray", "ピッチのばらつき"),
+    ("stereo spray", "定位のばらつき"),
+    ("drum tune", "ドラム チューン"),
+    ("drum decay", "ドラム ディケイ"),
+    ("arpeggiator", "アルペジエーター"),
+    ("arp mode", "アルペジオ モード"),
+    ("arp rate", "アルペジオ 速さ"),
+    ("arp gate", "アルペジオ ゲート"),
+    ("arp octaves", "アルペジオ オクターブ"),
+    ("arp latch", "アルペジオ ラッチ"),
+    ("sequencer", "シーケンサー"),
+    ("sequencer rate", "シーケンサー 速さ"),
+    ("reset on play", "再生時にリセット"),
+    ("step {} pitch", "ステップ{} ピッチ"),
+    ("step {} gate", "ステップ{} ゲート"),
+    ("step {} velocity", "ステップ{} ベロシティ"),
+    ("midi learn", "MIDI ラーン"),
+    ("midi output", "MIDI 出力"),
+    ("mpe", "MPE"),
+    ("program change", "プログラムチェンジ"),
+];
@@ -0,0 +1,90 @@
+use std::sync::atomic::{AtomicI32, Ordering};
+
+// Controllers from 120 up are channel mode messages like all notes off,
+// not knobs, so they can't be learned.
+pub const LEARNABLE_CONTROLLERS: usize = 120;
+
+const UNBOUND: i32 = -1;
+
+// Which parameter each MIDI controller has been bound to.  It's shared by
+// the GUI and the audio thread, so it's all atomics and neither ever has
+// to wait for the other.
+pub struct MidiLearn {
+    bindings: Vec<AtomicI32>,
+}
+
+impl Default for MidiLearn {
+    fn default() -> Self {
+        Self {
+            bindings: (0..LEARNABLE_CONTROLLERS)
+                .map(|_| AtomicI32::new(UNBOUND))
+                .collect(),
+        }
+    }
+}
+
+impl MidiLearn {
+    pub fn binding(&self, controller: u8) -> Option<i32> {
+        let parameter = self
+            .bindings
+            .get(usize::from(controller))?
+            .load(Ordering::Relaxed);
+        if parameter == UNBOUND {
+            None
+        } else {
+            Some(parameter)
+        }
+    }
+
+    // Bind `controller` to `parameter`, taking it off whatever it moved
+    // before.  A parameter only follows one controller, so any other
+    // controller bound to it is let go.  Learning a controller onto the
+    // parameter it already moves unbinds it, which is how bindings are
+    // got rid of.
+    pub fn learn(&self, controller: u8, parameter: i32) {
+        let slot = match self.bindings.get(usize::from(controller)) {
+            Some(slot) => slot,
+            None => return,
+        };
+        if slot.load(Ordering::Relaxed) == parameter {
+            slot.store(UNBOUND, Ordering::Relaxed);
+            return;
+        }
+        for other in self.bindings.iter() {
+            if other.load(Ordering::Relaxed) == parameter {
+                other.store(UNBOUND, Ordering::Relaxed);
+            }
+        }
+        slot.store(parameter, Ordering::Relaxed);
+    }
+
+    // Every binding as (controller, parameter), for saving.
+    pub fn bindings(&self) -> Vec<(u8, i32)> {
+        (0..LEARNABLE_CONTROLLERS as u8)
+            .filter_map(|controller| Some((controller, self.binding(controller)?)))
+            .collect()
+    }
+
+    // Replace every binding with the ones that were saved.
+    pub fn load(&self, bindings: &[(u8, i32)]) {
+        for slot in self.bindings.iter() {
+            slot.store(UNBOUND, Ordering::Relaxed);
+        }
+        for &(controller, parameter) in bindings {
+            if let Some(slot) = self.bindings.get(usize::from(controller)) {
+                slot.store(parameter, Ordering::Relaxed);
+            }
+        }
+    }
+}
+
+// Map a 0.0 to 1.0 parameter onto which of `count` parameters the next
+// controller gets bound to, with the bottom of the range meaning none.
+pub fn learn_target(value: f32, count: i32) -> Option<i32> {
+    let index = (value * count as f32).round() as i32;
+    if index <= 0 {
+        None
+    } else {
+        Some(index.min(count) - 1)
+    }
+}
@@ -0,0 +1,116 @@
+use crate::range::{Curve, Range};
+use rand::random;
+use std::f32::consts::PI;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum LfoShape {
+    Sine,
+    Triangle,
+    Square,
+    SampleAndHold,
+}
+
+impl LfoShape {
+    pub const ALL: [LfoShape; 4] = [
+        LfoShape::Sine,
+        LfoShape::Triangle,
+        LfoShape::Square,
+        LfoShape::SampleAndHold,
+    ];
+
+    pub fn from_parameter(value: f32) -> Self {
+        let index = (value * Self::ALL.len() as f32) as usize;
+        Self::ALL[index.min(Self::ALL.len() - 1)]
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            LfoShape::Sine => "Sine",
+            LfoShape::Triangle => "Triangle",
+            LfoShape::Square => "Square",
+            LfoShape::SampleAndHold => "S&H",
+        }
+    }
+}
+
+// What the LFO is wobbling.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum LfoDestination {
+    // Vibrato
+    Pitch,
+    // Tremolo
+    Amplitude,
+    Cutoff,
+}
+
+impl LfoDestination {
+    pub const ALL: [LfoDestination; 3] = [
+        LfoDestination::Pitch,
+        LfoDestination::Amplitude,
+        LfoDestination::Cutoff,
+    ];
+
+    pub fn from_parameter(value: f32) -> Self {
+        let index = (value * Self::ALL.len() as f32) as usize;
+        Self::ALL[index.min(Self::ALL.len() - 1)]
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            LfoDestination::Pitch => "Pitch",
+            LfoDestination::Amplitude => "Amplitude",
+            LfoDestination::Cutoff => "Cutoff",
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct Lfo {
+    phase: f32,
+    // The value sample-and-hold is currently holding.
+    held: f32,
+}
+
+impl Lfo {
+    // Go back to the start of the cycle.
+    pub fn reset(&mut self) {
+        self.phase = 0.0;
+    }
+
+    // Produce the next value, from -1.0 to 1.0.
+    pub fn next(&mut self, shape: LfoShape, rate: f32, sample_rate: f32) -> f32 {
+        let t = self.phase;
+        let value = match shape {
+            LfoShape::Sine => (t * 2.0 * PI).sin(),
+            LfoShape::Triangle => 1.0 - 4.0 * (t - 0.5).abs(),
+            LfoShape::Square => {
+                if t < 0.5 {
+                    1.0
+                } else {
+                    -1.0
+                }
+            }
+            LfoShape::SampleAndHold => self.held,
+        };
+
+        self.phase += rate / sample_rate;
+        if self.phase >= 1.0 {
+            self.phase -= 1.0;
+            // Pick a new value at the start of every cycle.
+            self.held = (random::<f32>() - 0.5) * 2.0;
+        }
+
+        value
+    }
+}
+
+// 0.05 Hz to 20 Hz, spread evenly over the octaves in between.
+pub const LFO_RATE: Range = Range {
+    min: 0.05,
+    max: 20.0,
+    curve: Curve::Log,
+};
+
+pub fn lfo_rate(value: f32) -> f32 {
+    LFO_RATE.map(value)
+}
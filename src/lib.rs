@@ -4,37 +4,1906 @@ extern crate vst;
 #[macro_use]
 extern crate conrod_core;
 
+mod about;
+mod aiff;
+mod arp;
+mod clipper;
+mod clock;
+mod crusher;
+mod delay;
+mod drive;
+mod drum;
+mod editor;
+mod envelope;
+mod eq;
+mod filter;
+mod fonts;
+mod gain;
+mod granular;
+mod history;
+mod keyboard;
+mod language;
+mod learn;
+mod lfo;
+mod matrix;
+mod midi;
+mod modulation;
+mod mpe;
+mod noise;
+mod notes;
+mod oscillator;
+mod pan;
+mod params;
+mod preset;
+mod queue;
+mod range;
+mod reverb;
+mod sample;
+mod scale;
+mod sequencer;
+mod settings;
+mod skin;
+mod smoother;
+mod snapshot;
+mod spectrum;
+mod state;
+mod support;
+mod sysex;
+mod telemetry;
+mod tempo;
+mod theme;
+mod tuning;
+mod velocity;
+mod voice;
+mod widgets;
+mod worker;
+
+use arp::{arp_gate, arp_octaves, ArpMode, ArpSettings, Arpeggiator};
+use clipper::soft_clip;
+use clock::MidiClock;
+use crusher::{bit_depth, downsample_factor, Crusher};
+use delay::{delay_feedback, delay_time, ring_time, Delay};
+use drive::{Drive, DriveRouting};
+use drum::{drum_decay, drum_tune, DrumSettings};
+use editor::GUIWrapper;
+use envelope::EnvelopeSettings;
+use eq::{eq_db, high_frequency, low_frequency, mid_frequency, Band, Equalizer};
+use filter::FilterMode;
+use gain::volume_gain;
+use granular::{grain_density, grain_size, pitch_spray, GrainSettings};
+use learn::{learn_target, MidiLearn};
+use lfo::{lfo_rate, Lfo, LfoDestination, LfoShape};
+use matrix::{ModDestination, ModSlot, ModSource, ModSources, MATRIX_SLOTS};
+use midi::{unit_value, MidiMessage, MidiOutput};
+use modulation::{Controllers, Destination, Route, ALL_NOTES_OFF, ALL_SOUND_OFF, MOD_WHEEL};
+use mpe::{Expression, MPE_BEND_RANGE, TIMBRE};
+use notes::{NotePriority, NoteTable};
+use num_traits::Float;
+use oscillator::{
+    bend_range, fm_index, fm_ratio, midi_pitch_to_freq, semitones_to_ratio, single_cycle,
+    sub_waveform, Waveform,
+};
+use pan::equal_power;
+use params::{parameter_defs, ParamDef};
+use preset::{
+    preset_path, rename_preset, user_name, Metadata, PresetEntry, PresetError, PresetFile,
+};
+use queue::{queue, Receiver, Sender};
 use rand::random;
-use std::os::raw::c_void;
-use std::sync::Arc;
+use reverb::{reverb_damping, reverb_tail, room_feedback, Reverb};
+use sample::{root_note, Sample, SampleError, SamplerSettings};
+use scale::{scale_root, Scale};
+use sequencer::{step_pitch, step_velocity, Sequencer, SequencerSettings, Step, SEQUENCER_STEPS};
+use smoother::{one_pole_coefficient, smoothing_time, Smoother};
+use snapshot::Snapshot;
+use state::{Program, State, PROGRAM_COUNT};
+use std::f32::consts::SQRT_2;
+use std::mem;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+use sysex::{dump, SysExCommand};
+use telemetry::Telemetry;
+use tempo::{host_transport, Division, Transport};
+use tuning::{Tuning, TuningError};
+use velocity::VelocityCurve;
+use voice::{
+    glide_time, humanize_seed, polyphony, unison_voices, Modulation, SynthesisMode, Unison,
+    VoiceAllocator, VoiceMode, VoiceSettings,
+};
 use vst::api::{Events, Supported};
-use vst::buffer::AudioBuffer;
+use vst::buffer::{AudioBuffer, SendEventBuffer};
 use vst::editor::Editor;
-use vst::event::Event;
-use vst::plugin::{CanDo, Category, Info, Plugin, PluginParameters};
+use vst::event::{Event, MidiEvent, SysExEvent};
+use vst::host::Host;
+use vst::plugin::{CanDo, Category, HostCallback, Info, Plugin, PluginParameters};
 use vst::util::AtomicFloat;
+use worker::Worker;
 
-#[derive(Default)]
 struct Whisper {
+    host: HostCallback,
     params: Arc<WhisperParameters>,
-    // Added a counter in our plugin struct.
-    notes: u8,
+    // Where the parameter that picks what the next MIDI controller moved
+    // gets bound to is, looked up once rather than for every controller.
+    midi_learn: Option<i32>,
+    // The audio thread's own copy of the parameter values, taken at the
+    // start of every buffer.
+    snapshot: Snapshot,
+    // Where messages from the GUI and the host arrive.
+    messages: Receiver<Message>,
+    // And where levels and the like go back to the GUI.
+    telemetry: Sender<Telemetry>,
+    // Everything we play, mixed down to mono, goes to the GUI's spectrum,
+    // and one in every `SCOPE_DECIMATION` samples of it to its scope.
+    // Which of those comes next carries on from one buffer to the next, so
+    // they stay evenly spaced.
+    tap: Sender<f32>,
+    scope: Sender<f32>,
+    scope_phase: usize,
+    sample_rate: f32,
+    block_size: usize,
+    // The host's tempo and playhead, read at the start of every buffer.
+    transport: Transport,
+    // Whether the host was playing during the last buffer.
+    playing: bool,
+    // Where the transport comes from when the host won't say.
+    clock: MidiClock,
+    // How many samples we've played since being loaded.
+    sample_time: u64,
+    voices: VoiceAllocator,
+    notes: NoteTable,
+    // What every key was moved to by the scale lock when it went down, so
+    // its note off still finds it if the scale has changed since.
+    quantized: [u8; 128],
+    // The pitch wheel position from -1.0 to 1.0, and the position we are
+    // actually playing, which follows it smoothly.
+    pitch_bend: f32,
+    bend: Smoother,
+    controllers: Controllers,
+    // Channel pressure, from 0.0 to 1.0.
+    aftertouch: f32,
+    // In MPE mode, the expression last sent on every channel, which the
+    // next note there starts with.
+    expression: [Expression; 16],
+    lfo: Lfo,
+    lfo2: Lfo,
+    // The random modulation source is a sample-and-hold LFO of its own.
+    random: Lfo,
+    // The continuous parameters, as they are actually heard.
+    volume: Smoother,
+    cutoff: Smoother,
+    resonance: Smoother,
+    lfo_depth: Smoother,
+    pan: Smoother,
+    crusher: Crusher,
+    delay: Delay,
+    equalizer: Equalizer,
+    // The delay time in samples, smoothed so changing it bends the pitch of
+    // the echoes like a tape delay rather than clicking.
+    delay_samples: Smoother,
+    reverb: Reverb,
+    // The smoothing time the smoothers were last set up for.
+    smoothing: f32,
+    // The sampler's sound, as of the last time we could look.
+    sample: Option<Arc<Sample>>,
+    // MIDI events received for the upcoming buffer, waiting for their sample.
+    events: Vec<MidiEvent>,
+    sequencer: Sequencer,
+    arpeggiator: Arpeggiator,
+    // What the sequencer or arpeggiator made of `events`, kept around to
+    // save allocating.
+    generated: Vec<MidiEvent>,
+    // The note offs they send when everything's let go, which can only go
+    // out to the host with a buffer's events.
+    note_offs: Vec<MidiEvent>,
+    // Where the worker's jobs go, its SysEx dumps come back to be sent out,
+    // and the buffers it has free for copying SysEx messages into.
+    jobs: Sender<Job>,
+    // A job the worker's queue was too full for, which is tried again next
+    // buffer.  Until it goes, nothing else that has to reach the worker is
+    // taken on.
+    pending: Option<Job>,
+    replies: Receiver<Vec<u8>>,
+    buffers: Receiver<Vec<u8>>,
+    // Only held on to so it stops when we're dropped.
+    _worker: Worker,
+    send_buffer: SendEventBuffer,
+    // Where we render before copying into the host's channels, sized from
+    // the block size so the audio thread doesn't have to allocate.
+    left: Vec<f32>,
+    right: Vec<f32>,
+}
+
+impl Default for Whisper {
+    fn default() -> Self {
+        let (sender, messages) = queue(MESSAGE_CAPACITY);
+        let (telemetry, readings) = queue(TELEMETRY_CAPACITY);
+        let (tap, samples) = queue(TAP_CAPACITY);
+        let (scope, scope_samples) = queue(SCOPE_CAPACITY);
+        let (jobs, mut work) = queue(JOB_CAPACITY);
+        let (reply, replies) = queue(REPLY_CAPACITY);
+        let (free, buffers) = queue(SYSEX_BUFFERS);
+        for _ in 0..SYSEX_BUFFERS {
+            let _ = free.send(Vec::with_capacity(SYSEX_CAPACITY));
+        }
+        let params = Arc::new(WhisperParameters {
+            messages: sender,
+            telemetry: Mutex::new(readings),
+            tap: Mutex::new(samples),
+            scope: Mutex::new(scope_samples),
+            ..WhisperParameters::default()
+        });
+        let snapshot = Snapshot::new(&params);
+        let shared = Arc::clone(&params);
+        let worker = Worker::spawn(move || {
+            while let Some(job) = work.receive() {
+                shared.work(job, &reply, &free);
+            }
+        });
+        Self {
+            host: HostCallback::default(),
+            midi_learn: params.parameter_index("midi learn"),
+            params,
+            snapshot,
+            messages,
+            telemetry,
+            tap,
+            scope,
+            scope_phase: 0,
+            sample_rate: 44100.0,
+            block_size: 1024,
+            transport: Transport::default(),
+            playing: false,
+            clock: MidiClock::default(),
+            sample_time: 0,
+            voices: VoiceAllocator::default(),
+            notes: NoteTable::default(),
+            quantized: [0; 128],
+            pitch_bend: 0.0,
+            bend: Smoother::new(0.0),
+            controllers: Controllers::default(),
+            aftertouch: 0.0,
+            expression: [Expression::default(); 16],
+            lfo: Lfo::default(),
+            lfo2: Lfo::default(),
+            random: Lfo::default(),
+            volume: Smoother::new(1.0),
+            cutoff: Smoother::new(1.0),
+            resonance: Smoother::new(0.0),
+            lfo_depth: Smoother::new(0.0),
+            pan: Smoother::new(0.0),
+            crusher: Crusher::default(),
+            delay: Delay::new(44100.0),
+            equalizer: Equalizer::default(),
+            delay_samples: Smoother::new(0.0),
+            reverb: Reverb::new(44100.0),
+            smoothing: -1.0,
+            sample: None,
+            events: Vec::with_capacity(1024),
+            sequencer: Sequencer::default(),
+            arpeggiator: Arpeggiator::default(),
+            generated: Vec::with_capacity(1024),
+            note_offs: Vec::with_capacity(1024),
+            jobs,
+            pending: None,
+            replies,
+            buffers,
+            _worker: worker,
+            send_buffer: SendEventBuffer::new(1024),
+            left: vec![0.0; 1024],
+            right: vec![0.0; 1024],
+        }
+    }
 }
 
+impl Whisper {
+    fn process_midi_event(&mut self, data: [u8; 3]) {
+        if let Some(message) = MidiMessage::parse(&data) {
+            self.process_message(message);
+        }
+    }
+
+    // Outside of MPE mode, messages on every channel are treated the same.
+    fn process_message(&mut self, message: MidiMessage) {
+        if let Some(channel) = message.channel() {
+            if channel != 0
+                && is_on(self.snapshot.get(&self.params.mpe))
+                && self.process_mpe(channel, message)
+            {
+                return;
+            }
+        }
+
+        match message {
+            MidiMessage::NoteOn { note, velocity, .. } => {
+                self.note_on(note, velocity, Expression::default())
+            }
+            MidiMessage::NoteOff { note, .. } => self.note_off(note),
+            MidiMessage::PitchBend { bend, .. } => self.pitch_bend = bend,
+            MidiMessage::ControlChange {
+                controller, value, ..
+            } => match controller {
+                ALL_SOUND_OFF => self.kill_all(),
+                ALL_NOTES_OFF => self.release_all(),
+                controller => {
+                    self.learn_controller(controller, value);
+                    self.controllers.set(controller, value);
+                }
+            },
+            MidiMessage::PolyPressure { note, pressure, .. } => {
+                self.voices.pressure(note, unit_value(pressure))
+            }
+            // A program change picks one of the presets, unless that's been
+            // switched off for hosts that send them when they shouldn't.
+            // Picking one copies every parameter around, so it's up to the
+            // worker.
+            MidiMessage::ProgramChange { program, .. }
+                if is_on(self.snapshot.get(&self.params.program_change)) =>
+            {
+                let _ = self.jobs.send(Job::ProgramChange(i32::from(program)));
+            }
+            MidiMessage::ChannelPressure { pressure, .. } => self.aftertouch = unit_value(pressure),
+            _ => (),
+        }
+    }
+
+    // SysEx is left to the worker, since answering a dump request means
+    // encoding the whole bank and restoring a dump means loading one.  All
+    // that's done here is copying the message into one of its buffers, so
+    // nothing is allocated, and a message that doesn't fit or finds none
+    // free is dropped.  So is one that comes while a job is still waiting
+    // for the worker, since there'd be nowhere to keep its buffer should the
+    // worker have no room for that either.
+    fn process_sysex(&mut self, payload: &[u8]) {
+        if self.pending.is_some() {
+            return;
+        }
+        let mut buffer = match self.buffers.receive() {
+            Some(buffer) => buffer,
+            None => return,
+        };
+        let job = if payload.len() <= buffer.capacity() {
+            buffer.extend_from_slice(payload);
+            Job::SysEx(buffer)
+        } else {
+            Job::Recycle(buffer)
+        };
+        self.hand_over(job);
+    }
+
+    // Move whatever parameter the controller is bound to, first binding it
+    // if we're learning.  The host is told about every change, so it can
+    // show it and record it as automation.
+    fn learn_controller(&mut self, controller: u8, value: u8) {
+        let learn = learn_target(self.params.midi_learn.get(), self.params.parameter_count());
+        let midi_learn = self.midi_learn;
+        if let Some(parameter) = learn.filter(|&parameter| Some(parameter) != midi_learn) {
+            self.params.learn.learn(controller, parameter);
+            self.params.midi_learn.set(0.0);
+            if let Some(midi_learn) = midi_learn {
+                self.host.automate(midi_learn, 0.0);
+            }
+        }
+        if let Some(parameter) = self.params.learn.binding(controller) {
+            let value = unit_value(value);
+            self.params.set_parameter(parameter, value);
+            self.host.automate(parameter, value);
+        }
+    }
+
+    // In MPE mode, everything but the first channel carries a single note,
+    // and the bend, pressure and timbre sent there only go to that note.
+    // Returns whether the message was one of those, anything else is
+    // treated as if it had come in on the first channel.
+    fn process_mpe(&mut self, channel: u8, message: MidiMessage) -> bool {
+        let expression = &mut self.expression[usize::from(channel)];
+        expression.channel = channel;
+        match message {
+            MidiMessage::NoteOn { note, velocity, .. } => {
+                let expression = *expression;
+                self.note_on(note, velocity, expression);
+                return true;
+            }
+            MidiMessage::PitchBend { bend, .. } => expression.bend = bend * MPE_BEND_RANGE,
+            MidiMessage::ChannelPressure { pressure, .. } => {
+                expression.pressure = unit_value(pressure)
+            }
+            MidiMessage::ControlChange {
+                controller: TIMBRE,
+                value,
+                ..
+            } => expression.timbre = unit_value(value),
+            _ => return false,
+        }
+        self.voices.set_expression(*expression);
+        true
+    }
+
+    // Start a voice playing that pitch.  If the key was somehow already
+    // down, the old voice is released so the note retriggers rather than
+    // stacking up voices that will never receive their note off.
+    fn note_on(&mut self, note: u8, velocity: u8, expression: Expression) {
+        let scale = Scale::from_parameter(self.snapshot.get(&self.params.scale));
+        let root = scale_root(self.snapshot.get(&self.params.scale_root));
+        let key = usize::from(note & 0x7f);
+        self.quantized[key] = scale.quantize(root, note);
+        let note = self.quantized[key];
+
+        let mode = VoiceMode::from_parameter(self.snapshot.get(&self.params.voice_mode));
+        if self.notes.note_on(note) {
+            self.voices.note_off(note, mode, None);
+        }
+        // A mono voice keeps playing what it was if the new key doesn't
+        // take priority over it.  The key is still remembered, for when the
+        // others are let go.
+        if mode != VoiceMode::Poly && self.notes.held_note(self.note_priority()) != Some(note) {
+            return;
+        }
+        let velocity =
+            VelocityCurve::from_parameter(self.snapshot.get(&self.params.velocity_curve))
+                .apply(velocity);
+        let polyphony = polyphony(self.snapshot.get(&self.params.polyphony));
+        self.voices
+            .note_on(note, velocity, expression, polyphony, mode);
+    }
+
+    // Let the voice playing that pitch fade out, ignoring keys that aren't
+    // held.  In mono modes we go back to the last key that's still down.
+    fn note_off(&mut self, note: u8) {
+        let note = self.quantized[usize::from(note & 0x7f)];
+        if self.notes.note_off(note) {
+            let mode = VoiceMode::from_parameter(self.snapshot.get(&self.params.voice_mode));
+            let fallback = self.notes.held_note(self.note_priority());
+            self.voices.note_off(note, mode, fallback);
+        }
+    }
+
+    fn note_priority(&self) -> NotePriority {
+        NotePriority::from_parameter(self.snapshot.get(&self.params.note_priority))
+    }
+
+    // The whole DSP runs in `f32`, this is only generic over what the host
+    // wants the output written as.
+    fn process_buffer<T: Float>(&mut self, buffer: &mut AudioBuffer<T>) {
+        let started = Instant::now();
+        // Only what came from the host, before the arpeggiator and the
+        // sequencer add theirs.
+        let midi = !self.events.is_empty();
+        // `buffer.split()` gives us a tuple containing the
+        // input and output buffers.  We only care about the
+        // output, so we can ignore the input by using `_`.
+        let samples = buffer.samples();
+        let (_, mut output_buffer) = buffer.split();
+        self.snapshot.take(&self.params);
+
+        // Hosts that don't tell us about their transport can still send
+        // MIDI clock.  This buffer's ticks are only counted after, so the
+        // transport is always where things stood as the buffer started.
+        let clock = &self.clock;
+        let (sample_time, sample_rate) = (self.sample_time, self.sample_rate);
+        self.transport = host_transport(&self.host)
+            .or_else(|| clock.transport(sample_time, sample_rate))
+            .unwrap_or_default();
+        self.clock
+            .process(&self.events, self.sample_time, self.sample_rate);
+        self.sample_time += samples as u64;
+        self.follow_transport();
+        self.update_smoothing();
+
+        // Only we get to speak to the host, so the worker leaves it to us to
+        // say when it's changed the programs.
+        if self.params.refresh.swap(false, Ordering::Relaxed) {
+            self.host.update_display();
+        }
+
+        // Whatever the worker couldn't take last time goes first.
+        if let Some(job) = self.pending.take() {
+            self.hand_over(job);
+        }
+
+        // Whatever the GUI and the host have sent since the last buffer.
+        // While a job is still waiting, the rest wait in their queue too.
+        while self.pending.is_none() {
+            let message = match self.messages.receive() {
+                Some(message) => message,
+                None => break,
+            };
+            match message {
+                // Freeing what they replace is left to the worker.
+                Message::Sample(sample) => {
+                    if let Some(old) = mem::replace(&mut self.sample, sample) {
+                        self.hand_over(Job::Sample(old));
+                    }
+                }
+                Message::Tuning(tuning) => {
+                    if let Some(old) = self.voices.set_tuning(tuning) {
+                        self.hand_over(Job::Tuning(old));
+                    }
+                }
+                Message::Panic => self.kill_all(),
+                Message::Midi(message) => self.process_message(message),
+            }
+        }
+        self.voices.set_humanize(
+            self.snapshot.get(&self.params.humanize),
+            humanize_seed(self.snapshot.get(&self.params.humanize_seed)),
+        );
+
+        let settings = VoiceSettings {
+            mode: SynthesisMode::from_parameter(self.snapshot.get(&self.params.synthesis_mode)),
+            waveform: Waveform::from_parameter(self.snapshot.get(&self.params.waveform)),
+            fm_ratio: fm_ratio(self.snapshot.get(&self.params.fm_ratio)),
+            fm_index: fm_index(self.snapshot.get(&self.params.fm_index)),
+            envelope: self
+                .params
+                .envelope_settings(&self.snapshot, self.sample_rate),
+            filter_mode: FilterMode::from_parameter(self.snapshot.get(&self.params.filter_mode)),
+            filter_envelope: self
+                .params
+                .filter_envelope_settings(&self.snapshot, self.sample_rate),
+            filter_envelope_amount: bipolar(self.snapshot.get(&self.params.filter_env_amount)),
+            aux_envelope: self
+                .params
+                .aux_envelope_settings(&self.snapshot, self.sample_rate),
+            glide: one_pole_coefficient(
+                glide_time(self.snapshot.get(&self.params.glide)),
+                self.sample_rate,
+            ),
+            unison: Unison::new(
+                unison_voices(self.snapshot.get(&self.params.unison)),
+                self.snapshot.get(&self.params.unison_detune),
+                self.snapshot.get(&self.params.stereo_spread),
+            ),
+            sub_waveform: sub_waveform(self.snapshot.get(&self.params.sub_waveform)),
+            sub_level: self.snapshot.get(&self.params.sub_level),
+            ring_mod: self.snapshot.get(&self.params.ring_mod),
+            sample: self.sample.clone(),
+            sampler: SamplerSettings {
+                root_frequency: midi_pitch_to_freq(root_note(
+                    self.snapshot.get(&self.params.sample_root),
+                )),
+                start: self.snapshot.get(&self.params.sample_start),
+                end: self.snapshot.get(&self.params.sample_end),
+                looping: is_on(self.snapshot.get(&self.params.sample_loop)),
+            },
+            grains: GrainSettings {
+                size: grain_size(self.snapshot.get(&self.params.grain_size)),
+                density: grain_density(self.snapshot.get(&self.params.grain_density)),
+                pitch_spray: pitch_spray(self.snapshot.get(&self.params.pitch_spray)),
+                stereo_spray: self.snapshot.get(&self.params.stereo_spray),
+            },
+            drum: DrumSettings {
+                decay: drum_decay(self.snapshot.get(&self.params.drum_decay)),
+                tune: drum_tune(self.snapshot.get(&self.params.drum_tune)),
+            },
+            matrix: self.params.matrix(&self.snapshot),
+            drive: match DriveRouting::from_parameter(self.snapshot.get(&self.params.drive_routing))
+            {
+                DriveRouting::Voice => Some(self.params.drive(&self.snapshot)),
+                DriveRouting::Mix => None,
+            },
+            sample_rate: self.sample_rate,
+        };
+
+        // Should the host break its promise about the block size, we grow
+        // rather than crash.
+        if self.left.len() < samples {
+            self.left.resize(samples, 0.0);
+            self.right.resize(samples, 0.0);
+        }
+        // The buffers are taken out of `self` while we render into them, so
+        // the render functions can still borrow `self` mutably.
+        let mut rendered_left = mem::replace(&mut self.left, Vec::new());
+        let mut rendered_right = mem::replace(&mut self.right, Vec::new());
+        let left = &mut rendered_left[..samples];
+        let right = &mut rendered_right[..samples];
+
+        // Switching the sequencer or arpeggiator off lets go of whatever
+        // it was playing.
+        let sequence = is_on(self.snapshot.get(&self.params.sequencer));
+        let arpeggiate = is_on(self.snapshot.get(&self.params.arp));
+        if !sequence {
+            self.sequencer.stop(&mut self.events);
+        }
+        if !arpeggiate {
+            self.arpeggiator.stop(&mut self.events);
+        }
+
+        // Events are usually sorted already, but hosts aren't required to.
+        self.events.sort_by_key(|ev| ev.delta_frames);
+
+        // Both swap the notes that came in for their own, the sequencer
+        // first so its notes can be arpeggiated too.
+        let beats_per_sample = self.transport.beats_per_sample(self.sample_rate);
+        if sequence {
+            self.sequencer.process(
+                &self.params.sequencer_settings(&self.snapshot),
+                &self.transport,
+                beats_per_sample,
+                samples,
+                &self.events,
+                &mut self.generated,
+            );
+            mem::swap(&mut self.events, &mut self.generated);
+            self.generated.clear();
+        }
+        if arpeggiate {
+            self.arpeggiator.process(
+                &self.params.arp_settings(&self.snapshot),
+                &self.transport,
+                beats_per_sample,
+                samples,
+                &self.events,
+                &mut self.generated,
+            );
+            mem::swap(&mut self.events, &mut self.generated);
+            self.generated.clear();
+        }
+
+        // Some hosts only take one lot of events from us a buffer, so it all
+        // goes out at once: the note offs from letting everything go since
+        // the last buffer and what we're about to play, unless MIDI output is
+        // off, and then whatever dump the worker has ready.  With a job
+        // already waiting for the worker, the dump waits in its queue, since
+        // its buffer goes back to the worker afterwards rather than being
+        // freed here.
+        let output = MidiOutput::from_parameter(self.snapshot.get(&self.params.midi_output));
+        let (note_offs, playing): (&[MidiEvent], &[MidiEvent]) = if output != MidiOutput::Off {
+            (&self.note_offs, &self.events)
+        } else {
+            (&[], &[])
+        };
+        let reply = if self.pending.is_none() {
+            self.replies.receive()
+        } else {
+            None
+        };
+        let dump = reply.iter().map(|reply| {
+            Event::SysEx(SysExEvent {
+                payload: reply,
+                delta_frames: 0,
+            })
+        });
+        let events = note_offs
+            .iter()
+            .chain(playing)
+            .map(|&ev| Event::Midi(ev))
+            .chain(dump);
+        self.send_buffer.send_events(events, &mut self.host);
+        self.note_offs.clear();
+        if let Some(reply) = reply {
+            self.hand_over(Job::Recycle(reply));
+        }
+        // When the notes are only for other plugins, we keep out of it and
+        // only listen to the controllers.
+        if output == MidiOutput::Only {
+            self.events.retain(|ev| match MidiMessage::parse(&ev.data) {
+                Some(MidiMessage::NoteOn { .. }) | Some(MidiMessage::NoteOff { .. }) => false,
+                _ => true,
+            });
+        }
+
+        // Render up to each event, apply it, and carry on from there, so
+        // every note starts and stops on exactly the right sample.
+        let mut start = 0;
+        for i in 0..self.events.len() {
+            let ev = self.events[i];
+            let end = (ev.delta_frames.max(0) as usize).min(samples);
+            self.render(&mut left[start..end], &mut right[start..end], &settings);
+            self.process_midi_event(ev.data);
+            start = end;
+        }
+        self.events.clear();
+        self.render(&mut left[start..], &mut right[start..], &settings);
+
+        self.process_effects(left, right);
+
+        // Nobody reads these while the GUI is closed, so once the queue is
+        // full the rest are dropped.
+        let mut telemetry = Telemetry::measure(left, right);
+        telemetry.voices = self.voices.active_voices();
+        let (envelope, filter_envelope) = self.voices.newest_envelopes();
+        telemetry.envelope = envelope;
+        telemetry.filter_envelope = filter_envelope;
+        telemetry.step = self.sequencer.current_step();
+        telemetry.midi = midi;
+        // What's left to do after this is a copy, so it's near enough the
+        // whole of it.  Hosts sometimes ask for no samples at all, which
+        // takes no time to play and counts as no load.
+        if samples > 0 {
+            let lasts = samples as f32 / self.sample_rate;
+            telemetry.load = started.elapsed().as_secs_f32() / lasts;
+        }
+        let _ = self.telemetry.send(telemetry);
+        for (l, r) in left.iter().zip(right.iter()) {
+            let sample = (l + r) / 2.0;
+            let _ = self.tap.send(sample);
+            if self.scope_phase == 0 {
+                let _ = self.scope.send(sample);
+            }
+            self.scope_phase = (self.scope_phase + 1) % SCOPE_DECIMATION;
+        }
+
+        // Copy what we rendered into the host's channels.  Should there be
+        // more than two (if you are working with surround sound), they
+        // alternate between left and right.
+        for (i, output_channel) in output_buffer.into_iter().enumerate() {
+            let rendered = if i % 2 == 0 { &*left } else { &*right };
+            for (output_sample, &sample) in output_channel.iter_mut().zip(rendered.iter()) {
+                *output_sample = num_traits::cast(sample).unwrap_or_else(T::zero);
+            }
+        }
+
+        self.left = rendered_left;
+        self.right = rendered_right;
+    }
+
+    // Dropping a job here could mean freeing what's in it on the audio
+    // thread, so one that doesn't fit waits to be handed over next buffer.
+    fn hand_over(&mut self, job: Job) {
+        if let Err(job) = self.jobs.send(job) {
+            self.pending = Some(job);
+        }
+    }
+
+    // Starting and stopping the host has to start and stop what else is
+    // keeping time.
+    fn follow_transport(&mut self) {
+        let playing = self.transport.playing;
+        if self.playing && !playing {
+            // Hosts don't always send note offs when they stop, and nothing
+            // should be left droning on afterwards.
+            self.release_all();
+        }
+        if !self.playing && playing && is_on(self.snapshot.get(&self.params.transport_reset)) {
+            // Starting every LFO and pattern from the top means playing the
+            // same part of the song always sounds the same.
+            self.lfo.reset();
+            self.lfo2.reset();
+            self.random.reset();
+            self.arpeggiator.restart();
+            self.voices.restart_seed();
+        }
+        self.playing = playing;
+    }
+
+    // Let go of every note, as if all the keys had been released at once.
+    // The sequencer and arpeggiator forget their notes too, or they would
+    // just start them again.  Their note offs only matter to whatever is
+    // playing our MIDI output, every voice here is being let go anyway, and
+    // they wait to go out with the next buffer's events, since this can
+    // happen while the host isn't processing.
+    fn release_all(&mut self) {
+        self.sequencer.stop(&mut self.note_offs);
+        self.arpeggiator.stop(&mut self.note_offs);
+        self.notes.clear();
+        self.voices.release_all();
+    }
+
+    // Silence everything at once, echoes included.
+    fn kill_all(&mut self) {
+        self.release_all();
+        self.voices.kill_all();
+        self.delay.clear();
+        self.reverb.clear();
+    }
+
+    // Everything that happens to the mixed signal of all the voices.
+    fn process_effects(&mut self, left: &mut [f32], right: &mut [f32]) {
+        // Driving the mix comes before the time-based effects, so the echoes
+        // of a distorted note aren't distorted all over again.
+        if DriveRouting::from_parameter(self.snapshot.get(&self.params.drive_routing))
+            == DriveRouting::Mix
+        {
+            let drive = self.params.drive(&self.snapshot);
+            for sample in left.iter_mut().chain(right.iter_mut()) {
+                *sample = drive.process(*sample);
+            }
+        }
+
+        let bits = bit_depth(self.snapshot.get(&self.params.bit_depth));
+        let downsample = downsample_factor(self.snapshot.get(&self.params.downsample));
+        for (left_sample, right_sample) in left.iter_mut().zip(right.iter_mut()) {
+            self.crusher
+                .process(left_sample, right_sample, bits, downsample);
+        }
+
+        self.process_delay(left, right);
+        self.process_reverb(left, right);
+
+        // The EQ shapes the finished sound, reverb included.
+        let (low, mid, high) = self.params.eq_bands(&self.snapshot);
+        self.equalizer.set(low, mid, high, self.sample_rate);
+        for (left_sample, right_sample) in left.iter_mut().zip(right.iter_mut()) {
+            self.equalizer.process(left_sample, right_sample);
+        }
+
+        // The clipper goes last, so nothing after it can push us past ±1.0.
+        if is_on(self.snapshot.get(&self.params.limiter)) {
+            for sample in left.iter_mut().chain(right.iter_mut()) {
+                *sample = soft_clip(*sample);
+            }
+        }
+    }
+
+    fn process_delay(&mut self, left: &mut [f32], right: &mut [f32]) {
+        let delay = self.delay_seconds() * self.sample_rate;
+        let feedback = delay_feedback(self.snapshot.get(&self.params.delay_feedback));
+        let mix = self.snapshot.get(&self.params.delay_mix);
+
+        for (left_sample, right_sample) in left.iter_mut().zip(right.iter_mut()) {
+            let delay = self.delay_samples.next(delay);
+            self.delay
+                .process(left_sample, right_sample, delay, feedback, mix);
+        }
+    }
+
+    // When synced, the delay time is a note length at the host's tempo instead of ms.
+    fn delay_seconds(&self) -> f32 {
+        if is_on(self.snapshot.get(&self.params.delay_sync)) {
+            Division::from_parameter(self.snapshot.get(&self.params.delay_division))
+                .seconds(self.transport.tempo)
+        } else {
+            delay_time(self.snapshot.get(&self.params.delay_time))
+        }
+    }
+
+    fn process_reverb(&mut self, left: &mut [f32], right: &mut [f32]) {
+        let feedback = room_feedback(self.snapshot.get(&self.params.reverb_size));
+        let damping = reverb_damping(self.snapshot.get(&self.params.reverb_damping));
+        let mix = self.snapshot.get(&self.params.reverb_mix);
+
+        for (left_sample, right_sample) in left.iter_mut().zip(right.iter_mut()) {
+            self.reverb
+                .process(left_sample, right_sample, feedback, damping, mix);
+        }
+    }
+
+    // Recompute the smoothing coefficients, but only if something they depend on changed.
+    fn update_smoothing(&mut self) {
+        let smoothing = smoothing_time(self.snapshot.get(&self.params.smoothing));
+        if smoothing == self.smoothing {
+            return;
+        }
+        self.smoothing = smoothing;
+
+        let sample_rate = self.sample_rate;
+        for smoother in [
+            &mut self.volume,
+            &mut self.cutoff,
+            &mut self.resonance,
+            &mut self.lfo_depth,
+            &mut self.pan,
+        ]
+        .iter_mut()
+        {
+            smoother.set_time(smoothing, sample_rate);
+        }
+
+        // Glide towards the pitch wheel position over a few milliseconds, so
+        // the steps between MIDI messages aren't audible.
+        self.bend.set_time(0.005, sample_rate);
+        self.delay_samples.set_time(0.05, sample_rate);
+    }
+
+    fn render(&mut self, left: &mut [f32], right: &mut [f32], settings: &VoiceSettings) {
+        // Work out where every continuous parameter should end up, controllers included.
+        let routes = self.params.routes(&self.snapshot);
+        // Volume is smoothed as a linear gain, which is what the audio is
+        // actually multiplied by.
+        let volume = volume_gain(self.snapshot.get(&self.params.volume));
+        let volume = match self.controllers.amount(&routes, Destination::Volume) {
+            Some(amount) => volume * amount,
+            None => volume,
+        };
+        let cutoff = self.snapshot.get(&self.params.cutoff)
+            + self
+                .controllers
+                .amount(&routes, Destination::Cutoff)
+                .unwrap_or(0.0);
+        let resonance = self.snapshot.get(&self.params.resonance);
+        let lfo_depth = match self.controllers.amount(&routes, Destination::LfoDepth) {
+            Some(amount) => self.snapshot.get(&self.params.lfo_depth) * amount,
+            None => self.snapshot.get(&self.params.lfo_depth),
+        };
+        let pan = bipolar(self.snapshot.get(&self.params.pan));
+
+        // We only want to run the voices if a note is sounding, which
+        // includes one that has been released but is still fading out.
+        // Else, we can fill the output with silence, and the effects after
+        // us still get to ring out on their own.
+        if !self.voices.is_active() {
+            for output_sample in left.iter_mut().chain(right.iter_mut()) {
+                *output_sample = 0.0;
+            }
+            // Nothing is playing, so there is nothing to glide.
+            self.bend.reset(self.pitch_bend);
+            self.volume.reset(volume);
+            self.cutoff.reset(cutoff);
+            self.resonance.reset(resonance);
+            self.lfo_depth.reset(lfo_depth);
+            self.pan.reset(pan);
+            return;
+        }
+
+        let bend_range = bend_range(self.snapshot.get(&self.params.bend_range));
+
+        let lfo_shape = LfoShape::from_parameter(self.snapshot.get(&self.params.lfo_shape));
+        // When synced, the rate is a note length at the host's tempo instead of Hz.
+        let lfo_rate = if is_on(self.snapshot.get(&self.params.lfo_sync)) {
+            Division::from_parameter(self.snapshot.get(&self.params.lfo_division))
+                .frequency(self.transport.tempo)
+        } else {
+            lfo_rate(self.snapshot.get(&self.params.lfo_rate))
+        };
+        let lfo_destination =
+            LfoDestination::from_parameter(self.snapshot.get(&self.params.lfo_destination));
+        // The second LFO only goes to the modulation matrix.
+        let lfo2_shape = LfoShape::from_parameter(self.snapshot.get(&self.params.lfo2_shape));
+        let lfo2_rate = lfo::lfo_rate(self.snapshot.get(&self.params.lfo2_rate));
+        let random_rate = if is_on(self.snapshot.get(&self.params.random_sync)) {
+            Division::from_parameter(self.snapshot.get(&self.params.random_division))
+                .frequency(self.transport.tempo)
+        } else {
+            lfo::lfo_rate(self.snapshot.get(&self.params.random_rate))
+        };
+
+        for (left_sample, right_sample) in left.iter_mut().zip(right.iter_mut()) {
+            let bend = self.bend.next(self.pitch_bend);
+            let lfo_depth = self.lfo_depth.next(lfo_depth);
+            let lfo1 = self.lfo.next(lfo_shape, lfo_rate, self.sample_rate);
+            let lfo = lfo1 * lfo_depth;
+            let lfo2 = self.lfo2.next(lfo2_shape, lfo2_rate, self.sample_rate);
+            let random = self
+                .random
+                .next(LfoShape::SampleAndHold, random_rate, self.sample_rate);
+
+            // At full depth, vibrato swings two semitones either way, tremolo
+            // goes all the way down to silence, and the cutoff moves by half
+            // of its range.
+            let mut semitones = bend * bend_range;
+            let mut amplitude = self.volume.next(volume);
+            let mut modulation_cutoff = self.cutoff.next(cutoff);
+            match lfo_destination {
+                LfoDestination::Pitch => semitones += lfo * 2.0,
+                LfoDestination::Amplitude => amplitude *= 1.0 - (lfo + lfo_depth) * 0.5,
+                LfoDestination::Cutoff => modulation_cutoff += lfo * 0.5,
+            }
+
+            let modulation = Modulation {
+                pitch: semitones_to_ratio(semitones),
+                cutoff: modulation_cutoff,
+                resonance: self.resonance.next(resonance),
+                sources: ModSources {
+                    lfo1,
+                    lfo2,
+                    random,
+                    mod_wheel: self.controllers.get(MOD_WHEEL),
+                    aftertouch: self.aftertouch,
+                    ..ModSources::default()
+                },
+            };
+            let (voices_left, voices_right) = self.voices.render(settings, &modulation);
+            // The equal-power law is scaled so the middle is unity gain,
+            // which leaves the sound untouched at the default setting.
+            let (pan_left, pan_right) = equal_power(self.pan.next(pan));
+            *left_sample = voices_left * amplitude * pan_left * SQRT_2;
+            *right_sample = voices_right * amplitude * pan_right * SQRT_2;
+        }
+    }
+}
+
+// What the GUI and the host hand the audio thread, which picks them up at
+// the start of every buffer.
+enum Message {
+    // A sample to play, or `None` for none.
+    Sample(Option<Arc<Sample>>),
+    // A tuning to play in, or `None` for the usual one.
+    Tuning(Option<Arc<Tuning>>),
+    // Stop every sound at once.
+    Panic,
+    // A note played in the editor, taken as if it had come in over MIDI.
+    Midi(MidiMessage<'static>),
+}
+
+// What the audio thread leaves for the worker to do.  Should the worker
+// ever stop keeping up, program changes that don't fit in its queue are
+// dropped, and everything else waits for a place.
+enum Job {
+    // Pick a program, or run one of the commands after them.
+    ProgramChange(i32),
+    // A SysEx message, copied into one of the worker's buffers.
+    SysEx(Vec<u8>),
+    // A buffer the audio thread is finished with, which goes back to being
+    // one of those if it's big enough.
+    Recycle(Vec<u8>),
+    // A sample and a tuning that were replaced, to be freed here.  The
+    // audio thread might be holding the last reference to them.
+    Sample(Arc<Sample>),
+    Tuning(Arc<Tuning>),
+}
+
+// Things that can be done to the program being played.  They come after
+// the bank in the host's list of programs, so picking one there runs it
+// rather than switching programs.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Command {
+    Randomize,
+    Init,
+}
+
+impl Command {
+    const ALL: [Command; 2] = [Command::Randomize, Command::Init];
+
+    fn name(self) -> &'static str {
+        match self {
+            Command::Randomize => "<Randomize>",
+            Command::Init => "<Init>",
+        }
+    }
+}
+
+// Two versions of a sound to flip between, to hear what some tweaks did.
+// Only the one not being played is kept here, the other being in the
+// parameters themselves.
+#[derive(Default)]
+struct Compare {
+    other: Option<Vec<f32>>,
+    on_b: bool,
+}
+
+// How many messages can be waiting for the audio thread at once.
+const MESSAGE_CAPACITY: usize = 64;
+
+// How many jobs can be waiting for the worker, and how many SysEx dumps
+// for the audio thread to send out.
+const JOB_CAPACITY: usize = 64;
+const REPLY_CAPACITY: usize = 4;
+
+// The biggest SysEx message we take in, a good few times what a whole
+// bank's dump comes to, and how many can be on their way to the worker at
+// once.
+const SYSEX_CAPACITY: usize = 1 << 20;
+const SYSEX_BUFFERS: usize = 2;
+
+// And how many buffers' telemetry for the GUI, which is plenty to cover the
+// time between two of its frames.
+const TELEMETRY_CAPACITY: usize = 256;
+
+// And how many samples for its spectrum, a good few frames' worth.  Its
+// scope only shows one in every few samples, which is plenty for a few
+// cycles of anything but the lowest notes.
+const TAP_CAPACITY: usize = 16384;
+const SCOPE_DECIMATION: usize = 4;
+const SCOPE_CAPACITY: usize = TAP_CAPACITY / SCOPE_DECIMATION;
+
 struct WhisperParameters {
+    // The files the sampler's sound and the tuning came from.  What's
+    // loaded from them is sent to the audio thread, along with everything
+    // else in `messages`, which the GUI and the host can both send to
+    // without waiting on each other.
+    sample_path: Mutex<Option<String>>,
+    tuning_path: Mutex<Option<String>>,
+    messages: Sender<Message>,
+    // Whether the worker has changed the programs behind the host's back.
+    refresh: AtomicBool,
+    // Only the GUI ever locks these.
+    telemetry: Mutex<Receiver<Telemetry>>,
+    tap: Mutex<Receiver<f32>>,
+    scope: Mutex<Receiver<f32>>,
+    // What the audio thread is running at, for the GUI to work out
+    // frequencies with.
+    sample_rate: AtomicFloat,
+    // And the most it's asked for at once, for the about box.
+    block_size: AtomicUsize,
+    // The bank of programs, and which one is picked.  The picked program
+    // lives in the parameters themselves while it's being played, and is
+    // only copied back into the bank when another one is picked.
+    programs: Mutex<Vec<Program>>,
+    program: AtomicUsize,
+    compare: Mutex<Compare>,
+    // Which parameters have changed since the GUI last looked, other than
+    // by the GUI itself, so it knows when to redraw.
+    changed: Vec<AtomicBool>,
+    // The parameters the GUI's XY pad moves, across and up.
+    xy_pad: Mutex<(i32, i32)>,
+    // What every parameter is called, starts out at and reads as, in the
+    // order the host sees them.
+    defs: Vec<ParamDef>,
+    // What every parameter starts out at.
+    defaults: Vec<f32>,
+    mod_matrix: [ModSlotParameters; MATRIX_SLOTS],
+    sequence: [StepParameters; SEQUENCER_STEPS],
+    learn: MidiLearn,
     volume: AtomicFloat,
+    waveform: AtomicFloat,
+    attack: AtomicFloat,
+    decay: AtomicFloat,
+    sustain: AtomicFloat,
+    release: AtomicFloat,
+    polyphony: AtomicFloat,
+    bend_range: AtomicFloat,
+    mod_wheel: AtomicFloat,
+    cutoff: AtomicFloat,
+    resonance: AtomicFloat,
+    filter_mode: AtomicFloat,
+    filter_attack: AtomicFloat,
+    filter_decay: AtomicFloat,
+    filter_sustain: AtomicFloat,
+    filter_release: AtomicFloat,
+    filter_env_amount: AtomicFloat,
+    lfo_shape: AtomicFloat,
+    lfo_rate: AtomicFloat,
+    lfo_depth: AtomicFloat,
+    lfo_destination: AtomicFloat,
+    lfo_sync: AtomicFloat,
+    lfo_division: AtomicFloat,
+    smoothing: AtomicFloat,
+    voice_mode: AtomicFloat,
+    glide: AtomicFloat,
+    unison: AtomicFloat,
+    unison_detune: AtomicFloat,
+    stereo_spread: AtomicFloat,
+    pan: AtomicFloat,
+    limiter: AtomicFloat,
+    delay_time: AtomicFloat,
+    delay_sync: AtomicFloat,
+    delay_division: AtomicFloat,
+    delay_feedback: AtomicFloat,
+    delay_mix: AtomicFloat,
+    reverb_size: AtomicFloat,
+    reverb_damping: AtomicFloat,
+    reverb_mix: AtomicFloat,
+    drive_curve: AtomicFloat,
+    drive: AtomicFloat,
+    drive_trim: AtomicFloat,
+    drive_routing: AtomicFloat,
+    bit_depth: AtomicFloat,
+    downsample: AtomicFloat,
+    eq_low_gain: AtomicFloat,
+    eq_low_frequency: AtomicFloat,
+    eq_mid_gain: AtomicFloat,
+    eq_mid_frequency: AtomicFloat,
+    eq_high_gain: AtomicFloat,
+    eq_high_frequency: AtomicFloat,
+    sub_level: AtomicFloat,
+    sub_waveform: AtomicFloat,
+    synthesis_mode: AtomicFloat,
+    fm_ratio: AtomicFloat,
+    fm_index: AtomicFloat,
+    ring_mod: AtomicFloat,
+    sample_root: AtomicFloat,
+    sample_start: AtomicFloat,
+    sample_end: AtomicFloat,
+    sample_loop: AtomicFloat,
+    grain_size: AtomicFloat,
+    grain_density: AtomicFloat,
+    pitch_spray: AtomicFloat,
+    stereo_spray: AtomicFloat,
+    drum_decay: AtomicFloat,
+    drum_tune: AtomicFloat,
+    velocity_curve: AtomicFloat,
+    lfo2_shape: AtomicFloat,
+    lfo2_rate: AtomicFloat,
+    aux_attack: AtomicFloat,
+    aux_decay: AtomicFloat,
+    aux_sustain: AtomicFloat,
+    aux_release: AtomicFloat,
+    random_rate: AtomicFloat,
+    random_sync: AtomicFloat,
+    random_division: AtomicFloat,
+    arp: AtomicFloat,
+    arp_mode: AtomicFloat,
+    arp_division: AtomicFloat,
+    arp_gate: AtomicFloat,
+    arp_octaves: AtomicFloat,
+    arp_latch: AtomicFloat,
+    sequencer: AtomicFloat,
+    sequencer_division: AtomicFloat,
+    transport_reset: AtomicFloat,
+    mpe: AtomicFloat,
+    midi_learn: AtomicFloat,
+    program_change: AtomicFloat,
+    midi_output: AtomicFloat,
+    note_priority: AtomicFloat,
+    scale: AtomicFloat,
+    scale_root: AtomicFloat,
+    humanize: AtomicFloat,
+    humanize_seed: AtomicFloat,
 }
 
-impl Default for WhisperParameters {
+// The three parameters of one modulation matrix slot.
+struct ModSlotParameters {
+    source: AtomicFloat,
+    destination: AtomicFloat,
+    amount: AtomicFloat,
+}
+
+impl Default for ModSlotParameters {
     fn default() -> Self {
         Self {
-            volume: AtomicFloat::new(1.0),
+            source: AtomicFloat::new(0.0),
+            destination: AtomicFloat::new(0.0),
+            amount: AtomicFloat::new(0.0),
+        }
+    }
+}
+
+impl ModSlotParameters {
+    fn slot(&self, snapshot: &Snapshot) -> ModSlot {
+        ModSlot {
+            source: ModSource::from_parameter(snapshot.get(&self.source)),
+            destination: ModDestination::from_parameter(snapshot.get(&self.destination)),
+            amount: bipolar(snapshot.get(&self.amount)),
+        }
+    }
+}
+
+struct StepParameters {
+    pitch: AtomicFloat,
+    gate: AtomicFloat,
+    velocity: AtomicFloat,
+}
+
+impl Default for StepParameters {
+    fn default() -> Self {
+        Self {
+            pitch: AtomicFloat::new(0.0),
+            gate: AtomicFloat::new(0.0),
+            velocity: AtomicFloat::new(0.0),
+        }
+    }
+}
+
+impl StepParameters {
+    fn step(&self, snapshot: &Snapshot) -> Step {
+        Step {
+            pitch: step_pitch(snapshot.get(&self.pitch)),
+            gate: f64::from(snapshot.get(&self.gate)),
+            velocity: step_velocity(snapshot.get(&self.velocity)),
+        }
+    }
+}
+
+impl Default for WhisperParameters {
+    fn default() -> Self {
+        // Parameters all start at zero here, and then at their defaults from
+        // the list of them.
+        let defs = parameter_defs();
+        let mut parameters = Self {
+            sample_path: Mutex::new(None),
+            tuning_path: Mutex::new(None),
+            messages: queue(MESSAGE_CAPACITY).0,
+            refresh: AtomicBool::new(false),
+            telemetry: Mutex::new(queue(TELEMETRY_CAPACITY).1),
+            tap: Mutex::new(queue(1).1),
+            scope: Mutex::new(queue(1).1),
+            sample_rate: AtomicFloat::new(44100.0),
+            block_size: AtomicUsize::new(1024),
+            programs: Mutex::new(Vec::new()),
+            program: AtomicUsize::new(0),
+            compare: Mutex::new(Compare::default()),
+            changed: defs.iter().map(|_| AtomicBool::new(false)).collect(),
+            xy_pad: Mutex::new((0, 0)),
+            defs,
+            defaults: Vec::new(),
+            mod_matrix: Default::default(),
+            sequence: Default::default(),
+            learn: MidiLearn::default(),
+            volume: AtomicFloat::new(0.0),
+            waveform: AtomicFloat::new(0.0),
+            attack: AtomicFloat::new(0.0),
+            decay: AtomicFloat::new(0.0),
+            sustain: AtomicFloat::new(0.0),
+            release: AtomicFloat::new(0.0),
+            polyphony: AtomicFloat::new(0.0),
+            bend_range: AtomicFloat::new(0.0),
+            mod_wheel: AtomicFloat::new(0.0),
+            cutoff: AtomicFloat::new(0.0),
+            resonance: AtomicFloat::new(0.0),
+            filter_mode: AtomicFloat::new(0.0),
+            filter_attack: AtomicFloat::new(0.0),
+            filter_decay: AtomicFloat::new(0.0),
+            filter_sustain: AtomicFloat::new(0.0),
+            filter_release: AtomicFloat::new(0.0),
+            filter_env_amount: AtomicFloat::new(0.0),
+            lfo_shape: AtomicFloat::new(0.0),
+            lfo_rate: AtomicFloat::new(0.0),
+            lfo_depth: AtomicFloat::new(0.0),
+            lfo_destination: AtomicFloat::new(0.0),
+            lfo_sync: AtomicFloat::new(0.0),
+            lfo_division: AtomicFloat::new(0.0),
+            smoothing: AtomicFloat::new(0.0),
+            voice_mode: AtomicFloat::new(0.0),
+            glide: AtomicFloat::new(0.0),
+            unison: AtomicFloat::new(0.0),
+            unison_detune: AtomicFloat::new(0.0),
+            stereo_spread: AtomicFloat::new(0.0),
+            pan: AtomicFloat::new(0.0),
+            limiter: AtomicFloat::new(0.0),
+            delay_time: AtomicFloat::new(0.0),
+            delay_sync: AtomicFloat::new(0.0),
+            delay_division: AtomicFloat::new(0.0),
+            delay_feedback: AtomicFloat::new(0.0),
+            delay_mix: AtomicFloat::new(0.0),
+            reverb_size: AtomicFloat::new(0.0),
+            reverb_damping: AtomicFloat::new(0.0),
+            reverb_mix: AtomicFloat::new(0.0),
+            drive_curve: AtomicFloat::new(0.0),
+            drive: AtomicFloat::new(0.0),
+            drive_trim: AtomicFloat::new(0.0),
+            drive_routing: AtomicFloat::new(0.0),
+            bit_depth: AtomicFloat::new(0.0),
+            downsample: AtomicFloat::new(0.0),
+            eq_low_gain: AtomicFloat::new(0.0),
+            eq_low_frequency: AtomicFloat::new(0.0),
+            eq_mid_gain: AtomicFloat::new(0.0),
+            eq_mid_frequency: AtomicFloat::new(0.0),
+            eq_high_gain: AtomicFloat::new(0.0),
+            eq_high_frequency: AtomicFloat::new(0.0),
+            sub_level: AtomicFloat::new(0.0),
+            sub_waveform: AtomicFloat::new(0.0),
+            synthesis_mode: AtomicFloat::new(0.0),
+            fm_ratio: AtomicFloat::new(0.0),
+            fm_index: AtomicFloat::new(0.0),
+            ring_mod: AtomicFloat::new(0.0),
+            sample_root: AtomicFloat::new(0.0),
+            sample_start: AtomicFloat::new(0.0),
+            sample_end: AtomicFloat::new(0.0),
+            sample_loop: AtomicFloat::new(0.0),
+            grain_size: AtomicFloat::new(0.0),
+            grain_density: AtomicFloat::new(0.0),
+            pitch_spray: AtomicFloat::new(0.0),
+            stereo_spray: AtomicFloat::new(0.0),
+            drum_decay: AtomicFloat::new(0.0),
+            drum_tune: AtomicFloat::new(0.0),
+            velocity_curve: AtomicFloat::new(0.0),
+            lfo2_shape: AtomicFloat::new(0.0),
+            lfo2_rate: AtomicFloat::new(0.0),
+            aux_attack: AtomicFloat::new(0.0),
+            aux_decay: AtomicFloat::new(0.0),
+            aux_sustain: AtomicFloat::new(0.0),
+            aux_release: AtomicFloat::new(0.0),
+            random_rate: AtomicFloat::new(0.0),
+            random_sync: AtomicFloat::new(0.0),
+            random_division: AtomicFloat::new(0.0),
+            arp: AtomicFloat::new(0.0),
+            arp_mode: AtomicFloat::new(0.0),
+            arp_division: AtomicFloat::new(0.0),
+            arp_gate: AtomicFloat::new(0.0),
+            arp_octaves: AtomicFloat::new(0.0),
+            arp_latch: AtomicFloat::new(0.0),
+            sequencer: AtomicFloat::new(0.0),
+            sequencer_division: AtomicFloat::new(0.0),
+            transport_reset: AtomicFloat::new(0.0),
+            mpe: AtomicFloat::new(0.0),
+            midi_learn: AtomicFloat::new(0.0),
+            program_change: AtomicFloat::new(0.0),
+            midi_output: AtomicFloat::new(0.0),
+            note_priority: AtomicFloat::new(0.0),
+            scale: AtomicFloat::new(0.0),
+            scale_root: AtomicFloat::new(0.0),
+            humanize: AtomicFloat::new(0.0),
+            humanize_seed: AtomicFloat::new(0.0),
+        };
+        for def in parameters.defs.iter() {
+            def.value(&parameters).set(def.default);
+        }
+        // The XY pad starts out on the filter.
+        let cutoff = parameters.parameter_index("cutoff").unwrap_or(0);
+        let resonance = parameters.parameter_index("resonance").unwrap_or(0);
+        *parameters.xy_pad.lock().unwrap() = (cutoff, resonance);
+        // Every program starts out as the default sound.
+        parameters.defaults = parameters.parameter_values();
+        let init = Program {
+            name: "Init".to_string(),
+            parameters: parameters.defaults.clone(),
+        };
+        *parameters.programs.lock().unwrap() = vec![init; PROGRAM_COUNT];
+        parameters
+    }
+}
+
+impl WhisperParameters {
+    // Load a file into the sampler.  The path is remembered even when that
+    // fails, so a project whose sample has gone missing doesn't forget it.
+    fn load_sample(&self, path: &str) -> Result<(), SampleError> {
+        *self.sample_path.lock().unwrap() = Some(path.to_string());
+        match Sample::load(Path::new(path)) {
+            Ok(sample) => {
+                self.send(Message::Sample(Some(Arc::new(sample))));
+                Ok(())
+            }
+            Err(error) => {
+                self.send(Message::Sample(None));
+                Err(error)
+            }
+        }
+    }
+
+    // Load a Scala or AnaMark tuning file.  Just like a sample, the path is
+    // kept even if it can't be loaded, and the usual tuning is used until it
+    // can.  An empty path goes back to the usual tuning.
+    fn load_tuning(&self, path: &str) -> Result<(), TuningError> {
+        if path.is_empty() {
+            *self.tuning_path.lock().unwrap() = None;
+            self.send(Message::Tuning(None));
+            return Ok(());
+        }
+        *self.tuning_path.lock().unwrap() = Some(path.to_string());
+        match Tuning::load(Path::new(path)) {
+            Ok(tuning) => {
+                self.send(Message::Tuning(Some(Arc::new(tuning))));
+                Ok(())
+            }
+            Err(error) => {
+                self.send(Message::Tuning(None));
+                Err(error)
+            }
+        }
+    }
+
+    fn run(&self, command: Command) {
+        match command {
+            Command::Randomize => self.randomize(),
+            Command::Init => self.init(),
+        }
+    }
+
+    // Pick a new value for every parameter that has a sensible range to
+    // pick from, leaving the rest as they are.
+    fn randomize(&self) {
+        for (index, def) in self.defs.iter().enumerate() {
+            if let Some((low, high)) = def.random {
+                self.set_parameter(index as i32, low + (high - low) * random::<f32>());
+            }
+        }
+    }
+
+    // Go back to the default sound.
+    fn init(&self) {
+        self.set_values(&self.defaults);
+        self.set_preset_name("Init".to_string());
+    }
+
+    fn set_values(&self, values: &[f32]) {
+        for (index, &value) in values.iter().enumerate() {
+            self.set_parameter(index as i32, value);
+        }
+    }
+
+    // The same from the GUI, which tells the host about them itself.
+    fn edit_values(&self, values: &[f32]) {
+        for (index, &value) in values.iter().enumerate() {
+            self.edit_parameter(index as i32, value);
+        }
+    }
+
+    // Swap over to the other A/B slot.  The first time, it starts out as a
+    // copy of this one.  Only the GUI has these, and it puts them in the
+    // history itself.
+    fn toggle_compare(&self) {
+        let mut compare = self.compare.lock().unwrap();
+        if let Some(other) = compare.other.replace(self.parameter_values()) {
+            self.edit_values(&other);
+        }
+        compare.on_b = !compare.on_b;
+    }
+
+    // Copy A over B, whichever of them is being played.
+    fn copy_a_to_b(&self) {
+        let mut compare = self.compare.lock().unwrap();
+        if !compare.on_b {
+            compare.other = Some(self.parameter_values());
+        } else if let Some(a) = &compare.other {
+            self.edit_values(a);
+        }
+    }
+
+    // Whatever the audio thread has left for the worker, which runs this.
+    fn work(&self, job: Job, replies: &Sender<Vec<u8>>, buffers: &Sender<Vec<u8>>) {
+        match job {
+            Job::ProgramChange(program) => {
+                self.change_preset(program);
+                self.refresh.store(true, Ordering::Relaxed);
+            }
+            Job::SysEx(mut message) => {
+                self.process_sysex(&message, replies);
+                message.clear();
+                let _ = buffers.send(message);
+            }
+            // A sent dump is only as big as it needed to be, and isn't kept.
+            Job::Recycle(mut buffer) => {
+                if buffer.capacity() >= SYSEX_CAPACITY {
+                    buffer.clear();
+                    let _ = buffers.send(buffer);
+                }
+            }
+            Job::Sample(sample) => drop(sample),
+            Job::Tuning(tuning) => drop(tuning),
+        }
+    }
+
+    // Dumps are answered with the events of the audio thread's next buffer,
+    // that being where it's allowed to send them.  Restoring one does
+    // everything loading a project would, so it's not something to do in
+    // the middle of a performance.
+    fn process_sysex(&self, payload: &[u8], replies: &Sender<Vec<u8>>) {
+        let message = match MidiMessage::parse(payload) {
+            Some(MidiMessage::SysEx(message)) => message,
+            _ => return,
+        };
+        match SysExCommand::parse(message) {
+            Some(SysExCommand::DumpRequest) => {
+                let _ = replies.send(dump(&self.encode(&self.bank())));
+            }
+            Some(SysExCommand::Dump(chunk)) => {
+                if self.load_chunk(&chunk) {
+                    self.refresh.store(true, Ordering::Relaxed);
+                }
+            }
+            None => (),
+        }
+    }
+
+    fn on_b(&self) -> bool {
+        self.compare.lock().unwrap().on_b
+    }
+
+    // Hand a message to the audio thread.  The queue only fills up when the
+    // host has stopped processing for a long while, and anything sent then
+    // is dropped.
+    fn send(&self, message: Message) {
+        let _ = self.messages.send(message);
+    }
+
+    // Everything the audio thread has measured since the last time, or
+    // `None` if it hasn't played a buffer since.
+    fn telemetry(&self) -> Option<Telemetry> {
+        let mut readings = self.telemetry.lock().unwrap();
+        let mut telemetry = readings.receive()?;
+        while let Some(later) = readings.receive() {
+            telemetry.merge(&later);
+        }
+        Some(telemetry)
+    }
+
+    // Add whatever the tap has been sent since the last time to `samples`.
+    fn tap(&self, samples: &mut Vec<f32>) {
+        receive_all(&mut self.tap.lock().unwrap(), samples);
+    }
+
+    // The same for the scope's samples.
+    fn scope(&self, samples: &mut Vec<f32>) {
+        receive_all(&mut self.scope.lock().unwrap(), samples);
+    }
+
+    // Stop every voice and clear out the echoes, for when something's stuck.
+    fn panic(&self) {
+        self.send(Message::Panic);
+    }
+
+    // Start or stop a note, on the first channel.
+    fn play_note(&self, note: u8, velocity: u8) {
+        let channel = 0;
+        self.send(Message::Midi(if velocity > 0 {
+            MidiMessage::NoteOn {
+                channel,
+                note,
+                velocity,
+            }
+        } else {
+            MidiMessage::NoteOff { channel, note }
+        }));
+    }
+
+    // One cycle of the oscillator as it's set now, for the editor to show,
+    // or nothing when the sound comes from somewhere else.
+    fn cycle(&self, length: usize) -> Vec<f32> {
+        let waveform = Waveform::from_parameter(self.waveform.get());
+        match SynthesisMode::from_parameter(self.synthesis_mode.get()) {
+            SynthesisMode::Subtractive => single_cycle(waveform, None, length),
+            SynthesisMode::Fm => {
+                let fm = (fm_ratio(self.fm_ratio.get()), fm_index(self.fm_index.get()));
+                single_cycle(waveform, Some(fm), length)
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    // How many parameters the host sees.
+    fn parameter_count(&self) -> i32 {
+        self.defs.len() as i32
+    }
+
+    fn parameter_values(&self) -> Vec<f32> {
+        (0..self.parameter_count())
+            .map(|index| self.get_parameter(index))
+            .collect()
+    }
+
+    // Setting a parameter from the GUI, which already knows about it.
+    fn edit_parameter(&self, index: i32, value: f32) {
+        if let Some(def) = self.defs.get(index as usize) {
+            def.value(self).set(def.snap(value));
+        }
+    }
+
+    // Whether a parameter has changed since the last time this was asked.
+    fn take_change(&self, index: i32) -> bool {
+        match self.changed.get(index as usize) {
+            Some(changed) => changed.swap(false, Ordering::Relaxed),
+            None => false,
+        }
+    }
+
+    // Where the parameter with this name is, if there is one.
+    fn parameter_index(&self, name: &str) -> Option<i32> {
+        self.defs
+            .iter()
+            .position(|def| def.name == name)
+            .map(|index| index as i32)
+    }
+
+    // Saved chunks go by these, so renaming a parameter means adding it to
+    // `state::RENAMED` too.
+    fn parameter_names(&self) -> Vec<String> {
+        (0..self.parameter_count())
+            .map(|index| self.get_parameter_name(index))
+            .collect()
+    }
+
+    fn encode(&self, state: &State) -> Vec<u8> {
+        state.encode(&self.parameter_names())
+    }
+
+    // Whether it was a chunk we could read.
+    fn load_chunk(&self, data: &[u8]) -> bool {
+        match State::decode(data, &self.parameter_names(), &self.defaults) {
+            Some(state) => {
+                self.load_state(&state);
+                true
+            }
+            None => false,
+        }
+    }
+
+    // Save the sound being played to the preset file called `name`, which
+    // becomes the program's name too.  Saving over a preset keeps its author
+    // and tags.
+    fn save_preset(&self, name: &str) -> Result<(), PresetError> {
+        let path = preset_path(name)?;
+        let metadata = match PresetFile::load(&path) {
+            Ok(file) => file.metadata,
+            Err(_) => Metadata {
+                author: user_name(),
+                ..Metadata::default()
+            },
+        };
+        self.set_preset_name(name.to_string());
+        let file = PresetFile {
+            metadata: Metadata {
+                name: name.to_string(),
+                ..metadata
+            },
+            sound: self.state().to_saved(&self.parameter_names()),
+        };
+        file.save(&path)
+    }
+
+    // Load the preset file called `name` into the program being played.
+    fn load_preset(&self, name: &str) -> Result<(), PresetError> {
+        self.load_preset_file(&preset_path(name)?)
+    }
+
+    // Rename one of the user's presets, and the program being played with
+    // it if that's where it came from.
+    fn rename_preset(&self, preset: &PresetEntry, new: &str) -> Result<(), PresetError> {
+        rename_preset(&preset.path, new)?;
+        if self.get_preset_name(self.get_preset_num()) == preset.name {
+            self.set_preset_name(new.to_string());
+        }
+        Ok(())
+    }
+
+    // The same for a preset's file, which might be a factory one.
+    fn load_preset_file(&self, path: &Path) -> Result<(), PresetError> {
+        let file = PresetFile::load(path)?;
+        let mut state = State::from_saved(file.sound, &self.parameter_names(), &self.defaults);
+        if !file.metadata.name.is_empty() {
+            state.name = file.metadata.name;
+        }
+        self.load_state(&state);
+        Ok(())
+    }
+
+    // Everything about the sound being played, for saving one preset.
+    fn state(&self) -> State {
+        let program = self.program.load(Ordering::Relaxed);
+        State {
+            parameters: self.parameter_values(),
+            sample_path: self.sample_path.lock().unwrap().clone(),
+            bindings: self.learn.bindings(),
+            tuning_path: self.tuning_path.lock().unwrap().clone(),
+            name: self.programs.lock().unwrap()[program].name.clone(),
+            programs: Vec::new(),
+            program,
+            other_slot: None,
+            on_b: false,
+            xy_pad: Some(*self.xy_pad.lock().unwrap()),
+        }
+    }
+
+    // The same, along with every program in the bank and the A/B compare.
+    fn bank(&self) -> State {
+        let mut state = self.state();
+        let mut programs = self.programs.lock().unwrap();
+        programs[state.program].parameters = state.parameters.clone();
+        state.programs = programs.clone();
+        let compare = self.compare.lock().unwrap();
+        state.other_slot = compare.other.clone();
+        state.on_b = compare.on_b;
+        state
+    }
+
+    // A preset goes into the program being played, and a bank replaces
+    // them all.
+    fn load_state(&self, state: &State) {
+        for (index, &value) in state.parameters.iter().enumerate() {
+            self.set_parameter(index as i32, value);
+        }
+        if !state.programs.is_empty() {
+            self.load_bank(&state.programs);
+            self.program
+                .store(state.program.min(PROGRAM_COUNT - 1), Ordering::Relaxed);
+            *self.compare.lock().unwrap() = Compare {
+                other: state.other_slot.clone(),
+                on_b: state.on_b,
+            };
+        }
+        if !state.name.is_empty() {
+            self.set_preset_name(state.name.clone());
+        }
+        self.learn.load(&state.bindings);
+        if let Some(xy_pad) = state.xy_pad {
+            *self.xy_pad.lock().unwrap() = xy_pad;
+        }
+        let _ = self.load_tuning(state.tuning_path.as_ref().map_or("", String::as_str));
+        match &state.sample_path {
+            // A missing file just leaves the sampler silent.
+            Some(path) => {
+                let _ = self.load_sample(path);
+            }
+            None => {
+                *self.sample_path.lock().unwrap() = None;
+                self.send(Message::Sample(None));
+            }
+        }
+    }
+
+    // Programs saved before some of the parameters existed leave those at
+    // their defaults, and a short bank is filled up with the default sound.
+    fn load_bank(&self, bank: &[Program]) {
+        let defaults = Program {
+            name: "Init".to_string(),
+            parameters: self.defaults.clone(),
+        };
+        let mut programs = self.programs.lock().unwrap();
+        for (index, program) in programs.iter_mut().enumerate() {
+            *program = defaults.clone();
+            if let Some(saved) = bank.get(index) {
+                program.name = saved.name.clone();
+                let count = saved.parameters.len().min(program.parameters.len());
+                program.parameters[..count].copy_from_slice(&saved.parameters[..count]);
+            }
+        }
+    }
+
+    // What the audio thread makes of the values in its snapshot.  First,
+    // where each MIDI controller is routed to.
+    fn routes(&self, snapshot: &Snapshot) -> [Route; 1] {
+        [Route {
+            controller: MOD_WHEEL,
+            destination: Destination::from_parameter(snapshot.get(&self.mod_wheel)),
+        }]
+    }
+
+    fn matrix(&self, snapshot: &Snapshot) -> [ModSlot; MATRIX_SLOTS] {
+        let mut slots = [self.mod_matrix[0].slot(snapshot); MATRIX_SLOTS];
+        for (slot, parameters) in slots.iter_mut().zip(self.mod_matrix.iter()) {
+            *slot = parameters.slot(snapshot);
+        }
+        slots
+    }
+
+    fn drive(&self, snapshot: &Snapshot) -> Drive {
+        Drive::from_parameters(
+            snapshot.get(&self.drive_curve),
+            snapshot.get(&self.drive),
+            snapshot.get(&self.drive_trim),
+        )
+    }
+
+    fn eq_bands(&self, snapshot: &Snapshot) -> (Band, Band, Band) {
+        (
+            Band {
+                frequency: low_frequency(snapshot.get(&self.eq_low_frequency)),
+                db: eq_db(snapshot.get(&self.eq_low_gain)),
+            },
+            Band {
+                frequency: mid_frequency(snapshot.get(&self.eq_mid_frequency)),
+                db: eq_db(snapshot.get(&self.eq_mid_gain)),
+            },
+            Band {
+                frequency: high_frequency(snapshot.get(&self.eq_high_frequency)),
+                db: eq_db(snapshot.get(&self.eq_high_gain)),
+            },
+        )
+    }
+
+    fn envelope_settings(&self, snapshot: &Snapshot, sample_rate: f32) -> EnvelopeSettings {
+        EnvelopeSettings::from_parameters(
+            snapshot.get(&self.attack),
+            snapshot.get(&self.decay),
+            snapshot.get(&self.sustain),
+            snapshot.get(&self.release),
+            sample_rate,
+        )
+    }
+
+    fn filter_envelope_settings(&self, snapshot: &Snapshot, sample_rate: f32) -> EnvelopeSettings {
+        EnvelopeSettings::from_parameters(
+            snapshot.get(&self.filter_attack),
+            snapshot.get(&self.filter_decay),
+            snapshot.get(&self.filter_sustain),
+            snapshot.get(&self.filter_release),
+            sample_rate,
+        )
+    }
+
+    fn aux_envelope_settings(&self, snapshot: &Snapshot, sample_rate: f32) -> EnvelopeSettings {
+        EnvelopeSettings::from_parameters(
+            snapshot.get(&self.aux_attack),
+            snapshot.get(&self.aux_decay),
+            snapshot.get(&self.aux_sustain),
+            snapshot.get(&self.aux_release),
+            sample_rate,
+        )
+    }
+
+    fn sequencer_settings(&self, snapshot: &Snapshot) -> SequencerSettings {
+        let mut steps = [self.sequence[0].step(snapshot); SEQUENCER_STEPS];
+        for (step, parameters) in steps.iter_mut().zip(self.sequence.iter()) {
+            *step = parameters.step(snapshot);
+        }
+        SequencerSettings {
+            steps,
+            step: Division::from_parameter(snapshot.get(&self.sequencer_division)).beats(),
+        }
+    }
+
+    fn arp_settings(&self, snapshot: &Snapshot) -> ArpSettings {
+        ArpSettings {
+            mode: ArpMode::from_parameter(snapshot.get(&self.arp_mode)),
+            step: Division::from_parameter(snapshot.get(&self.arp_division)).beats(),
+            gate: arp_gate(snapshot.get(&self.arp_gate)),
+            octaves: arp_octaves(snapshot.get(&self.arp_octaves)),
+            latch: is_on(snapshot.get(&self.arp_latch)),
         }
     }
 }
 
 // We're implementing a trait `Plugin` that does all the VST-y stuff for us.
 impl Plugin for Whisper {
+    // Hold on to the host, so we can ask it things like the tempo later.
+    fn new(host: HostCallback) -> Self {
+        Self {
+            host,
+            ..Default::default()
+        }
+    }
+
     fn get_info(&self) -> Info {
         Info {
             name: "Whisper".to_string(),
@@ -49,10 +1918,20 @@ impl Plugin for Whisper {
             // explicit anyways.
             outputs: 2,
 
+            // MIDI goes back out for SysEx dumps and other plugins to play.
+            midi_outputs: 1,
+
             // Set our category
             category: Category::Synth,
 
-            parameters: 1,
+            // We can process doubles as well as floats.
+            f64_precision: true,
+
+            parameters: self.params.parameter_count(),
+            presets: (PROGRAM_COUNT + Command::ALL.len()) as i32,
+
+            // We save our own chunk of state instead of just the parameters.
+            preset_chunks: true,
 
             // We don't care about other stuff, and it can stay default.
             ..Default::default()
@@ -65,59 +1944,73 @@ impl Plugin for Whisper {
         // to make sure we only get MIDI, since that's all we care about.
         for event in events.events() {
             match event {
-                Event::Midi(ev) => {
-                    // Check if it's a noteon or noteoff event.
-                    // This is difficult to explain without knowing how the MIDI standard works.
-                    // Basically, the first byte of data tells us if this signal is a note on event
-                    // or a note off event.  You can read more about that here:
-                    // https://www.midi.org/specifications/item/table-1-summary-of-midi-message
-                    match ev.data[0] {
-                        // if note on, increment our counter
-                        144 => self.notes += 1u8,
-
-                        // if note off, decrement our counter
-                        128 => self.notes -= 1u8,
-                        _ => (),
-                    }
-                    // if we cared about the pitch of the note, it's stored in `ev.data[1]`.
-                }
+                // Each event says how many samples into the next buffer it
+                // happens, so we hold on to it until `process` gets there.
+                Event::Midi(ev) => self.events.push(ev),
+                Event::SysEx(ev) => self.process_sysex(ev.payload),
                 // We don't care if we get any other type of event
                 _ => (),
             }
         }
     }
 
-    fn process(&mut self, buffer: &mut AudioBuffer<f32>) {
-        // `buffer.split()` gives us a tuple containing the
-        // input and output buffers.  We only care about the
-        // output, so we can ignore the input by using `_`.
-        let (_, mut output_buffer) = buffer.split();
+    // Everything that depends on the sample rate has to be worked out again
+    // when it changes, or notes would come out at the wrong pitch.
+    fn set_sample_rate(&mut self, rate: f32) {
+        self.sample_rate = rate;
+        self.params.sample_rate.set(rate);
+        self.voices.set_sample_rate(rate);
+        self.delay.set_sample_rate(rate);
+        self.reverb.set_sample_rate(rate);
+        // Force the smoothers to pick up the new rate.
+        self.smoothing = -1.0;
+        self.update_smoothing();
+    }
 
-        // We only want to process *anything* if a note is being held.
-        // Else, we can fill the output buffer with silence.
-        if self.notes == 0 {
-            for output_channel in output_buffer.into_iter() {
-                // Let's iterate over every sample in our channel.
-                for output_sample in output_channel {
-                    *output_sample = 0.0;
-                }
-            }
-            return;
+    // How long we keep making sound after the last note has finished, so
+    // hosts that stop processing silent plugins don't cut off the echoes.
+    // Zero would mean "don't know" and one means "no tail at all".
+    fn get_tail_size(&self) -> isize {
+        let mut seconds: f32 = 0.0;
+        if self.params.delay_mix.get() > 0.0 {
+            let feedback = delay_feedback(self.params.delay_feedback.get());
+            seconds = seconds.max(ring_time(self.delay_seconds(), feedback));
+        }
+        if self.params.reverb_mix.get() > 0.0 {
+            seconds = seconds.max(reverb_tail(room_feedback(self.params.reverb_size.get())));
         }
+        ((seconds * self.sample_rate) as isize).max(1)
+    }
 
-        let volume = self.params.volume.get();
+    // Whatever was playing when we were switched off shouldn't still be
+    // there when we're switched back on.
+    fn suspend(&mut self) {
+        self.kill_all();
+    }
 
-        // Now, we want to loop over our output channels.  This
-        // includes our left and right channels (or more, if you
-        // are working with surround sound).
-        for output_channel in output_buffer.into_iter() {
-            // Let's iterate over every sample in our channel.
-            for output_sample in output_channel {
-                // For every sample, we want to generate a random value
-                // from -1.0 to 1.0.
-                *output_sample = (random::<f32>() - 0.5f32) * 2f32 * volume;
-            }
-        }
+    fn resume(&mut self) {
+        self.kill_all();
+        self.voices.restart_seed();
+    }
+
+    // The host promises never to ask for more samples than this at once.
+    fn set_block_size(&mut self, size: i64) {
+        self.block_size = size.max(1) as usize;
+        self.params
+            .block_size
+            .store(self.block_size, Ordering::Relaxed);
+        self.left.resize(self.block_size, 0.0);
+        self.right.resize(self.block_size, 0.0);
+    }
+
+    fn process(&mut self, buffer: &mut AudioBuffer<f32>) {
+        self.process_buffer(buffer);
+    }
+
+    // Some hosts would rather give us doubles, which works just as well since
+    // the DSP itself doesn't care what we hand over in the end.
+    fn process_f64(&mut self, buffer: &mut AudioBuffer<f64>) {
+        self.process_buffer(buffer);
     }
 
     // It's good to tell our host what our plugin can do.
@@ -127,6 +2020,13 @@ impl Plugin for Whisper {
         match can_do {
             // Tell our host that the plugin supports receiving MIDI messages
             CanDo::ReceiveMidiEvent => Supported::Yes,
+            // And that it wants to know the tempo
+            CanDo::ReceiveTimeInfo => Supported::Yes,
+            // SysEx dumps come in, and go back out again when asked for
+            CanDo::ReceiveSysExEvent => Supported::Yes,
+            CanDo::SendEvents => Supported::Yes,
+            // As can the notes we play
+            CanDo::SendMidiEvent => Supported::Yes,
             // Maybe it also supports ather things
             _ => Supported::Maybe,
         }
@@ -137,216 +2037,146 @@ impl Plugin for Whisper {
     }
 
     fn get_editor(&mut self) -> Option<Box<dyn Editor>> {
-        Some(Box::new(GUIWrapper::new(self.params.clone())))
+        Some(Box::new(GUIWrapper::new(self.params.clone(), self.host)))
     }
 }
 
 plugin_main!(Whisper);
 
+// Turn a 0.0 to 1.0 parameter into -1.0 to 1.0, with the middle of the knob at zero.
+fn bipolar(value: f32) -> f32 {
+    value * 2.0 - 1.0
+}
+
+// Switches are on for the top half of the parameter's range.
+fn is_on(value: f32) -> bool {
+    value >= 0.5
+}
+
+fn on_off(value: f32) -> String {
+    if is_on(value) { "On" } else { "Off" }.to_string()
+}
+
+fn receive_all<T>(receiver: &mut Receiver<T>, items: &mut Vec<T>) {
+    while let Some(item) = receiver.receive() {
+        items.push(item);
+    }
+}
+
 impl PluginParameters for WhisperParameters {
+    // Picking a program keeps any changes made to the one being left, so
+    // going back to it later finds it how it was.
+    fn change_preset(&self, preset: i32) {
+        if preset < 0 {
+            return;
+        }
+        if let Some(&command) = Command::ALL.get((preset as usize).wrapping_sub(PROGRAM_COUNT)) {
+            self.run(command);
+            return;
+        }
+        if preset as usize >= PROGRAM_COUNT {
+            return;
+        }
+        let mut programs = self.programs.lock().unwrap();
+        programs[self.program.load(Ordering::Relaxed)].parameters = self.parameter_values();
+        for (index, &value) in programs[preset as usize].parameters.iter().enumerate() {
+            self.set_parameter(index as i32, value);
+        }
+        self.program.store(preset as usize, Ordering::Relaxed);
+    }
+
+    fn get_preset_num(&self) -> i32 {
+        self.program.load(Ordering::Relaxed) as i32
+    }
+
+    fn set_preset_name(&self, name: String) {
+        self.programs.lock().unwrap()[self.program.load(Ordering::Relaxed)].name = name;
+    }
+
+    fn get_preset_name(&self, preset: i32) -> String {
+        let programs = self.programs.lock().unwrap();
+        match programs.get(preset as usize) {
+            Some(program) => program.name.clone(),
+            None => match Command::ALL.get((preset as usize).wrapping_sub(PROGRAM_COUNT)) {
+                Some(command) => command.name().to_string(),
+                None => String::new(),
+            },
+        }
+    }
+
     fn get_parameter_label(&self, index: i32) -> String {
-        match index {
-            0 => "x".to_string(),
-            _ => "".to_string(),
+        match self.defs.get(index as usize) {
+            Some(def) => def.label.to_string(),
+            None => "".to_string(),
         }
     }
     // This is what will display underneath our control.  We can
     // format it into a string that makes the most sense.
     fn get_parameter_text(&self, index: i32) -> String {
-        match index {
-            0 => format!("{:.3}", self.volume.get()),
-            _ => format!(""),
+        match self.defs.get(index as usize) {
+            Some(def) => def.text(self),
+            None => "".to_string(),
         }
     }
 
     fn get_parameter_name(&self, index: i32) -> String {
-        match index {
-            0 => "volume".to_string(),
-            _ => "".to_string(),
+        match self.defs.get(index as usize) {
+            Some(def) => def.name.clone(),
+            None => "".to_string(),
         }
     }
     // get_parameter has to return the value used in set_parameter
     fn get_parameter(&self, index: i32) -> f32 {
-        match index {
-            0 => self.volume.get(),
-            _ => 0.0,
+        match self.defs.get(index as usize) {
+            Some(def) => def.value(self).get(),
+            None => 0.0,
         }
     }
     fn set_parameter(&self, index: i32, value: f32) {
-        match index {
-            0 => self.volume.set(value),
-            _ => (),
+        self.edit_parameter(index, value);
+        if let Some(changed) = self.changed.get(index as usize) {
+            changed.store(true, Ordering::Relaxed);
         }
     }
-}
-
-use winapi::shared::windef::HWND;
-use winit::platform::desktop::EventLoopExtDesktop;
-use winit::platform::windows::WindowBuilderExtWindows;
-
-mod support;
-
-use conrod_core::text::Font;
-use conrod_core::{widget, Colorable, Positionable, Ui, Widget};
-use conrod_glium::Renderer;
-use glium::glutin::event_loop::EventLoop;
-use glium::glutin::window::WindowBuilder;
-use glium::Surface;
-use winit::event_loop::ControlFlow;
-
-const WIDTH: u32 = 400;
-const HEIGHT: u32 = 200;
 
-widget_ids!(struct Ids { text, volume_slider });
-
-struct GUIWrapper {
-    params: Arc<WhisperParameters>,
-    inner: Option<GUI>,
-}
-
-struct GUI {
-    event_loop: EventLoop<()>,
-    display: support::GliumDisplayWinitWrapper,
-    ids: Ids,
-    ui: Ui,
-    renderer: Renderer,
-    image_map: conrod_core::image::Map<glium::texture::Texture2d>,
-}
-
-impl GUI {
-    fn new(parent: HWND) -> Self {
-        let event_loop = EventLoop::new();
-
-        let window = WindowBuilder::new()
-            .with_title("A fantastic window!")
-            .with_decorations(false)
-            .with_resizable(false)
-            .with_parent_window(parent)
-            .with_inner_size((WIDTH, HEIGHT).into());
-
-        let context = glium::glutin::ContextBuilder::new();
-
-        let display = glium::Display::new(window, context, &event_loop).unwrap();
-        let display = support::GliumDisplayWinitWrapper(display);
-
-        let mut ui = conrod_core::UiBuilder::new([WIDTH as f64, HEIGHT as f64]).build();
-        let ids = Ids::new(ui.widget_id_generator());
-
-        let font: &[u8] = include_bytes!("../assets/fonts/NotoSans/NotoSans-Regular.ttf");
-        ui.fonts.insert(Font::from_bytes(font).unwrap());
-
-        let renderer = conrod_glium::Renderer::new(&display.0).unwrap();
-
-        // The image map describing each of our widget->image mappings (in our case, none).
-        let image_map = conrod_core::image::Map::<glium::texture::Texture2d>::new();
-
-        Self {
-            event_loop,
-            display,
-            ids,
-            ui,
-            renderer,
-            image_map,
+    fn can_be_automated(&self, index: i32) -> bool {
+        match self.defs.get(index as usize) {
+            Some(def) => def.automatable,
+            None => false,
         }
     }
-}
 
-impl GUIWrapper {
-    fn new(params: Arc<WhisperParameters>) -> Self {
-        Self {
-            params,
-            inner: None,
+    // Hosts that let values be typed in hand us the text.  It's read in the
+    // parameter's own unit unless it says otherwise.
+    fn string_to_parameter(&self, index: i32, text: String) -> bool {
+        match self
+            .defs
+            .get(index as usize)
+            .and_then(|def| def.parse(self, &text))
+        {
+            Some(value) => {
+                self.set_parameter(index, value);
+                true
+            }
+            None => false,
         }
     }
-}
 
-impl Editor for GUIWrapper {
-    fn size(&self) -> (i32, i32) {
-        if let Some(inner) = self.inner.as_ref() {
-            let s = inner.display.0.gl_window().window().inner_size();
-            (s.width as i32, s.height as i32)
-        } else {
-            (0, 0)
-        }
-    }
-
-    fn position(&self) -> (i32, i32) {
-        (0, 0)
-    }
-
-    fn idle(&mut self) {
-        use winit::event;
-
-        let mut end = false;
-        if let Some(inner) = self.inner.as_mut() {
-            let display = &mut inner.display;
-            let ui = &mut inner.ui;
-            let ids = &mut inner.ids;
-            let renderer = &mut inner.renderer;
-            let image_map = &mut inner.image_map;
-            let params = &self.params;
-            inner
-                .event_loop
-                .run_return(move |event, _, control_flow| match event {
-                    event::Event::WindowEvent {
-                        event: event::WindowEvent::CloseRequested,
-                        window_id,
-                    } if window_id == display.0.gl_window().window().id() => {
-                        end = true;
-                        *control_flow = ControlFlow::Exit
-                    }
-                    event::Event::EventsCleared => *control_flow = ControlFlow::Exit,
-                    _ => {
-                        let input = match support::convert_event(event, display) {
-                            None => return,
-                            Some(input) => input,
-                        };
-
-                        // Handle the input with the `Ui`.
-                        ui.handle_event(input);
-
-                        // Set the widgets.
-                        let ui = &mut ui.set_widgets();
-
-                        // "Hello World!" in the middle of the screen.
-                        widget::Text::new("Volume")
-                            .middle_of(ui.window)
-                            .color(conrod_core::color::WHITE)
-                            .font_size(32)
-                            .set(ids.text, ui);
-
-                        if let Some(new_volume) = widget::Slider::new(params.volume.get(), 0.0, 1.0)
-                            .set(ids.volume_slider, ui)
-                        {
-                            params.volume.set(new_volume);
-                        }
-
-                        // Draw the `Ui` if it has changed.
-                        if let Some(primitives) = ui.draw_if_changed() {
-                            renderer.fill(&display.0, primitives, image_map);
-                            let mut target = display.0.draw();
-                            target.clear_color(0.0, 0.0, 0.0, 1.0);
-                            renderer.draw(&display.0, &mut target, &image_map).unwrap();
-                            target.finish().unwrap();
-                        }
-                    }
-                });
-        }
-        if end {
-            self.inner = None;
-        }
+    // Both kinds of chunk hold the sound being played.  The bank's also has
+    // every program in it, so they all come back with the project.
+    fn get_preset_data(&self) -> Vec<u8> {
+        self.encode(&self.state())
     }
 
-    fn close(&mut self) {
-        self.inner = None;
+    fn get_bank_data(&self) -> Vec<u8> {
+        self.encode(&self.bank())
     }
 
-    fn open(&mut self, parent: *mut c_void) -> bool {
-        self.inner = Some(GUI::new(parent as HWND));
-        true
+    fn load_preset_data(&self, data: &[u8]) {
+        self.load_chunk(data);
     }
 
-    fn is_open(&mut self) -> bool {
-        self.inner.is_some()
+    fn load_bank_data(&self, data: &[u8]) {
+        self.load_chunk(data);
     }
 }
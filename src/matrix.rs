@@ -0,0 +1,187 @@
+// A modulation matrix: a handful of slots, each connecting a source of
+// movement to something it moves, by a bipolar amount.
+
+use crate::oscillator::semitones_to_ratio;
+
+// How many slots there are.
+pub const MATRIX_SLOTS: usize = 8;
+
+// How far each destination moves at full amount with the source at 1.0.
+const PITCH_RANGE: f32 = 12.0;
+const CUTOFF_RANGE: f32 = 1.0;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ModSource {
+    Off,
+    Lfo1,
+    Lfo2,
+    // The filter envelope doubles as the second envelope.
+    Envelope2,
+    // An envelope that's only there to modulate things.
+    Envelope3,
+    // A new random value every step, held until the next one.
+    Random,
+    Velocity,
+    ModWheel,
+    Aftertouch,
+    // Aftertouch for each key on its own, from keyboards that can tell.
+    PolyPressure,
+    // MPE's third dimension, usually sliding up and down the key.
+    Timbre,
+}
+
+impl ModSource {
+    pub const ALL: [ModSource; 11] = [
+        ModSource::Off,
+        ModSource::Lfo1,
+        ModSource::Lfo2,
+        ModSource::Envelope2,
+        ModSource::Envelope3,
+        ModSource::Random,
+        ModSource::Velocity,
+        ModSource::ModWheel,
+        ModSource::Aftertouch,
+        ModSource::PolyPressure,
+        ModSource::Timbre,
+    ];
+
+    pub fn from_parameter(value: f32) -> Self {
+        let index = (value * Self::ALL.len() as f32) as usize;
+        Self::ALL[index.min(Self::ALL.len() - 1)]
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            ModSource::Off => "Off",
+            ModSource::Lfo1 => "LFO 1",
+            ModSource::Lfo2 => "LFO 2",
+            ModSource::Envelope2 => "Env 2",
+            ModSource::Envelope3 => "Env 3",
+            ModSource::Random => "Random",
+            ModSource::Velocity => "Velocity",
+            ModSource::ModWheel => "Mod Wheel",
+            ModSource::Aftertouch => "Aftertouch",
+            ModSource::PolyPressure => "Poly Pressure",
+            ModSource::Timbre => "Timbre",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ModDestination {
+    Pitch,
+    Cutoff,
+    Amplitude,
+    Pan,
+}
+
+impl ModDestination {
+    pub const ALL: [ModDestination; 4] = [
+        ModDestination::Pitch,
+        ModDestination::Cutoff,
+        ModDestination::Amplitude,
+        ModDestination::Pan,
+    ];
+
+    pub fn from_parameter(value: f32) -> Self {
+        let index = (value * Self::ALL.len() as f32) as usize;
+        Self::ALL[index.min(Self::ALL.len() - 1)]
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            ModDestination::Pitch => "Pitch",
+            ModDestination::Cutoff => "Cutoff",
+            ModDestination::Amplitude => "Amp",
+            ModDestination::Pan => "Pan",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct ModSlot {
+    pub source: ModSource,
+    pub destination: ModDestination,
+    // From -1.0 to 1.0.
+    pub amount: f32,
+}
+
+// Where every source is at right now.  The LFOs, the random steps and the
+// controllers are the same for every voice, the envelopes, velocity and
+// per-note expression are filled in by each.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ModSources {
+    // From -1.0 to 1.0.
+    pub lfo1: f32,
+    pub lfo2: f32,
+    pub random: f32,
+    // The rest from 0.0 to 1.0.
+    pub envelope2: f32,
+    pub envelope3: f32,
+    pub velocity: f32,
+    pub mod_wheel: f32,
+    pub aftertouch: f32,
+    pub poly_pressure: f32,
+    pub timbre: f32,
+}
+
+impl ModSources {
+    fn get(&self, source: ModSource) -> f32 {
+        match source {
+            ModSource::Off => 0.0,
+            ModSource::Lfo1 => self.lfo1,
+            ModSource::Lfo2 => self.lfo2,
+            ModSource::Envelope2 => self.envelope2,
+            ModSource::Envelope3 => self.envelope3,
+            ModSource::Random => self.random,
+            ModSource::Velocity => self.velocity,
+            ModSource::ModWheel => self.mod_wheel,
+            ModSource::Aftertouch => self.aftertouch,
+            ModSource::PolyPressure => self.poly_pressure,
+            ModSource::Timbre => self.timbre,
+        }
+    }
+}
+
+// The sum of all slots, per destination.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ModAmounts {
+    // In semitones.
+    pub pitch: f32,
+    // On the 0.0 to 1.0 cutoff parameter scale.
+    pub cutoff: f32,
+    // Added to a gain of 1.0.
+    pub amplitude: f32,
+    // From -1.0 to 1.0.
+    pub pan: f32,
+}
+
+impl ModAmounts {
+    pub fn new(slots: &[ModSlot], sources: &ModSources) -> Self {
+        let mut amounts = Self::default();
+        for slot in slots.iter().filter(|slot| slot.source != ModSource::Off) {
+            let value = sources.get(slot.source) * slot.amount;
+            match slot.destination {
+                ModDestination::Pitch => amounts.pitch += value * PITCH_RANGE,
+                ModDestination::Cutoff => amounts.cutoff += value * CUTOFF_RANGE,
+                ModDestination::Amplitude => amounts.amplitude += value,
+                ModDestination::Pan => amounts.pan += value,
+            }
+        }
+        amounts
+    }
+
+    // What to multiply the frequency by.  Skipping the `powf` when nothing
+    // is routed to pitch saves quite a bit with lots of voices.
+    pub fn pitch_ratio(&self) -> f32 {
+        if self.pitch == 0.0 {
+            1.0
+        } else {
+            semitones_to_ratio(self.pitch)
+        }
+    }
+
+    pub fn gain(&self) -> f32 {
+        (1.0 + self.amplitude).max(0.0)
+    }
+}
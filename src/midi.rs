@@ -0,0 +1,266 @@
+// Decoding raw MIDI bytes into something that says what they mean.  You
+// can read more about what each message is here:
+// https://www.midi.org/specifications/item/table-1-summary-of-midi-message
+
+use vst::event::MidiEvent;
+
+// Channels are counted from 0 here, so channel 1 on a keyboard is 0.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum MidiMessage<'a> {
+    NoteOn {
+        channel: u8,
+        note: u8,
+        velocity: u8,
+    },
+    NoteOff {
+        channel: u8,
+        note: u8,
+    },
+    // Polyphonic aftertouch, how hard one key is being pressed down.
+    PolyPressure {
+        channel: u8,
+        note: u8,
+        pressure: u8,
+    },
+    ControlChange {
+        channel: u8,
+        controller: u8,
+        value: u8,
+    },
+    ProgramChange {
+        channel: u8,
+        program: u8,
+    },
+    // Channel aftertouch, how hard the keys are being pressed down.
+    ChannelPressure {
+        channel: u8,
+        pressure: u8,
+    },
+    // From -1.0 to 1.0.
+    PitchBend {
+        channel: u8,
+        bend: f32,
+    },
+    // Everything between the 0xF0 that starts a system exclusive message
+    // and the 0xF7 that ends it.
+    SysEx(&'a [u8]),
+    // MIDI clock, sent 24 times every quarter note, and the messages that
+    // start and stop it.
+    Clock,
+    Start,
+    Continue,
+    Stop,
+}
+
+impl<'a> MidiMessage<'a> {
+    // Anything we don't know, or that's cut short, gives `None`.
+    pub fn parse(data: &'a [u8]) -> Option<Self> {
+        let status = *data.get(0)?;
+        let byte = |index: usize| data.get(index).map(|byte| byte & 0x7f);
+        let channel = status & 0x0f;
+        let message = match status {
+            0xf0 => {
+                let end = data.iter().position(|&byte| byte == 0xf7);
+                MidiMessage::SysEx(&data[1..end.unwrap_or_else(|| data.len())])
+            }
+            0xf8 => MidiMessage::Clock,
+            0xfa => MidiMessage::Start,
+            0xfb => MidiMessage::Continue,
+            0xfc => MidiMessage::Stop,
+            // A note on with a velocity of zero is how a lot of keyboards
+            // send a note off, so it's decoded as one.
+            0x90..=0x9f if byte(2)? > 0 => MidiMessage::NoteOn {
+                channel,
+                note: byte(1)?,
+                velocity: byte(2)?,
+            },
+            0x80..=0x9f => MidiMessage::NoteOff {
+                channel,
+                note: byte(1)?,
+            },
+            0xa0..=0xaf => MidiMessage::PolyPressure {
+                channel,
+                note: byte(1)?,
+                pressure: byte(2)?,
+            },
+            0xb0..=0xbf => MidiMessage::ControlChange {
+                channel,
+                controller: byte(1)?,
+                value: byte(2)?,
+            },
+            0xc0..=0xcf => MidiMessage::ProgramChange {
+                channel,
+                program: byte(1)?,
+            },
+            0xd0..=0xdf => MidiMessage::ChannelPressure {
+                channel,
+                pressure: byte(1)?,
+            },
+            // Pitch bend is a 14 bit number split over both data bytes,
+            // with the wheel at rest exactly in the middle.
+            0xe0..=0xef => {
+                let value = (i32::from(byte(2)?) << 7) | i32::from(byte(1)?);
+                MidiMessage::PitchBend {
+                    channel,
+                    bend: ((value - 8192) as f32 / 8192.0).max(-1.0),
+                }
+            }
+            _ => return None,
+        };
+        Some(message)
+    }
+
+    // System messages don't belong to any channel.
+    pub fn channel(&self) -> Option<u8> {
+        match *self {
+            MidiMessage::NoteOn { channel, .. }
+            | MidiMessage::NoteOff { channel, .. }
+            | MidiMessage::PolyPressure { channel, .. }
+            | MidiMessage::ControlChange { channel, .. }
+            | MidiMessage::ProgramChange { channel, .. }
+            | MidiMessage::ChannelPressure { channel, .. }
+            | MidiMessage::PitchBend { channel, .. } => Some(channel),
+            _ => None,
+        }
+    }
+}
+
+// Whether the notes we play, the sequencer's and arpeggiator's included,
+// also go out to the host for other plugins to play.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum MidiOutput {
+    Off,
+    // Sent out, and played here as well.
+    Thru,
+    // Sent out, and not played here at all.
+    Only,
+}
+
+impl MidiOutput {
+    pub const ALL: [MidiOutput; 3] = [MidiOutput::Off, MidiOutput::Thru, MidiOutput::Only];
+
+    pub fn from_parameter(value: f32) -> Self {
+        let index = (value * Self::ALL.len() as f32) as usize;
+        Self::ALL[index.min(Self::ALL.len() - 1)]
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            MidiOutput::Off => "Off",
+            MidiOutput::Thru => "Out + Synth",
+            MidiOutput::Only => "Out Only",
+        }
+    }
+}
+
+// A seven bit value, like a controller position or a pressure, from 0.0 to 1.0.
+pub fn unit_value(value: u8) -> f32 {
+    f32::from(value & 0x7f) / 127.0
+}
+
+// A MIDI event of our own making, to be played `delta_frames` samples into
+// the buffer.
+pub fn midi_event(data: [u8; 3], delta_frames: i32) -> MidiEvent {
+    MidiEvent {
+        data,
+        delta_frames,
+        live: true,
+        note_length: None,
+        note_offset: None,
+        detune: 0,
+        note_off_velocity: 0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn notes() {
+        assert_eq!(
+            MidiMessage::parse(&[0x91, 60, 100]),
+            Some(MidiMessage::NoteOn {
+                channel: 1,
+                note: 60,
+                velocity: 100,
+            })
+        );
+        assert_eq!(
+            MidiMessage::parse(&[0x80, 60, 64]),
+            Some(MidiMessage::NoteOff {
+                channel: 0,
+                note: 60,
+            })
+        );
+        // A note on with no velocity is a note off.
+        assert_eq!(
+            MidiMessage::parse(&[0x9f, 61, 0]),
+            Some(MidiMessage::NoteOff {
+                channel: 15,
+                note: 61,
+            })
+        );
+        // Cut short, there's no telling which.
+        assert_eq!(MidiMessage::parse(&[0x90, 60]), None);
+    }
+
+    #[test]
+    fn messages() {
+        assert_eq!(
+            MidiMessage::parse(&[0xb2, 74, 200]),
+            Some(MidiMessage::ControlChange {
+                channel: 2,
+                controller: 74,
+                value: 72,
+            })
+        );
+        assert_eq!(
+            MidiMessage::parse(&[0xc0, 5]),
+            Some(MidiMessage::ProgramChange {
+                channel: 0,
+                program: 5,
+            })
+        );
+        assert_eq!(
+            MidiMessage::parse(&[0xd3, 90]),
+            Some(MidiMessage::ChannelPressure {
+                channel: 3,
+                pressure: 90,
+            })
+        );
+        assert_eq!(MidiMessage::parse(&[0xf8]), Some(MidiMessage::Clock));
+        assert_eq!(MidiMessage::parse(&[0xfc]), Some(MidiMessage::Stop));
+        assert_eq!(MidiMessage::parse(&[]), None);
+        assert_eq!(MidiMessage::parse(&[0xf4]), None);
+        assert_eq!(
+            MidiMessage::parse(&[0x91, 60, 100]).unwrap().channel(),
+            Some(1)
+        );
+        assert_eq!(MidiMessage::Clock.channel(), None);
+    }
+
+    #[test]
+    fn pitch_bend() {
+        let bend = |data: &[u8]| match MidiMessage::parse(data) {
+            Some(MidiMessage::PitchBend { bend, .. }) => bend,
+            _ => panic!("should be a pitch bend"),
+        };
+        assert_eq!(bend(&[0xe0, 0x00, 0x40]), 0.0);
+        assert_eq!(bend(&[0xe0, 0x00, 0x00]), -1.0);
+        assert!((bend(&[0xe0, 0x7f, 0x7f]) - 1.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn sysex() {
+        assert_eq!(
+            MidiMessage::parse(&[0xf0, 1, 2, 3, 0xf7]),
+            Some(MidiMessage::SysEx(&[1, 2, 3]))
+        );
+        // Without the end, it's everything there is.
+        assert_eq!(
+            MidiMessage::parse(&[0xf0, 1, 2]),
+            Some(MidiMessage::SysEx(&[1, 2]))
+        );
+    }
+}
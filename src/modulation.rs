@@ -0,0 +1,78 @@
+// The controller number of the modulation wheel.
+pub const MOD_WHEEL: u8 = 1;
+
+// Controllers that are really commands.  All sound off silences everything
+// right now, all notes off is like letting go of every key.
+pub const ALL_SOUND_OFF: u8 = 120;
+pub const ALL_NOTES_OFF: u8 = 123;
+
+// The things a MIDI controller can be routed to.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Destination {
+    Off,
+    Volume,
+    Cutoff,
+    LfoDepth,
+}
+
+impl Destination {
+    pub const ALL: [Destination; 4] = [
+        Destination::Off,
+        Destination::Volume,
+        Destination::Cutoff,
+        Destination::LfoDepth,
+    ];
+
+    pub fn from_parameter(value: f32) -> Self {
+        let index = (value * Self::ALL.len() as f32) as usize;
+        Self::ALL[index.min(Self::ALL.len() - 1)]
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Destination::Off => "Off",
+            Destination::Volume => "Volume",
+            Destination::Cutoff => "Cutoff",
+            Destination::LfoDepth => "LFO Depth",
+        }
+    }
+}
+
+// One entry in the routing table: a controller and where it goes.
+#[derive(Clone, Copy, Debug)]
+pub struct Route {
+    pub controller: u8,
+    pub destination: Destination,
+}
+
+// The latest value of every MIDI controller, from 0.0 to 1.0.
+pub struct Controllers {
+    values: [f32; 128],
+}
+
+impl Default for Controllers {
+    fn default() -> Self {
+        Self { values: [0.0; 128] }
+    }
+}
+
+impl Controllers {
+    pub fn set(&mut self, controller: u8, value: u8) {
+        self.values[usize::from(controller & 0x7f)] = f32::from(value & 0x7f) / 127.0;
+    }
+
+    pub fn get(&self, controller: u8) -> f32 {
+        self.values[usize::from(controller & 0x7f)]
+    }
+
+    // How much the controllers routed to `destination` are pushing it, or
+    // `None` if nothing is routed there and it should be left alone.
+    pub fn amount(&self, routes: &[Route], destination: Destination) -> Option<f32> {
+        routes
+            .iter()
+            .filter(|route| route.destination == destination)
+            .map(|route| self.get(route.controller))
+            .fold(None, |total, value| Some(total.unwrap_or(0.0) + value))
+            .map(|total| total.min(1.0))
+    }
+}
@@ -0,0 +1,26 @@
+// MPE, MIDI Polyphonic Expression, gives every note a MIDI channel of its
+// own, so pitch bend, pressure and the "timbre" controller sent on that
+// channel only change that note.  Channel 1 is kept for what should change
+// every note at once.
+
+// How far a note's own pitch bend goes, in semitones.  This is what the
+// MPE spec asks for by default, enough to slide between any two keys on
+// the kind of surfaces that want it.
+pub const MPE_BEND_RANGE: f32 = 48.0;
+
+// MPE controllers send how far the finger has moved up or down the key on
+// this controller.
+pub const TIMBRE: u8 = 74;
+
+// Where a single note's expression is at.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Expression {
+    // Which channel it's sent on, counting from 0.  Outside of MPE every
+    // note is on the first.
+    pub channel: u8,
+    // In semitones.
+    pub bend: f32,
+    // The rest from 0.0 to 1.0.
+    pub pressure: f32,
+    pub timbre: f32,
+}
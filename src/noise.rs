@@ -0,0 +1,48 @@
+// A tiny random number generator, xorshift, for the noise and randomness in
+// each voice.  Unlike `rand::random` it can be started from a seed, so a
+// voice can be made to come out exactly the same every time it's played.
+// It's nowhere near good enough for anything that needs to be secure, but
+// nobody can hear the difference.
+
+pub struct Noise {
+    state: u32,
+}
+
+impl Default for Noise {
+    fn default() -> Self {
+        Self::new(0)
+    }
+}
+
+impl Noise {
+    pub fn new(seed: u32) -> Self {
+        let mut noise = Self { state: 0 };
+        noise.seed(seed);
+        noise
+    }
+
+    pub fn seed(&mut self, seed: u32) {
+        // Xorshift gets stuck on zero, and spreading the bits out means
+        // seeds that are close together don't start out alike.
+        self.state = seed.wrapping_mul(0x9e37_79b9).max(1);
+    }
+
+    pub fn next_u32(&mut self) -> u32 {
+        let mut x = self.state;
+        x ^= x << 13;
+        x ^= x >> 17;
+        x ^= x << 5;
+        self.state = x;
+        x
+    }
+
+    // From 0.0 to 1.0.
+    pub fn unit(&mut self) -> f32 {
+        (self.next_u32() >> 8) as f32 / (1 << 24) as f32
+    }
+
+    // White noise, from -1.0 to 1.0.
+    pub fn white(&mut self) -> f32 {
+        self.unit() * 2.0 - 1.0
+    }
+}
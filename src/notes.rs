@@ -0,0 +1,139 @@
+// When more than one key is down in a mono mode, which one gets played.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum NotePriority {
+    // The one pressed most recently.
+    Last,
+    Low,
+    High,
+}
+
+impl NotePriority {
+    pub const ALL: [NotePriority; 3] = [NotePriority::Last, NotePriority::Low, NotePriority::High];
+
+    pub fn from_parameter(value: f32) -> Self {
+        let index = (value * Self::ALL.len() as f32) as usize;
+        Self::ALL[index.min(Self::ALL.len() - 1)]
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            NotePriority::Last => "Last",
+            NotePriority::Low => "Low",
+            NotePriority::High => "High",
+        }
+    }
+}
+
+// Which of the 128 MIDI keys are currently held down.  Hosts can send
+// duplicate note-ons and note-offs, so rather than counting events we just
+// remember the state of every key and derive everything else from it.
+pub struct NoteTable {
+    held: [bool; 128],
+    // When each key was pressed, so we know which held key is the most recent.
+    pressed: [u64; 128],
+    counter: u64,
+}
+
+impl Default for NoteTable {
+    fn default() -> Self {
+        Self {
+            held: [false; 128],
+            pressed: [0; 128],
+            counter: 0,
+        }
+    }
+}
+
+impl NoteTable {
+    // Returns whether the key was already down.
+    pub fn note_on(&mut self, note: u8) -> bool {
+        let index = usize::from(note & 0x7f);
+        let was_held = self.held[index];
+        self.held[index] = true;
+        self.counter += 1;
+        self.pressed[index] = self.counter;
+        was_held
+    }
+
+    // Returns whether the key was actually down, so stray note-offs can be ignored.
+    pub fn note_off(&mut self, note: u8) -> bool {
+        let index = usize::from(note & 0x7f);
+        let was_held = self.held[index];
+        self.held[index] = false;
+        was_held
+    }
+
+    pub fn clear(&mut self) {
+        self.held = [false; 128];
+    }
+
+    // The key that should be sounding out of all those still down.
+    pub fn held_note(&self, priority: NotePriority) -> Option<u8> {
+        let mut held = (0..128u8).filter(|&note| self.held[usize::from(note)]);
+        match priority {
+            NotePriority::Last => held.max_by_key(|&note| self.pressed[usize::from(note)]),
+            NotePriority::Low => held.next(),
+            NotePriority::High => held.last(),
+        }
+    }
+}
+
+// The names of the twelve notes in an octave, starting from C.
+pub const NOTE_NAMES: [&str; 12] = [
+    "C", "C#", "D", "D#", "E", "F", "F#", "G", "G#", "A", "A#", "B",
+];
+
+// The name of a MIDI note, like "C4" for middle C (note 60).
+pub fn note_name(note: u8) -> String {
+    format!(
+        "{}{}",
+        NOTE_NAMES[usize::from(note % 12)],
+        i32::from(note / 12) - 1
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Middle C, then the G above and the E between.
+    fn chord() -> NoteTable {
+        let mut notes = NoteTable::default();
+        for &note in [60, 67, 64].iter() {
+            notes.note_on(note);
+        }
+        notes
+    }
+
+    #[test]
+    fn priority() {
+        let notes = chord();
+        assert_eq!(notes.held_note(NotePriority::Last), Some(64));
+        assert_eq!(notes.held_note(NotePriority::Low), Some(60));
+        assert_eq!(notes.held_note(NotePriority::High), Some(67));
+    }
+
+    #[test]
+    fn after_a_release() {
+        let mut notes = chord();
+        // Letting go of the newest goes back to the one before it.
+        assert!(notes.note_off(64));
+        assert_eq!(notes.held_note(NotePriority::Last), Some(67));
+        assert!(notes.note_off(60));
+        assert_eq!(notes.held_note(NotePriority::Low), Some(67));
+        assert!(notes.note_off(67));
+        assert_eq!(notes.held_note(NotePriority::High), None);
+        // A note off for a key that isn't down changes nothing.
+        assert!(!notes.note_off(67));
+    }
+
+    #[test]
+    fn pressed_again() {
+        let mut notes = chord();
+        // The C going down again makes it the newest, without counting twice.
+        assert!(notes.note_on(60));
+        assert_eq!(notes.held_note(NotePriority::Last), Some(60));
+        assert!(notes.note_off(60));
+        assert_eq!(notes.held_note(NotePriority::Last), Some(64));
+    }
+}
@@ -0,0 +1,170 @@
+use crate::noise::Noise;
+use std::f32::consts::PI;
+
+/// The shapes our oscillator knows how to draw.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Waveform {
+    Sine,
+    Saw,
+    Square,
+    Triangle,
+    // The original white noise, kept around because it's still a useful sound.
+    Noise,
+}
+
+impl Waveform {
+    pub const ALL: [Waveform; 5] = [
+        Waveform::Sine,
+        Waveform::Saw,
+        Waveform::Square,
+        Waveform::Triangle,
+        Waveform::Noise,
+    ];
+
+    // VST parameters are always 0.0 to 1.0, so we slice that range into
+    // equally sized steps, one for each waveform.
+    pub fn from_parameter(value: f32) -> Self {
+        let index = (value * Self::ALL.len() as f32) as usize;
+        Self::ALL[index.min(Self::ALL.len() - 1)]
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Waveform::Sine => "Sine",
+            Waveform::Saw => "Saw",
+            Waveform::Square => "Square",
+            Waveform::Triangle => "Triangle",
+            Waveform::Noise => "Noise",
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct Oscillator {
+    // Where we are in the current cycle, from 0.0 to 1.0.
+    phase: f32,
+    noise: Noise,
+}
+
+impl Oscillator {
+    pub fn seed(&mut self, seed: u32) {
+        self.noise.seed(seed);
+    }
+
+    pub fn set_phase(&mut self, phase: f32) {
+        self.phase = phase.fract();
+    }
+
+    // Produce the next sample and advance the phase by `increment`, which is
+    // the frequency divided by the sample rate.
+    pub fn next(&mut self, waveform: Waveform, increment: f32) -> f32 {
+        let t = self.phase;
+
+        let value = match waveform {
+            Waveform::Sine => (t * 2.0 * PI).sin(),
+            // A naive saw and square alias badly at high pitches, so we smooth
+            // their discontinuities with a polynomial band-limited step.
+            Waveform::Saw => 2.0 * t - 1.0 - poly_blep(t, increment),
+            Waveform::Square => {
+                let naive = if t < 0.5 { 1.0 } else { -1.0 };
+                naive + poly_blep(t, increment) - poly_blep((t + 0.5) % 1.0, increment)
+            }
+            Waveform::Triangle => 1.0 - 4.0 * (t - 0.5).abs(),
+            Waveform::Noise => self.noise.white(),
+        };
+
+        self.phase += increment;
+        if self.phase >= 1.0 {
+            self.phase -= 1.0;
+        }
+
+        value
+    }
+
+    // A sine whose phase is pushed around by `modulation`, measured in
+    // radians.  Driven by another oscillator, this is FM synthesis.
+    pub fn next_phase_modulated(&mut self, increment: f32, modulation: f32) -> f32 {
+        let value = (self.phase * 2.0 * PI + modulation).sin();
+
+        self.phase += increment;
+        if self.phase >= 1.0 {
+            self.phase -= 1.0;
+        }
+
+        value
+    }
+}
+
+// One cycle of what the oscillator plays, `length` samples long, for the
+// editor to draw.  With `fm`, a ratio and an index, it's the FM carrier
+// instead of the waveform.
+pub fn single_cycle(waveform: Waveform, fm: Option<(f32, f32)>, length: usize) -> Vec<f32> {
+    let increment = 1.0 / length as f32;
+    let mut carrier = Oscillator::default();
+    let mut modulator = Oscillator::default();
+    (0..length)
+        .map(|_| match fm {
+            Some((ratio, index)) => {
+                let modulation = modulator.next(Waveform::Sine, increment * ratio);
+                carrier.next_phase_modulated(increment, modulation * index)
+            }
+            None => carrier.next(waveform, increment),
+        })
+        .collect()
+}
+
+// The correction that turns a hard step at phase 0 into a smooth one.
+fn poly_blep(t: f32, dt: f32) -> f32 {
+    if t < dt {
+        let t = t / dt;
+        t + t - t * t - 1.0
+    } else if t > 1.0 - dt {
+        let t = (t - 1.0) / dt;
+        t * t + t + t + 1.0
+    } else {
+        0.0
+    }
+}
+
+// The sub-oscillator only makes sense as something simple and round, so it
+// has a choice of just two waveforms.
+pub fn sub_waveform(value: f32) -> Waveform {
+    if value < 0.5 {
+        Waveform::Sine
+    } else {
+        Waveform::Square
+    }
+}
+
+// Map a 0.0 to 1.0 parameter onto the FM modulator's frequency as a multiple
+// of the note's.  Whole numbers keep the result harmonic, so those are all we
+// offer, plus 0.5 for an octave down.
+pub fn fm_ratio(value: f32) -> f32 {
+    let ratio = (value * 16.0).round();
+    if ratio < 1.0 {
+        0.5
+    } else {
+        ratio
+    }
+}
+
+// Map a 0.0 to 1.0 parameter onto an FM index, how many radians the
+// modulator swings the carrier's phase by, from 0 to 10.
+pub fn fm_index(value: f32) -> f32 {
+    value * 10.0
+}
+
+// Convert a MIDI note number into a frequency in Hz, with A4 (note 69) at 440 Hz.
+pub fn midi_pitch_to_freq(pitch: u8) -> f32 {
+    440.0 * 2.0f32.powf((f32::from(pitch) - 69.0) / 12.0)
+}
+
+// How much to multiply a frequency by to shift it by a number of semitones.
+pub fn semitones_to_ratio(semitones: f32) -> f32 {
+    2.0f32.powf(semitones / 12.0)
+}
+
+// Map a 0.0 to 1.0 parameter onto a pitch-bend range of 0 to 24 semitones.
+pub fn bend_range(value: f32) -> f32 {
+    (value * 24.0).round()
+}
@@ -0,0 +1,21 @@
+use std::f32::consts::FRAC_PI_4;
+
+// Equal-power panning: `pan` goes from -1.0 (left) to 1.0 (right), and the
+// returned left and right gains always add up to the same power, so a sound
+// doesn't get quieter in the middle the way it does with a linear crossfade.
+pub fn equal_power(pan: f32) -> (f32, f32) {
+    let angle = (pan.max(-1.0).min(1.0) + 1.0) * FRAC_PI_4;
+    (angle.cos(), angle.sin())
+}
+
+// Show a pan position the way mixing desks do: "L50", "C" or "R50".
+pub fn format_pan(pan: f32) -> String {
+    let amount = (pan * 100.0).round() as i32;
+    if amount < 0 {
+        format!("L{}", -amount)
+    } else if amount > 0 {
+        format!("R{}", amount)
+    } else {
+        "C".to_string()
+    }
+}
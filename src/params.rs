@@ -0,0 +1,1176 @@
+// Every parameter the host sees, in order, with everything about it in one
+// place: its name, its unit, where it starts, and how its value reads.  The
+// values themselves are kept in `WhisperParameters`, where the audio thread
+// reads them by name, and each entry here points at its own.  Adding a
+// parameter means giving it a field there and an entry on the end of the
+// list here.
+
+use crate::arp::{arp_gate, arp_octaves, ArpMode};
+use crate::crusher::{bit_depth, downsample_factor};
+use crate::delay::{delay_feedback, DELAY_TIME};
+use crate::drive::{drive_db, trim_db, DriveCurve, DriveRouting};
+use crate::drum::{drum_decay, drum_tune};
+use crate::envelope::ENVELOPE_TIME;
+use crate::eq::{eq_db, HIGH_FREQUENCY, LOW_FREQUENCY, MID_FREQUENCY};
+use crate::filter::{FilterMode, CUTOFF};
+use crate::gain::{format_db, volume_db, volume_parameter};
+use crate::granular::{pitch_spray, GRAIN_DENSITY, GRAIN_SIZE};
+use crate::learn::learn_target;
+use crate::lfo::{LfoDestination, LfoShape, LFO_RATE};
+use crate::matrix::{ModDestination, ModSource, MATRIX_SLOTS};
+use crate::midi::MidiOutput;
+use crate::modulation::Destination;
+use crate::notes::{note_name, NotePriority, NOTE_NAMES};
+use crate::oscillator::{bend_range, fm_index, fm_ratio, sub_waveform, Waveform};
+use crate::pan::format_pan;
+use crate::range::{Range, PERCENT};
+use crate::sample::root_note;
+use crate::scale::{scale_root, Scale};
+use crate::sequencer::{step_pitch, step_velocity, SEQUENCER_STEPS};
+use crate::smoother::SMOOTHING_TIME;
+use crate::tempo::Division;
+use crate::velocity::VelocityCurve;
+use crate::voice::{
+    humanize_seed, polyphony, unison_voices, SynthesisMode, VoiceMode, GLIDE_TIME, MAX_VOICES,
+};
+use crate::{bipolar, on_off, WhisperParameters};
+use std::cmp::Ordering;
+use vst::util::AtomicFloat;
+
+// How many values `parse` tries for parameters that read as text.
+const PARSE_STEPS: usize = 1000;
+
+// Units that can be typed in instead of a parameter's own label: the label,
+// the other unit, and how many of the label's unit one of it is.
+const UNITS: &[(&str, &str, f32)] = &[
+    ("ms", "s", 1000.0),
+    ("Hz", "khz", 1000.0),
+    ("Hz", "k", 1000.0),
+];
+
+type Value = dyn Fn(&WhisperParameters) -> &AtomicFloat + Send + Sync;
+type Text = dyn Fn(&WhisperParameters, f32) -> String + Send + Sync;
+
+pub struct ParamDef {
+    pub name: String,
+    // The unit the host shows after the text.
+    pub label: &'static str,
+    pub default: f32,
+    // Which part of the synth it belongs to.
+    pub category: Category,
+    // Settings that are picked once for a project, rather than played with,
+    // are left out of the host's list of things to automate.
+    pub automatable: bool,
+    // How many choices there are, for parameters like the waveform that
+    // jump from one thing to the next rather than moving smoothly.
+    pub steps: Option<usize>,
+    // The part of the parameter's range that randomizing picks from, or
+    // `None` for the ones it should leave alone, like the volume and the
+    // MIDI settings.
+    pub random: Option<(f32, f32)>,
+    value: Box<Value>,
+    format: Format,
+}
+
+// The parts of the synth, for grouping parameters together.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Category {
+    Master,
+    Oscillator,
+    Amp,
+    Filter,
+    Modulation,
+    Voice,
+    Effects,
+    Sampler,
+    Drums,
+    Arpeggiator,
+    Sequencer,
+    Midi,
+}
+
+// How the value reads.
+enum Format {
+    // A number in the unit of the label: the real value the parameter's range
+    // gives, times `scale` to get it into that unit, like 1000 for a time in
+    // seconds shown in ms.
+    Number {
+        range: Range,
+        scale: f32,
+        decimals: usize,
+    },
+    // Anything else, like the names of the choices a parameter has.
+    Text(Box<Text>),
+}
+
+impl ParamDef {
+    fn new<N, V, T>(name: N, label: &'static str, default: f32, value: V, text: T) -> Self
+    where
+        N: Into<String>,
+        V: Fn(&WhisperParameters) -> &AtomicFloat + Send + Sync + 'static,
+        T: Fn(f32) -> String + Send + Sync + 'static,
+    {
+        Self {
+            name: name.into(),
+            label,
+            default,
+            category: Category::Master,
+            automatable: true,
+            steps: None,
+            random: Some((0.0, 1.0)),
+            value: Box::new(value),
+            format: Format::Text(Box::new(move |_, value| text(value))),
+        }
+    }
+
+    fn ranged<N, V>(
+        name: N,
+        label: &'static str,
+        default: f32,
+        value: V,
+        range: Range,
+        scale: f32,
+        decimals: usize,
+    ) -> Self
+    where
+        N: Into<String>,
+        V: Fn(&WhisperParameters) -> &AtomicFloat + Send + Sync + 'static,
+    {
+        Self {
+            name: name.into(),
+            label,
+            default,
+            category: Category::Master,
+            automatable: true,
+            steps: None,
+            random: Some((0.0, 1.0)),
+            value: Box::new(value),
+            format: Format::Number {
+                range,
+                scale,
+                decimals,
+            },
+        }
+    }
+
+    fn in_category(mut self, category: Category) -> Self {
+        self.category = category;
+        self
+    }
+
+    fn not_automated(mut self) -> Self {
+        self.automatable = false;
+        self
+    }
+
+    fn random_between(mut self, low: f32, high: f32) -> Self {
+        self.random = Some((low, high));
+        self
+    }
+
+    fn not_random(mut self) -> Self {
+        self.random = None;
+        self
+    }
+
+    fn with_steps(mut self, steps: usize) -> Self {
+        self.steps = Some(steps);
+        self.default = self.snap(self.default);
+        self
+    }
+
+    // For the few parameters whose text depends on more than their own value.
+    fn with_text<T>(mut self, text: T) -> Self
+    where
+        T: Fn(&WhisperParameters, f32) -> String + Send + Sync + 'static,
+    {
+        self.format = Format::Text(Box::new(text));
+        self
+    }
+
+    pub fn value<'a>(&self, parameters: &'a WhisperParameters) -> &'a AtomicFloat {
+        (self.value)(parameters)
+    }
+
+    // The value of the choice `value` picks, with the first choice at 0.0 and
+    // the last at 1.0, so hosts showing the value as a number spread them out
+    // evenly.  Anything that isn't stepped is left as it is.
+    pub fn snap(&self, value: f32) -> f32 {
+        match self.steps {
+            Some(steps) if steps > 1 => {
+                let choice = ((value * steps as f32) as usize).min(steps - 1);
+                choice as f32 / (steps - 1) as f32
+            }
+            _ => value,
+        }
+    }
+
+    pub fn text(&self, parameters: &WhisperParameters) -> String {
+        self.format(parameters, self.value(parameters).get())
+    }
+
+    // How `value` would read, whether or not it's the parameter's value now.
+    pub fn format(&self, parameters: &WhisperParameters, value: f32) -> String {
+        match &self.format {
+            Format::Number {
+                range,
+                scale,
+                decimals,
+            } => format!("{:.*}", decimals, range.map(value) * scale),
+            Format::Text(text) => text(parameters, value),
+        }
+    }
+
+    // The 0.0 to 1.0 value for something typed in, like "440", "2 s" or
+    // "-6 dB", or `None` if it doesn't read as this parameter at all.
+    pub fn parse(&self, parameters: &WhisperParameters, text: &str) -> Option<f32> {
+        let text = text.trim();
+        match &self.format {
+            Format::Number { range, scale, .. } => {
+                let (number, unit) = split_number(text)?;
+                let unit = unit.to_lowercase();
+                let factor = if unit.is_empty() || unit == self.label.to_lowercase() {
+                    1.0
+                } else {
+                    UNITS
+                        .iter()
+                        .find(|&&(label, other, _)| label == self.label && other == unit)?
+                        .2
+                };
+                Some(range.unmap(number * factor / scale))
+            }
+            // There's no range to work back through, so try the values the
+            // parameter can have, and take the first that reads the same, or
+            // failing that, the one whose number is closest.
+            Format::Text(_) => {
+                let values = (0..=PARSE_STEPS).map(|step| step as f32 / PARSE_STEPS as f32);
+                let same = values
+                    .clone()
+                    .find(|&value| self.format(parameters, value).eq_ignore_ascii_case(text));
+                if same.is_some() {
+                    return same;
+                }
+                let (number, _) = split_number(text)?;
+                values
+                    .filter_map(|value| {
+                        let (other, _) = split_number(&self.format(parameters, value))?;
+                        Some((value, (other - number).abs()))
+                    })
+                    .min_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(Ordering::Equal))
+                    .map(|(value, _)| value)
+            }
+        }
+    }
+}
+
+pub fn parameter_defs() -> Vec<ParamDef> {
+    let mut defs = vec![
+        // Volume shows its unit in the text itself, so "-inf dB" reads right.
+        ParamDef::new(
+            "volume",
+            "",
+            volume_parameter(0.0),
+            |p| &p.volume,
+            |value| format_db(volume_db(value)),
+        )
+        .in_category(Category::Master)
+        .not_random(),
+        ParamDef::new(
+            "waveform",
+            "",
+            0.0,
+            |p| &p.waveform,
+            |value| Waveform::from_parameter(value).name().to_string(),
+        )
+        .with_steps(Waveform::ALL.len())
+        .in_category(Category::Oscillator),
+        ParamDef::ranged("attack", "ms", 0.1, |p| &p.attack, ENVELOPE_TIME, 1000.0, 0)
+            .in_category(Category::Amp)
+            .random_between(0.0, 0.4),
+        ParamDef::ranged("decay", "ms", 0.3, |p| &p.decay, ENVELOPE_TIME, 1000.0, 0)
+            .in_category(Category::Amp)
+            .random_between(0.1, 0.6),
+        ParamDef::ranged("sustain", "%", 0.8, |p| &p.sustain, PERCENT, 100.0, 0)
+            .in_category(Category::Amp),
+        ParamDef::ranged(
+            "release",
+            "ms",
+            0.3,
+            |p| &p.release,
+            ENVELOPE_TIME,
+            1000.0,
+            0,
+        )
+        .in_category(Category::Amp)
+        .random_between(0.1, 0.5),
+        ParamDef::new(
+            "polyphony",
+            "",
+            0.5,
+            |p| &p.polyphony,
+            |value| format!("{} / {}", polyphony(value), MAX_VOICES),
+        )
+        .in_category(Category::Voice)
+        .not_random(),
+        ParamDef::new(
+            "bend range",
+            "semitones",
+            0.0833,
+            |p| &p.bend_range,
+            |value| format!("{:.0}", bend_range(value)),
+        )
+        .in_category(Category::Voice)
+        .not_random(),
+        ParamDef::new(
+            "mod wheel",
+            "",
+            0.0,
+            |p| &p.mod_wheel,
+            |value| Destination::from_parameter(value).name().to_string(),
+        )
+        .with_steps(Destination::ALL.len())
+        .in_category(Category::Modulation),
+        ParamDef::ranged("cutoff", "Hz", 1.0, |p| &p.cutoff, CUTOFF, 1.0, 0)
+            .in_category(Category::Filter)
+            .random_between(0.3, 1.0),
+        ParamDef::ranged("resonance", "%", 0.0, |p| &p.resonance, PERCENT, 100.0, 0)
+            .in_category(Category::Filter)
+            .random_between(0.0, 0.7),
+        ParamDef::new(
+            "filter mode",
+            "",
+            0.0,
+            |p| &p.filter_mode,
+            |value| FilterMode::from_parameter(value).name().to_string(),
+        )
+        .with_steps(FilterMode::ALL.len())
+        .in_category(Category::Filter),
+        ParamDef::ranged(
+            "filter attack",
+            "ms",
+            0.0,
+            |p| &p.filter_attack,
+            ENVELOPE_TIME,
+            1000.0,
+            0,
+        )
+        .in_category(Category::Filter)
+        .random_between(0.0, 0.4),
+        ParamDef::ranged(
+            "filter decay",
+            "ms",
+            0.3,
+            |p| &p.filter_decay,
+            ENVELOPE_TIME,
+            1000.0,
+            0,
+        )
+        .in_category(Category::Filter)
+        .random_between(0.1, 0.6),
+        ParamDef::ranged(
+            "filter sustain",
+            "%",
+            0.0,
+            |p| &p.filter_sustain,
+            PERCENT,
+            100.0,
+            0,
+        )
+        .in_category(Category::Filter),
+        ParamDef::ranged(
+            "filter release",
+            "ms",
+            0.3,
+            |p| &p.filter_release,
+            ENVELOPE_TIME,
+            1000.0,
+            0,
+        )
+        .in_category(Category::Filter)
+        .random_between(0.1, 0.5),
+        ParamDef::new(
+            "filter env amount",
+            "%",
+            0.5,
+            |p| &p.filter_env_amount,
+            |value| format!("{:+.0}", bipolar(value) * 100.0),
+        )
+        .in_category(Category::Filter),
+        ParamDef::new(
+            "lfo shape",
+            "",
+            0.0,
+            |p| &p.lfo_shape,
+            |value| LfoShape::from_parameter(value).name().to_string(),
+        )
+        .with_steps(LfoShape::ALL.len())
+        .in_category(Category::Modulation),
+        ParamDef::ranged("lfo rate", "Hz", 0.5, |p| &p.lfo_rate, LFO_RATE, 1.0, 2)
+            .in_category(Category::Modulation),
+        ParamDef::ranged("lfo depth", "%", 0.0, |p| &p.lfo_depth, PERCENT, 100.0, 0)
+            .in_category(Category::Modulation)
+            .random_between(0.0, 0.5),
+        ParamDef::new(
+            "lfo destination",
+            "",
+            0.0,
+            |p| &p.lfo_destination,
+            |value| LfoDestination::from_parameter(value).name().to_string(),
+        )
+        .with_steps(LfoDestination::ALL.len())
+        .in_category(Category::Modulation),
+        ParamDef::new("lfo sync", "", 0.0, |p| &p.lfo_sync, on_off)
+            .with_steps(2)
+            .in_category(Category::Modulation),
+        ParamDef::new(
+            "lfo division",
+            "",
+            0.2,
+            |p| &p.lfo_division,
+            |value| Division::from_parameter(value).name().to_string(),
+        )
+        .with_steps(Division::ALL.len())
+        .in_category(Category::Modulation),
+        ParamDef::ranged(
+            "smoothing",
+            "ms",
+            0.2,
+            |p| &p.smoothing,
+            SMOOTHING_TIME,
+            1000.0,
+            0,
+        )
+        .in_category(Category::Master)
+        .not_random(),
+        ParamDef::new(
+            "voice mode",
+            "",
+            0.0,
+            |p| &p.voice_mode,
+            |value| VoiceMode::from_parameter(value).name().to_string(),
+        )
+        .with_steps(VoiceMode::ALL.len())
+        .in_category(Category::Voice),
+        ParamDef::ranged("glide", "ms", 0.0, |p| &p.glide, GLIDE_TIME, 1000.0, 0)
+            .in_category(Category::Voice)
+            .random_between(0.0, 0.2),
+        ParamDef::new(
+            "unison voices",
+            "",
+            0.0,
+            |p| &p.unison,
+            |value| format!("{}", unison_voices(value)),
+        )
+        .in_category(Category::Voice),
+        ParamDef::ranged(
+            "unison detune",
+            "cents",
+            0.2,
+            |p| &p.unison_detune,
+            PERCENT,
+            100.0,
+            0,
+        )
+        .in_category(Category::Voice)
+        .random_between(0.0, 0.5),
+        ParamDef::ranged(
+            "stereo spread",
+            "%",
+            0.5,
+            |p| &p.stereo_spread,
+            PERCENT,
+            100.0,
+            0,
+        )
+        .in_category(Category::Voice),
+        ParamDef::new(
+            "pan",
+            "",
+            0.5,
+            |p| &p.pan,
+            |value| format_pan(bipolar(value)),
+        )
+        .in_category(Category::Master)
+        .not_random(),
+        ParamDef::new("limiter", "", 1.0, |p| &p.limiter, on_off)
+            .with_steps(2)
+            .in_category(Category::Master)
+            .not_random(),
+        ParamDef::ranged(
+            "delay time",
+            "ms",
+            0.5,
+            |p| &p.delay_time,
+            DELAY_TIME,
+            1000.0,
+            0,
+        )
+        .in_category(Category::Effects),
+        ParamDef::new("delay sync", "", 0.0, |p| &p.delay_sync, on_off)
+            .with_steps(2)
+            .in_category(Category::Effects),
+        ParamDef::new(
+            "delay division",
+            "",
+            0.2,
+            |p| &p.delay_division,
+            |value| Division::from_parameter(value).name().to_string(),
+        )
+        .with_steps(Division::ALL.len())
+        .in_category(Category::Effects),
+        ParamDef::new(
+            "delay feedback",
+            "%",
+            0.4,
+            |p| &p.delay_feedback,
+            |value| format!("{:.0}", delay_feedback(value) * 100.0),
+        )
+        .in_category(Category::Effects)
+        .random_between(0.0, 0.7),
+        ParamDef::ranged("delay mix", "%", 0.0, |p| &p.delay_mix, PERCENT, 100.0, 0)
+            .in_category(Category::Effects)
+            .random_between(0.0, 0.4),
+        ParamDef::ranged(
+            "reverb size",
+            "%",
+            0.5,
+            |p| &p.reverb_size,
+            PERCENT,
+            100.0,
+            0,
+        )
+        .in_category(Category::Effects),
+        ParamDef::ranged(
+            "reverb damping",
+            "%",
+            0.5,
+            |p| &p.reverb_damping,
+            PERCENT,
+            100.0,
+            0,
+        )
+        .in_category(Category::Effects),
+        ParamDef::ranged("reverb mix", "%", 0.0, |p| &p.reverb_mix, PERCENT, 100.0, 0)
+            .in_category(Category::Effects)
+            .random_between(0.0, 0.4),
+        ParamDef::new(
+            "drive curve",
+            "",
+            0.0,
+            |p| &p.drive_curve,
+            |value| DriveCurve::from_parameter(value).name().to_string(),
+        )
+        .with_steps(DriveCurve::ALL.len())
+        .in_category(Category::Effects),
+        ParamDef::new(
+            "drive",
+            "",
+            0.0,
+            |p| &p.drive,
+            |value| format_db(drive_db(value)),
+        )
+        .in_category(Category::Effects)
+        .random_between(0.0, 0.4),
+        ParamDef::new(
+            "drive trim",
+            "",
+            0.5,
+            |p| &p.drive_trim,
+            |value| format_db(trim_db(value)),
+        )
+        .in_category(Category::Effects),
+        ParamDef::new(
+            "drive routing",
+            "",
+            1.0,
+            |p| &p.drive_routing,
+            |value| DriveRouting::from_parameter(value).name().to_string(),
+        )
+        .with_steps(DriveRouting::ALL.len())
+        .in_category(Category::Effects),
+        ParamDef::new(
+            "bit depth",
+            "bits",
+            1.0,
+            |p| &p.bit_depth,
+            |value| format!("{}", bit_depth(value)),
+        )
+        .in_category(Category::Effects)
+        .random_between(0.6, 1.0),
+        ParamDef::new(
+            "downsample",
+            "",
+            0.0,
+            |p| &p.downsample,
+            |value| format!("{}x", downsample_factor(value)),
+        )
+        .in_category(Category::Effects)
+        .random_between(0.0, 0.2),
+        ParamDef::new(
+            "eq low gain",
+            "",
+            0.5,
+            |p| &p.eq_low_gain,
+            |value| format_db(eq_db(value)),
+        )
+        .in_category(Category::Effects)
+        .random_between(0.35, 0.65),
+        ParamDef::ranged(
+            "eq low freq",
+            "Hz",
+            0.5,
+            |p| &p.eq_low_frequency,
+            LOW_FREQUENCY,
+            1.0,
+            0,
+        )
+        .in_category(Category::Effects),
+        ParamDef::new(
+            "eq mid gain",
+            "",
+            0.5,
+            |p| &p.eq_mid_gain,
+            |value| format_db(eq_db(value)),
+        )
+        .in_category(Category::Effects)
+        .random_between(0.35, 0.65),
+        ParamDef::ranged(
+            "eq mid freq",
+            "Hz",
+            0.5,
+            |p| &p.eq_mid_frequency,
+            MID_FREQUENCY,
+            1.0,
+            0,
+        )
+        .in_category(Category::Effects),
+        ParamDef::new(
+            "eq high gain",
+            "",
+            0.5,
+            |p| &p.eq_high_gain,
+            |value| format_db(eq_db(value)),
+        )
+        .in_category(Category::Effects)
+        .random_between(0.35, 0.65),
+        ParamDef::ranged(
+            "eq high freq",
+            "Hz",
+            0.5,
+            |p| &p.eq_high_frequency,
+            HIGH_FREQUENCY,
+            1.0,
+            0,
+        )
+        .in_category(Category::Effects),
+        ParamDef::ranged("sub level", "%", 0.0, |p| &p.sub_level, PERCENT, 100.0, 0)
+            .in_category(Category::Oscillator)
+            .random_between(0.0, 0.5),
+        ParamDef::new(
+            "sub waveform",
+            "",
+            0.0,
+            |p| &p.sub_waveform,
+            |value| sub_waveform(value).name().to_string(),
+        )
+        .with_steps(2)
+        .in_category(Category::Oscillator),
+        ParamDef::new(
+            "synthesis mode",
+            "",
+            0.0,
+            |p| &p.synthesis_mode,
+            |value| SynthesisMode::from_parameter(value).name().to_string(),
+        )
+        .with_steps(SynthesisMode::ALL.len())
+        .in_category(Category::Oscillator)
+        .not_random(),
+        ParamDef::new(
+            "fm ratio",
+            "",
+            0.0625,
+            |p| &p.fm_ratio,
+            |value| format!("{}x", fm_ratio(value)),
+        )
+        .in_category(Category::Oscillator),
+        ParamDef::new(
+            "fm index",
+            "",
+            0.2,
+            |p| &p.fm_index,
+            |value| format!("{:.2}", fm_index(value)),
+        )
+        .in_category(Category::Oscillator)
+        .random_between(0.0, 0.5),
+        ParamDef::ranged("ring mod", "%", 0.0, |p| &p.ring_mod, PERCENT, 100.0, 0)
+            .in_category(Category::Oscillator)
+            .random_between(0.0, 0.3),
+        ParamDef::new(
+            "sample root",
+            "",
+            0.4724,
+            |p| &p.sample_root,
+            |value| note_name(root_note(value)),
+        )
+        .in_category(Category::Sampler)
+        .not_random(),
+        ParamDef::ranged(
+            "sample start",
+            "%",
+            0.0,
+            |p| &p.sample_start,
+            PERCENT,
+            100.0,
+            1,
+        )
+        .in_category(Category::Sampler)
+        .not_random(),
+        ParamDef::ranged("sample end", "%", 1.0, |p| &p.sample_end, PERCENT, 100.0, 1)
+            .in_category(Category::Sampler)
+            .not_random(),
+        ParamDef::new("sample loop", "", 0.0, |p| &p.sample_loop, on_off)
+            .with_steps(2)
+            .in_category(Category::Sampler)
+            .not_random(),
+        ParamDef::ranged(
+            "grain size",
+            "ms",
+            0.3,
+            |p| &p.grain_size,
+            GRAIN_SIZE,
+            1000.0,
+            0,
+        )
+        .in_category(Category::Sampler),
+        ParamDef::ranged(
+            "grain density",
+            "Hz",
+            0.5,
+            |p| &p.grain_density,
+            GRAIN_DENSITY,
+            1.0,
+            1,
+        )
+        .in_category(Category::Sampler),
+        ParamDef::new(
+            "pitch spray",
+            "semitones",
+            0.0,
+            |p| &p.pitch_spray,
+            |value| format!("{:.1}", pitch_spray(value)),
+        )
+        .in_category(Category::Sampler),
+        ParamDef::ranged(
+            "stereo spray",
+            "%",
+            0.5,
+            |p| &p.stereo_spray,
+            PERCENT,
+            100.0,
+            0,
+        )
+        .in_category(Category::Sampler),
+        ParamDef::new(
+            "drum decay",
+            "",
+            0.5,
+            |p| &p.drum_decay,
+            |value| format!("{:.2}x", drum_decay(value)),
+        )
+        .in_category(Category::Drums),
+        ParamDef::new(
+            "drum tune",
+            "semitones",
+            0.5,
+            |p| &p.drum_tune,
+            |value| format!("{:+.0}", drum_tune(value)),
+        )
+        .in_category(Category::Drums),
+        ParamDef::new(
+            "velocity curve",
+            "",
+            0.375,
+            |p| &p.velocity_curve,
+            |value| VelocityCurve::from_parameter(value).name().to_string(),
+        )
+        .with_steps(VelocityCurve::ALL.len())
+        .in_category(Category::Voice)
+        .not_random(),
+    ];
+    defs.extend(matrix_defs());
+    defs.extend(vec![
+        ParamDef::new(
+            "lfo 2 shape",
+            "",
+            0.0,
+            |p| &p.lfo2_shape,
+            |value| LfoShape::from_parameter(value).name().to_string(),
+        )
+        .with_steps(LfoShape::ALL.len())
+        .in_category(Category::Modulation),
+        ParamDef::ranged("lfo 2 rate", "Hz", 0.3, |p| &p.lfo2_rate, LFO_RATE, 1.0, 2)
+            .in_category(Category::Modulation),
+        ParamDef::ranged(
+            "env 3 attack",
+            "ms",
+            0.1,
+            |p| &p.aux_attack,
+            ENVELOPE_TIME,
+            1000.0,
+            0,
+        )
+        .in_category(Category::Modulation),
+        ParamDef::ranged(
+            "env 3 decay",
+            "ms",
+            0.3,
+            |p| &p.aux_decay,
+            ENVELOPE_TIME,
+            1000.0,
+            0,
+        )
+        .in_category(Category::Modulation),
+        ParamDef::ranged(
+            "env 3 sustain",
+            "%",
+            0.5,
+            |p| &p.aux_sustain,
+            PERCENT,
+            100.0,
+            0,
+        )
+        .in_category(Category::Modulation),
+        ParamDef::ranged(
+            "env 3 release",
+            "ms",
+            0.3,
+            |p| &p.aux_release,
+            ENVELOPE_TIME,
+            1000.0,
+            0,
+        )
+        .in_category(Category::Modulation),
+        ParamDef::ranged(
+            "random rate",
+            "Hz",
+            0.5,
+            |p| &p.random_rate,
+            LFO_RATE,
+            1.0,
+            2,
+        )
+        .in_category(Category::Modulation),
+        ParamDef::new("random sync", "", 0.0, |p| &p.random_sync, on_off)
+            .with_steps(2)
+            .in_category(Category::Modulation),
+        ParamDef::new(
+            "random division",
+            "",
+            0.4,
+            |p| &p.random_division,
+            |value| Division::from_parameter(value).name().to_string(),
+        )
+        .with_steps(Division::ALL.len())
+        .in_category(Category::Modulation),
+        ParamDef::new("arpeggiator", "", 0.0, |p| &p.arp, on_off)
+            .with_steps(2)
+            .in_category(Category::Arpeggiator)
+            .not_random(),
+        ParamDef::new(
+            "arp mode",
+            "",
+            0.0,
+            |p| &p.arp_mode,
+            |value| ArpMode::from_parameter(value).name().to_string(),
+        )
+        .with_steps(ArpMode::ALL.len())
+        .in_category(Category::Arpeggiator),
+        ParamDef::new(
+            "arp rate",
+            "",
+            0.7,
+            |p| &p.arp_division,
+            |value| Division::from_parameter(value).name().to_string(),
+        )
+        .with_steps(Division::ALL.len())
+        .in_category(Category::Arpeggiator),
+        ParamDef::new(
+            "arp gate",
+            "%",
+            0.5,
+            |p| &p.arp_gate,
+            |value| format!("{:.0}", arp_gate(value) * 100.0),
+        )
+        .in_category(Category::Arpeggiator),
+        ParamDef::new(
+            "arp octaves",
+            "",
+            0.0,
+            |p| &p.arp_octaves,
+            |value| arp_octaves(value).to_string(),
+        )
+        .in_category(Category::Arpeggiator),
+        ParamDef::new("arp latch", "", 0.0, |p| &p.arp_latch, on_off)
+            .with_steps(2)
+            .in_category(Category::Arpeggiator),
+        ParamDef::new("sequencer", "", 0.0, |p| &p.sequencer, on_off)
+            .with_steps(2)
+            .in_category(Category::Sequencer)
+            .not_random(),
+        ParamDef::new(
+            "sequencer rate",
+            "",
+            0.7,
+            |p| &p.sequencer_division,
+            |value| Division::from_parameter(value).name().to_string(),
+        )
+        .with_steps(Division::ALL.len())
+        .in_category(Category::Sequencer),
+    ]);
+    defs.extend(sequencer_defs());
+    defs.extend(vec![
+        ParamDef::new("reset on play", "", 0.0, |p| &p.transport_reset, on_off)
+            .with_steps(2)
+            .in_category(Category::Master)
+            .not_random(),
+        ParamDef::new("mpe", "", 0.0, |p| &p.mpe, on_off)
+            .with_steps(2)
+            .in_category(Category::Midi)
+            .not_automated()
+            .not_random(),
+        ParamDef::new("midi learn", "", 0.0, |p| &p.midi_learn, |_| String::new())
+            .with_text(|p, value| match learn_target(value, p.parameter_count()) {
+                Some(parameter) => p.defs[parameter as usize].name.clone(),
+                None => "Off".to_string(),
+            })
+            .in_category(Category::Midi)
+            .not_automated()
+            .not_random(),
+        ParamDef::new("program change", "", 1.0, |p| &p.program_change, on_off)
+            .with_steps(2)
+            .in_category(Category::Midi)
+            .not_automated()
+            .not_random(),
+        ParamDef::new(
+            "midi output",
+            "",
+            0.0,
+            |p| &p.midi_output,
+            |value| MidiOutput::from_parameter(value).name().to_string(),
+        )
+        .with_steps(MidiOutput::ALL.len())
+        .in_category(Category::Midi)
+        .not_automated()
+        .not_random(),
+        ParamDef::new(
+            "note priority",
+            "",
+            0.0,
+            |p| &p.note_priority,
+            |value| NotePriority::from_parameter(value).name().to_string(),
+        )
+        .with_steps(NotePriority::ALL.len())
+        .in_category(Category::Voice)
+        .not_random(),
+        ParamDef::new(
+            "scale",
+            "",
+            0.0,
+            |p| &p.scale,
+            |value| Scale::from_parameter(value).name().to_string(),
+        )
+        .with_steps(Scale::ALL.len())
+        .in_category(Category::Voice)
+        .not_random(),
+        ParamDef::new(
+            "scale root",
+            "",
+            0.0,
+            |p| &p.scale_root,
+            |value| NOTE_NAMES[usize::from(scale_root(value))].to_string(),
+        )
+        .in_category(Category::Voice)
+        .not_random(),
+        ParamDef::ranged("humanize", "%", 0.0, |p| &p.humanize, PERCENT, 100.0, 0)
+            .in_category(Category::Voice),
+        ParamDef::new(
+            "humanize seed",
+            "",
+            0.0,
+            |p| &p.humanize_seed,
+            |value| humanize_seed(value).map_or("Random".to_string(), |seed| seed.to_string()),
+        )
+        .in_category(Category::Voice)
+        .not_random(),
+    ]);
+    defs
+}
+
+// The number at the start of `text`, and whatever comes after it.
+fn split_number(text: &str) -> Option<(f32, &str)> {
+    let end = text
+        .find(|c: char| !(c.is_ascii_digit() || c == '.' || c == '-' || c == '+'))
+        .unwrap_or(text.len());
+    let number = text[..end].parse().ok()?;
+    Some((number, text[end..].trim()))
+}
+
+// Three for each slot of the mod matrix.
+fn matrix_defs() -> Vec<ParamDef> {
+    let mut defs = Vec::new();
+    for slot in 0..MATRIX_SLOTS {
+        let number = slot + 1;
+        defs.push(
+            ParamDef::new(
+                format!("mod {} source", number),
+                "",
+                0.0,
+                move |p| &p.mod_matrix[slot].source,
+                |value| ModSource::from_parameter(value).name().to_string(),
+            )
+            .with_steps(ModSource::ALL.len())
+            .in_category(Category::Modulation),
+        );
+        defs.push(
+            ParamDef::new(
+                format!("mod {} destination", number),
+                "",
+                0.0,
+                move |p| &p.mod_matrix[slot].destination,
+                |value| ModDestination::from_parameter(value).name().to_string(),
+            )
+            .with_steps(ModDestination::ALL.len())
+            .in_category(Category::Modulation),
+        );
+        defs.push(
+            ParamDef::new(
+                format!("mod {} amount", number),
+                "%",
+                0.5,
+                move |p| &p.mod_matrix[slot].amount,
+                |value| format!("{:+.0}", bipolar(value) * 100.0),
+            )
+            .in_category(Category::Modulation),
+        );
+    }
+    defs
+}
+
+// And three for each step of the sequencer.
+fn sequencer_defs() -> Vec<ParamDef> {
+    let mut defs = Vec::new();
+    for step in 0..SEQUENCER_STEPS {
+        let number = step + 1;
+        defs.push(
+            ParamDef::new(
+                format!("step {} pitch", number),
+                "semitones",
+                0.5,
+                move |p| &p.sequence[step].pitch,
+                |value| format!("{:+}", step_pitch(value)),
+            )
+            .in_category(Category::Sequencer),
+        );
+        // The unit is in the text, so a rest doesn't read as "Rest %".
+        defs.push(
+            ParamDef::new(
+                format!("step {} gate", number),
+                "",
+                0.5,
+                move |p| &p.sequence[step].gate,
+                |value| {
+                    if value == 0.0 {
+                        "Rest".to_string()
+                    } else {
+                        format!("{:.0}%", value * 100.0)
+                    }
+                },
+            )
+            .in_category(Category::Sequencer),
+        );
+        defs.push(
+            ParamDef::new(
+                format!("step {} velocity", number),
+                "",
+                0.8,
+                move |p| &p.sequence[step].velocity,
+                |value| step_velocity(value).to_string(),
+            )
+            .in_category(Category::Sequencer),
+        );
+    }
+    defs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn def<'a>(parameters: &'a WhisperParameters, name: &str) -> &'a ParamDef {
+        parameters.defs.iter().find(|def| def.name == name).unwrap()
+    }
+
+    // What `text` reads as, in the parameter's own unit.
+    fn read(def: &ParamDef, range: Range, scale: f32, text: &str) -> Option<f32> {
+        let parameters = WhisperParameters::default();
+        def.parse(&parameters, text)
+            .map(|value| range.map(value) * scale)
+    }
+
+    fn close(a: Option<f32>, b: f32) -> bool {
+        match a {
+            Some(a) => (a - b).abs() < b * 1e-3,
+            None => false,
+        }
+    }
+
+    #[test]
+    fn numbers_and_units() {
+        let parameters = WhisperParameters::default();
+        let cutoff = def(&parameters, "cutoff");
+        assert!(close(read(cutoff, CUTOFF, 1.0, "440"), 440.0));
+        assert!(close(read(cutoff, CUTOFF, 1.0, "1.5k"), 1500.0));
+        assert!(close(read(cutoff, CUTOFF, 1.0, " 1.5 kHz "), 1500.0));
+        assert!(close(read(cutoff, CUTOFF, 1.0, "800 Hz"), 800.0));
+        let attack = def(&parameters, "attack");
+        assert!(close(read(attack, ENVELOPE_TIME, 1000.0, "250ms"), 250.0));
+        assert!(close(read(attack, ENVELOPE_TIME, 1000.0, "0.25 s"), 250.0));
+        // Past the ends of the range, it's the ends.
+        assert_eq!(attack.parse(&parameters, "60 s"), Some(1.0));
+        // A unit that isn't this parameter's, or no number at all.
+        assert_eq!(attack.parse(&parameters, "250 Hz"), None);
+        assert_eq!(cutoff.parse(&parameters, "loud"), None);
+    }
+
+    #[test]
+    fn text() {
+        let parameters = WhisperParameters::default();
+        let waveform = def(&parameters, "waveform");
+        for &shape in Waveform::ALL.iter() {
+            let typed = shape.name().to_lowercase();
+            let value = waveform.parse(&parameters, &typed).unwrap();
+            assert_eq!(Waveform::from_parameter(value), shape);
+        }
+        assert_eq!(waveform.parse(&parameters, "kazoo"), None);
+    }
+
+    #[test]
+    fn snapping() {
+        let parameters = WhisperParameters::default();
+        let waveform = def(&parameters, "waveform");
+        let last = (Waveform::ALL.len() - 1) as f32;
+        // Either end, and every choice spread out evenly in between.
+        assert_eq!(waveform.snap(0.0), 0.0);
+        assert_eq!(waveform.snap(1.0), 1.0);
+        for (choice, &shape) in Waveform::ALL.iter().enumerate() {
+            let snapped = waveform.snap((choice as f32 + 0.5) / Waveform::ALL.len() as f32);
+            assert_eq!(snapped, choice as f32 / last);
+            assert_eq!(Waveform::from_parameter(snapped), shape);
+        }
+        // Anything not stepped is left alone.
+        assert_eq!(def(&parameters, "cutoff").snap(0.123), 0.123);
+        for def in parameters.defs.iter() {
+            assert_eq!(def.snap(def.default), def.default, "{}", def.name);
+        }
+    }
+}
@@ -0,0 +1,205 @@
+// Presets saved as files of their own, so they can be passed around without
+// the project they were made in.  They go in a folder for each user,
+// %APPDATA%\Whisper\Presets on Windows, and are JSON that looks like
+//
+//     {
+//       "name": "Breathy Pad",
+//       "author": "someone",
+//       "tags": ["pad", "noise"],
+//       "sound": { ... }
+//     }
+//
+// where "sound" is the same thing we save in the host's projects, parameters
+// by name and all, as described in state.rs.  Only "sound" has to be there,
+// so hand-written files can leave the rest out.
+//
+// The factory presets that come with the plugin are files just the same, in
+// a Factory folder next to the user's.  They can be loaded, but they're
+// never saved over, renamed or deleted.
+
+use crate::state::Saved;
+use serde::{Deserialize, Serialize};
+use std::env;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Metadata {
+    pub name: String,
+    pub author: String,
+    pub tags: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct PresetFile {
+    #[serde(flatten)]
+    pub metadata: Metadata,
+    pub sound: Saved,
+}
+
+#[derive(Debug)]
+pub enum PresetError {
+    Io(io::Error),
+    Json(serde_json::Error),
+    // There's nowhere to keep presets for this user.
+    NoDirectory,
+    // Renaming would save over another preset.
+    Exists,
+}
+
+impl fmt::Display for PresetError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PresetError::Io(error) => write!(f, "{}", error),
+            PresetError::Json(error) => write!(f, "{}", error),
+            PresetError::NoDirectory => write!(f, "there's no folder to keep presets in"),
+            PresetError::Exists => write!(f, "there's already a preset called that"),
+        }
+    }
+}
+
+impl From<io::Error> for PresetError {
+    fn from(error: io::Error) -> Self {
+        PresetError::Io(error)
+    }
+}
+
+impl From<serde_json::Error> for PresetError {
+    fn from(error: serde_json::Error) -> Self {
+        PresetError::Json(error)
+    }
+}
+
+impl PresetFile {
+    pub fn load(path: &Path) -> Result<Self, PresetError> {
+        Ok(serde_json::from_slice(&fs::read(path)?)?)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), PresetError> {
+        if let Some(directory) = path.parent() {
+            fs::create_dir_all(directory)?;
+        }
+        fs::write(path, serde_json::to_vec_pretty(self)?)?;
+        Ok(())
+    }
+}
+
+// A preset found in one of the folders.
+pub struct PresetEntry {
+    pub name: String,
+    pub path: PathBuf,
+    pub factory: bool,
+}
+
+// Where this user's presets live, made on the first save.
+pub fn preset_directory() -> Option<PathBuf> {
+    Some(whisper_directory()?.join("Presets"))
+}
+
+pub fn factory_directory() -> Option<PathBuf> {
+    Some(whisper_directory()?.join("Factory"))
+}
+
+// Where everything we keep for this user goes, %APPDATA%\Whisper on Windows.
+// Other systems use the home folder, for running the plugin under Wine and
+// the like.
+pub fn whisper_directory() -> Option<PathBuf> {
+    let base = match env::var_os("APPDATA") {
+        Some(data) => PathBuf::from(data),
+        None => PathBuf::from(env::var_os("HOME")?)
+            .join(".local")
+            .join("share"),
+    };
+    Some(base.join("Whisper"))
+}
+
+// Every preset there is, the factory ones first, each lot in order of name.
+// Folders that aren't there yet and files that don't load are skipped.
+pub fn list_presets() -> Vec<PresetEntry> {
+    let mut presets = Vec::new();
+    let directories = [(factory_directory(), true), (preset_directory(), false)];
+    for (directory, factory) in directories.iter() {
+        let files = match directory.as_ref().map(fs::read_dir) {
+            Some(Ok(files)) => files,
+            _ => continue,
+        };
+        let mut found: Vec<PresetEntry> = files
+            .filter_map(|file| {
+                let path = file.ok()?.path();
+                if path.extension()? != "json" {
+                    return None;
+                }
+                let name = match PresetFile::load(&path).ok()?.metadata.name {
+                    name if name.is_empty() => path.file_stem()?.to_string_lossy().into_owned(),
+                    name => name,
+                };
+                Some(PresetEntry {
+                    name,
+                    path,
+                    factory: *factory,
+                })
+            })
+            .collect();
+        found.sort_by_key(|preset| preset.name.to_lowercase());
+        presets.extend(found);
+    }
+    presets
+}
+
+// Give the user's preset in `old_path` another name, which moves its file
+// too.  Where file names don't care about case, the new name can be the old
+// one in different case and still lead to the same file, which is moved
+// rather than taken for another preset.
+pub fn rename_preset(old_path: &Path, new: &str) -> Result<(), PresetError> {
+    let new_path = preset_path(new)?;
+    if !same_file(old_path, &new_path) && new_path.exists() {
+        return Err(PresetError::Exists);
+    }
+    let mut file = PresetFile::load(old_path)?;
+    file.metadata.name = new.to_string();
+    if new_path != old_path {
+        fs::rename(old_path, &new_path)?;
+    }
+    file.save(&new_path)
+}
+
+pub fn delete_preset(path: &Path) -> Result<(), PresetError> {
+    fs::remove_file(path)?;
+    Ok(())
+}
+
+// Only files that are both there can be the same one.
+fn same_file(a: &Path, b: &Path) -> bool {
+    match (fs::canonicalize(a), fs::canonicalize(b)) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => false,
+    }
+}
+
+// The file a preset with this name is saved to.  Anything that can't go in
+// a file name is swapped for an underscore.
+pub fn preset_path(name: &str) -> Result<PathBuf, PresetError> {
+    let file: String = name
+        .trim()
+        .chars()
+        .map(|c| {
+            if c.is_alphanumeric() || c == ' ' || c == '-' || c == '_' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    let directory = preset_directory().ok_or(PresetError::NoDirectory)?;
+    Ok(directory.join(format!("{}.json", file)))
+}
+
+// Whoever is logged in, for the author of new presets.
+pub fn user_name() -> String {
+    env::var("USERNAME")
+        .or_else(|_| env::var("USER"))
+        .unwrap_or_default()
+}
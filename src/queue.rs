@@ -0,0 +1,195 @@
+// A fixed-size queue for handing things from one thread to another without
+// either of them ever waiting on a lock, which the audio thread mustn't do.
+// There's one `Receiver`, but the `Sender` can be cloned, so the GUI and
+// the host can both send from their own threads at once.
+
+use std::cell::UnsafeCell;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+// Every slot counts how far round the ring it's got, so a sender can tell
+// whether it's its turn to fill it and the receiver whether it's been.  A
+// slot starts out at its own position, goes one past that once it's filled,
+// and a whole lap on from where it started once it's been taken from.
+struct Slot<T> {
+    sequence: AtomicUsize,
+    item: UnsafeCell<Option<T>>,
+}
+
+struct Ring<T> {
+    slots: Box<[Slot<T>]>,
+    // The number of slots is a power of two, so this picks a position's
+    // slot out of the count.
+    mask: usize,
+    // How many things have been taken out, and how many senders have
+    // started putting in.  They only ever go up, wrapping round.
+    head: AtomicUsize,
+    tail: AtomicUsize,
+}
+
+// Only the sender whose turn it was touches a slot until it says it's
+// filled, and then only the receiver, so no slot is used by two threads at
+// once.
+unsafe impl<T: Send> Sync for Ring<T> {}
+
+pub struct Sender<T> {
+    ring: Arc<Ring<T>>,
+}
+
+// Deriving this would only clone senders of things that can be cloned.
+impl<T> Clone for Sender<T> {
+    fn clone(&self) -> Self {
+        Self {
+            ring: Arc::clone(&self.ring),
+        }
+    }
+}
+
+pub struct Receiver<T> {
+    ring: Arc<Ring<T>>,
+}
+
+// A queue with room for at least `capacity` things at once, rounded up to a
+// power of two.  A single slot would look the same filled as free for the
+// next lap, so there are always at least two.
+pub fn queue<T>(capacity: usize) -> (Sender<T>, Receiver<T>) {
+    let size = capacity.max(2).next_power_of_two();
+    let slots: Vec<_> = (0..size)
+        .map(|position| Slot {
+            sequence: AtomicUsize::new(position),
+            item: UnsafeCell::new(None),
+        })
+        .collect();
+    let ring = Arc::new(Ring {
+        slots: slots.into_boxed_slice(),
+        mask: size - 1,
+        head: AtomicUsize::new(0),
+        tail: AtomicUsize::new(0),
+    });
+    (Sender { ring: ring.clone() }, Receiver { ring })
+}
+
+impl<T> Sender<T> {
+    // Gives it back if the queue is full.
+    pub fn send(&self, item: T) -> Result<(), T> {
+        let ring = &*self.ring;
+        let mut tail = ring.tail.load(Ordering::Relaxed);
+        loop {
+            let slot = &ring.slots[tail & ring.mask];
+            let sequence = slot.sequence.load(Ordering::Acquire);
+            let ahead = sequence.wrapping_sub(tail) as isize;
+            if ahead == 0 {
+                // Our turn, unless another sender has just taken it.
+                match ring.tail.compare_exchange_weak(
+                    tail,
+                    tail.wrapping_add(1),
+                    Ordering::Relaxed,
+                    Ordering::Relaxed,
+                ) {
+                    Ok(_) => {
+                        unsafe {
+                            *slot.item.get() = Some(item);
+                        }
+                        slot.sequence.store(tail.wrapping_add(1), Ordering::Release);
+                        return Ok(());
+                    }
+                    Err(current) => tail = current,
+                }
+            } else if ahead < 0 {
+                // The receiver hasn't got round to this slot since last time.
+                return Err(item);
+            } else {
+                tail = ring.tail.load(Ordering::Relaxed);
+            }
+        }
+    }
+}
+
+impl<T> Receiver<T> {
+    // `None` once there's nothing left.
+    pub fn receive(&mut self) -> Option<T> {
+        let ring = &*self.ring;
+        let head = ring.head.load(Ordering::Relaxed);
+        let slot = &ring.slots[head & ring.mask];
+        if slot.sequence.load(Ordering::Acquire) != head.wrapping_add(1) {
+            return None;
+        }
+        let item = unsafe { (*slot.item.get()).take() };
+        slot.sequence
+            .store(head.wrapping_add(ring.slots.len()), Ordering::Release);
+        ring.head.store(head.wrapping_add(1), Ordering::Relaxed);
+        item
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+
+    #[test]
+    fn wraps_around() {
+        let (sender, mut receiver) = queue(4);
+        // Many more than there are slots, a few at a time.
+        for round in 0..100 {
+            for i in 0..3 {
+                assert!(sender.send(round * 3 + i).is_ok());
+            }
+            for i in 0..3 {
+                assert_eq!(receiver.receive(), Some(round * 3 + i));
+            }
+            assert_eq!(receiver.receive(), None);
+        }
+    }
+
+    #[test]
+    fn gives_back_when_full() {
+        let (sender, mut receiver) = queue(4);
+        for i in 0..4 {
+            assert!(sender.send(i).is_ok());
+        }
+        assert_eq!(sender.send(4), Err(4));
+        assert_eq!(receiver.receive(), Some(0));
+        assert!(sender.send(4).is_ok());
+        let received: Vec<_> = (0..5).filter_map(|_| receiver.receive()).collect();
+        assert_eq!(received, vec![1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn several_senders() {
+        let (sender, mut receiver) = queue(64);
+        let senders: Vec<_> = (0..4)
+            .map(|first| {
+                let sender = sender.clone();
+                thread::spawn(move || {
+                    for i in 0..1000 {
+                        let mut item = first * 1000 + i;
+                        while let Err(back) = sender.send(item) {
+                            item = back;
+                            thread::yield_now();
+                        }
+                    }
+                })
+            })
+            .collect();
+        let mut received = Vec::new();
+        while received.len() < 4000 {
+            match receiver.receive() {
+                Some(item) => received.push(item),
+                None => thread::yield_now(),
+            }
+        }
+        for sender in senders {
+            sender.join().unwrap();
+        }
+        // Each sender's own come out in the order it sent them.
+        for first in 0..4 {
+            let from: Vec<_> = received
+                .iter()
+                .filter(|&&item| item / 1000 == first)
+                .collect();
+            assert!(from.windows(2).all(|pair| pair[0] < pair[1]));
+            assert_eq!(from.len(), 1000);
+        }
+    }
+}
@@ -0,0 +1,53 @@
+// What a parameter's 0.0 to 1.0 stands for.  The host only ever sees 0.0 to
+// 1.0, so it's up to us how that's spread over the real values: evenly for
+// most things, but frequencies and times want more of the knob's travel at
+// the low end, where small changes are easiest to hear.
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Curve {
+    // Evenly from `min` to `max`.
+    Linear,
+    // Raised to a power first, so the higher the power, the more of the
+    // range is squeezed towards the top of the knob.
+    Power(f32),
+    // The same interval for the same turn of the knob, like octaves for a
+    // frequency.  `min` has to be above zero.
+    Log,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Range {
+    pub min: f32,
+    pub max: f32,
+    pub curve: Curve,
+}
+
+impl Range {
+    // The real value for a 0.0 to 1.0 parameter.
+    pub fn map(self, value: f32) -> f32 {
+        let value = value.max(0.0).min(1.0);
+        match self.curve {
+            Curve::Linear => self.min + (self.max - self.min) * value,
+            Curve::Power(power) => self.min + (self.max - self.min) * value.powf(power),
+            Curve::Log => self.min * (self.max / self.min).powf(value),
+        }
+    }
+
+    // And back again, with anything outside the range pinned to its ends.
+    pub fn unmap(self, real: f32) -> f32 {
+        let real = real.max(self.min).min(self.max);
+        let value = match self.curve {
+            Curve::Linear => (real - self.min) / (self.max - self.min),
+            Curve::Power(power) => ((real - self.min) / (self.max - self.min)).powf(1.0 / power),
+            Curve::Log => (real / self.min).ln() / (self.max / self.min).ln(),
+        };
+        value.max(0.0).min(1.0)
+    }
+}
+
+// Things that go from nothing to everything, shown as a percentage.
+pub const PERCENT: Range = Range {
+    min: 0.0,
+    max: 1.0,
+    curve: Curve::Linear,
+};
@@ -0,0 +1,184 @@
+use crate::delay::ring_time;
+
+// The classic Freeverb design: eight parallel comb filters build up a dense
+// cloud of echoes, then four allpass filters in series smear them out so
+// they stop sounding like separate repeats.  The delay lengths are the ones
+// Jezar tuned by ear, in samples at 44.1 kHz.
+const COMB_TUNING: [usize; 8] = [1116, 1188, 1277, 1356, 1422, 1491, 1557, 1617];
+const ALLPASS_TUNING: [usize; 4] = [556, 441, 341, 225];
+
+// The right channel's delays are a little longer, which is what makes the
+// reverb wide instead of sitting in the middle.
+const STEREO_SPREAD: usize = 23;
+
+// Dozens of echoes on top of each other get loud, so the input is turned
+// down a lot going in and back up coming out.
+const INPUT_GAIN: f32 = 0.015;
+const WET_GAIN: f32 = 3.0;
+
+// A feedback comb filter with a one-pole low-pass in the loop, so high
+// frequencies die out faster than low ones, like they do in a real room.
+struct Comb {
+    buffer: Vec<f32>,
+    position: usize,
+    filtered: f32,
+}
+
+impl Comb {
+    fn new(length: usize) -> Self {
+        Self {
+            buffer: vec![0.0; length.max(1)],
+            position: 0,
+            filtered: 0.0,
+        }
+    }
+
+    fn clear(&mut self) {
+        for sample in self.buffer.iter_mut() {
+            *sample = 0.0;
+        }
+        self.filtered = 0.0;
+    }
+
+    fn process(&mut self, input: f32, feedback: f32, damping: f32) -> f32 {
+        let output = self.buffer[self.position];
+        self.filtered = output + (self.filtered - output) * damping;
+        self.buffer[self.position] = input + self.filtered * feedback;
+        self.position = (self.position + 1) % self.buffer.len();
+        output
+    }
+}
+
+// An allpass filter lets every frequency through at the same level, it only
+// scatters them in time.
+struct Allpass {
+    buffer: Vec<f32>,
+    position: usize,
+}
+
+impl Allpass {
+    fn new(length: usize) -> Self {
+        Self {
+            buffer: vec![0.0; length.max(1)],
+            position: 0,
+        }
+    }
+
+    fn clear(&mut self) {
+        for sample in self.buffer.iter_mut() {
+            *sample = 0.0;
+        }
+    }
+
+    fn process(&mut self, input: f32) -> f32 {
+        let delayed = self.buffer[self.position];
+        self.buffer[self.position] = input + delayed * 0.5;
+        self.position = (self.position + 1) % self.buffer.len();
+        delayed - input
+    }
+}
+
+// One channel's worth of filters.
+struct Tank {
+    combs: Vec<Comb>,
+    allpasses: Vec<Allpass>,
+}
+
+impl Tank {
+    // The tunings are scaled so the room sounds the same size at any sample rate.
+    fn new(sample_rate: f32, spread: usize) -> Self {
+        let scale = |length: usize| ((length + spread) as f32 * sample_rate / 44100.0) as usize;
+        Self {
+            combs: COMB_TUNING
+                .iter()
+                .map(|&length| Comb::new(scale(length)))
+                .collect(),
+            allpasses: ALLPASS_TUNING
+                .iter()
+                .map(|&length| Allpass::new(scale(length)))
+                .collect(),
+        }
+    }
+
+    fn clear(&mut self) {
+        for comb in self.combs.iter_mut() {
+            comb.clear();
+        }
+        for allpass in self.allpasses.iter_mut() {
+            allpass.clear();
+        }
+    }
+
+    fn process(&mut self, input: f32, feedback: f32, damping: f32) -> f32 {
+        let mut output = 0.0;
+        for comb in self.combs.iter_mut() {
+            output += comb.process(input, feedback, damping);
+        }
+        for allpass in self.allpasses.iter_mut() {
+            output = allpass.process(output);
+        }
+        output
+    }
+}
+
+pub struct Reverb {
+    left: Tank,
+    right: Tank,
+}
+
+impl Reverb {
+    pub fn new(sample_rate: f32) -> Self {
+        Self {
+            left: Tank::new(sample_rate, 0),
+            right: Tank::new(sample_rate, STEREO_SPREAD),
+        }
+    }
+
+    // The delay lines are sized from the sample rate, so they have to be
+    // rebuilt whenever it changes.
+    pub fn set_sample_rate(&mut self, sample_rate: f32) {
+        *self = Self::new(sample_rate);
+    }
+
+    // Let the room go quiet straight away.
+    pub fn clear(&mut self) {
+        self.left.clear();
+        self.right.clear();
+    }
+
+    // Feed one stereo sample through the reverb.  Both channels share the
+    // same mono input, only the tanks differ.
+    pub fn process(
+        &mut self,
+        left: &mut f32,
+        right: &mut f32,
+        feedback: f32,
+        damping: f32,
+        mix: f32,
+    ) {
+        let input = (*left + *right) * INPUT_GAIN;
+        let wet_left = self.left.process(input, feedback, damping) * WET_GAIN;
+        let wet_right = self.right.process(input, feedback, damping) * WET_GAIN;
+
+        *left += (wet_left - *left) * mix;
+        *right += (wet_right - *right) * mix;
+    }
+}
+
+// Map the 0.0 to 1.0 size parameter onto how much each comb feeds back.
+// Anything at or above 1.0 would ring forever.
+pub fn room_feedback(size: f32) -> f32 {
+    0.7 + size * 0.28
+}
+
+// How long the reverb keeps ringing after the input stops, in seconds.  The
+// longest comb decays slowest, and the damping only ever shortens that.
+pub fn reverb_tail(feedback: f32) -> f32 {
+    let longest = (COMB_TUNING[COMB_TUNING.len() - 1] + STEREO_SPREAD) as f32 / 44100.0;
+    ring_time(longest, feedback)
+}
+
+// Map the 0.0 to 1.0 damping parameter onto the low-pass in the comb loops.
+pub fn reverb_damping(value: f32) -> f32 {
+    value * 0.4
+}
@@ -0,0 +1,168 @@
+use crate::aiff;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+// A sound loaded from disk for the sampler, always kept as stereo so voices
+// don't have to care how many channels the file had.
+pub struct Sample {
+    pub left: Vec<f32>,
+    pub right: Vec<f32>,
+    pub sample_rate: f32,
+}
+
+#[derive(Debug)]
+pub enum SampleError {
+    Io(io::Error),
+    Wav(hound::Error),
+    Aiff(&'static str),
+    Empty,
+}
+
+impl fmt::Display for SampleError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SampleError::Io(error) => write!(f, "{}", error),
+            SampleError::Wav(error) => write!(f, "{}", error),
+            SampleError::Aiff(error) => write!(f, "{}", error),
+            SampleError::Empty => write!(f, "the file has no audio in it"),
+        }
+    }
+}
+
+impl From<io::Error> for SampleError {
+    fn from(error: io::Error) -> Self {
+        SampleError::Io(error)
+    }
+}
+
+impl From<hound::Error> for SampleError {
+    fn from(error: hound::Error) -> Self {
+        SampleError::Wav(error)
+    }
+}
+
+impl Sample {
+    // Load a WAV or AIFF file, going by its extension.
+    pub fn load(path: &Path) -> Result<Self, SampleError> {
+        let extension = path
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .unwrap_or("")
+            .to_lowercase();
+        let (channels, sample_rate, samples) = match extension.as_str() {
+            "aif" | "aiff" | "aifc" => {
+                let aiff = aiff::read(&fs::read(path)?).map_err(SampleError::Aiff)?;
+                (aiff.channels, aiff.sample_rate, aiff.samples)
+            }
+            _ => read_wav(path)?,
+        };
+        Self::from_interleaved(channels, sample_rate, &samples)
+    }
+
+    // Mono files play on both sides, and anything past the second channel
+    // is ignored.
+    fn from_interleaved(
+        channels: usize,
+        sample_rate: f32,
+        samples: &[f32],
+    ) -> Result<Self, SampleError> {
+        if channels == 0 || samples.len() < channels || sample_rate <= 0.0 {
+            return Err(SampleError::Empty);
+        }
+        let frames = samples.chunks_exact(channels);
+        Ok(Self {
+            left: frames.clone().map(|frame| frame[0]).collect(),
+            right: frames.map(|frame| frame[1.min(channels - 1)]).collect(),
+            sample_rate,
+        })
+    }
+
+    pub fn len(&self) -> usize {
+        self.left.len()
+    }
+
+    // Read between two samples, `position` being a fractional index.
+    // Outside of the sample there is only silence.
+    pub fn read(&self, position: f64) -> (f32, f32) {
+        if position < 0.0 {
+            return (0.0, 0.0);
+        }
+        let index = position as usize;
+        if index + 1 >= self.len() {
+            return (0.0, 0.0);
+        }
+        let fraction = (position - index as f64) as f32;
+        let left = self.left[index] + (self.left[index + 1] - self.left[index]) * fraction;
+        let right = self.right[index] + (self.right[index + 1] - self.right[index]) * fraction;
+        (left, right)
+    }
+}
+
+fn read_wav(path: &Path) -> Result<(usize, f32, Vec<f32>), SampleError> {
+    let mut reader = hound::WavReader::open(path)?;
+    let spec = reader.spec();
+    let samples = match spec.sample_format {
+        hound::SampleFormat::Float => reader.samples::<f32>().collect::<Result<Vec<_>, _>>()?,
+        // Integers come back at their own bit depth, so they are scaled
+        // down to -1.0 to 1.0 from however many bits there are.
+        hound::SampleFormat::Int => {
+            let scale = 1.0 / (1i64 << (spec.bits_per_sample.max(1) - 1)) as f32;
+            reader
+                .samples::<i32>()
+                .map(|sample| sample.map(|sample| sample as f32 * scale))
+                .collect::<Result<Vec<_>, _>>()?
+        }
+    };
+    Ok((usize::from(spec.channels), spec.sample_rate as f32, samples))
+}
+
+// Which part of the sample the sampler plays, and at what pitch.
+#[derive(Clone, Copy, Debug)]
+pub struct SamplerSettings {
+    // The frequency of the note the sample plays back at its own speed.
+    pub root_frequency: f32,
+    // Where playback starts and ends, as fractions of the whole sample.
+    pub start: f32,
+    pub end: f32,
+    // Go back to the start on reaching the end, instead of stopping.
+    pub looping: bool,
+}
+
+impl SamplerSettings {
+    // The region being played, in frames.  It's always at least a frame long.
+    pub fn region(&self, sample: &Sample) -> (f64, f64) {
+        let length = sample.len() as f64;
+        let start = (f64::from(self.start) * length).floor();
+        let end = (f64::from(self.end) * length).max(start + 1.0);
+        (start, end)
+    }
+
+    // How many frames to move through the sample per output sample, for a
+    // voice moving `increment` through its cycle.  The sample plays at its
+    // own speed at the root note, and every other note steps through it
+    // faster or slower.
+    pub fn step(&self, sample: &Sample, increment: f32) -> f64 {
+        f64::from(increment * sample.sample_rate / self.root_frequency)
+    }
+
+    // Read the sample at `position` and move it along by `step` frames.
+    pub fn play(&self, sample: &Sample, position: &mut f64, step: f64) -> (f32, f32) {
+        let (start, end) = self.region(sample);
+        if *position >= end {
+            if !self.looping {
+                return (0.0, 0.0);
+            }
+            *position = start + (*position - end) % (end - start);
+        }
+        let value = sample.read(*position);
+        *position += step;
+        value
+    }
+}
+
+// Map a 0.0 to 1.0 parameter onto a MIDI note for the sample's root.
+pub fn root_note(value: f32) -> u8 {
+    (value * 127.0).round() as u8
+}
@@ -0,0 +1,125 @@
+// Scale lock: every note that comes in gets moved to the nearest note of
+// the chosen scale, so anything played, or generated, stays in key.
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Scale {
+    Off,
+    Major,
+    Minor,
+    HarmonicMinor,
+    Dorian,
+    MajorPentatonic,
+    MinorPentatonic,
+    Blues,
+    WholeTone,
+}
+
+impl Scale {
+    pub const ALL: [Scale; 9] = [
+        Scale::Off,
+        Scale::Major,
+        Scale::Minor,
+        Scale::HarmonicMinor,
+        Scale::Dorian,
+        Scale::MajorPentatonic,
+        Scale::MinorPentatonic,
+        Scale::Blues,
+        Scale::WholeTone,
+    ];
+
+    pub fn from_parameter(value: f32) -> Self {
+        let index = (value * Self::ALL.len() as f32) as usize;
+        Self::ALL[index.min(Self::ALL.len() - 1)]
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Scale::Off => "Off",
+            Scale::Major => "Major",
+            Scale::Minor => "Minor",
+            Scale::HarmonicMinor => "Harmonic Minor",
+            Scale::Dorian => "Dorian",
+            Scale::MajorPentatonic => "Major Pentatonic",
+            Scale::MinorPentatonic => "Minor Pentatonic",
+            Scale::Blues => "Blues",
+            Scale::WholeTone => "Whole Tone",
+        }
+    }
+
+    // The notes of the scale, in semitones above the root.
+    fn degrees(self) -> &'static [u8] {
+        match self {
+            Scale::Off => &[0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11],
+            Scale::Major => &[0, 2, 4, 5, 7, 9, 11],
+            Scale::Minor => &[0, 2, 3, 5, 7, 8, 10],
+            Scale::HarmonicMinor => &[0, 2, 3, 5, 7, 8, 11],
+            Scale::Dorian => &[0, 2, 3, 5, 7, 9, 10],
+            Scale::MajorPentatonic => &[0, 2, 4, 7, 9],
+            Scale::MinorPentatonic => &[0, 3, 5, 7, 10],
+            Scale::Blues => &[0, 3, 5, 6, 7, 10],
+            Scale::WholeTone => &[0, 2, 4, 6, 8, 10],
+        }
+    }
+
+    fn contains(self, root: u8, note: i32) -> bool {
+        let degree = (note - i32::from(root)).rem_euclid(12) as u8;
+        self.degrees().contains(&degree)
+    }
+
+    // The nearest note in the scale, going down when two are as near.
+    pub fn quantize(self, root: u8, note: u8) -> u8 {
+        let note = i32::from(note);
+        for distance in 0..12 {
+            for &candidate in &[note - distance, note + distance] {
+                if (0..128).contains(&candidate) && self.contains(root, candidate) {
+                    return candidate as u8;
+                }
+            }
+        }
+        note as u8
+    }
+}
+
+// Map a 0.0 to 1.0 parameter onto a root note from C to B.
+pub fn scale_root(value: f32) -> u8 {
+    ((value * 12.0) as u8).min(11)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn nearest_tone() {
+        // In C major, F and the C an octave up are already there, and C#
+        // and F# are as near the notes either side of them, so go down.
+        assert_eq!(Scale::Major.quantize(0, 65), 65);
+        assert_eq!(Scale::Major.quantize(0, 72), 72);
+        assert_eq!(Scale::Major.quantize(0, 61), 60);
+        assert_eq!(Scale::Major.quantize(0, 66), 65);
+        // The same in D major, moved up two.
+        assert_eq!(Scale::Major.quantize(2, 63), 62);
+        assert_eq!(Scale::Major.quantize(2, 60), 59);
+        for note in 0..128 {
+            assert_eq!(Scale::Off.quantize(0, note), note);
+        }
+    }
+
+    #[test]
+    fn wraps_at_the_octave() {
+        // The B in C major pentatonic is nearer the C above than the A below.
+        assert_eq!(Scale::MajorPentatonic.quantize(0, 71), 72);
+        // And with the root on G, the F# goes up to the G after.
+        assert_eq!(Scale::MajorPentatonic.quantize(7, 66), 67);
+        // At the ends of the keyboard the nearest note the other way is taken.
+        assert_eq!(Scale::WholeTone.quantize(1, 0), 1);
+        assert_eq!(Scale::WholeTone.quantize(0, 127), 126);
+    }
+
+    #[test]
+    fn roots() {
+        assert_eq!(scale_root(0.0), 0);
+        assert_eq!(scale_root(0.5), 6);
+        assert_eq!(scale_root(1.0), 11);
+    }
+}
@@ -0,0 +1,164 @@
+use crate::midi::{midi_event, MidiMessage};
+use crate::tempo::Transport;
+use vst::event::MidiEvent;
+
+pub const SEQUENCER_STEPS: usize = 16;
+
+// The note the sequence is written around, before any key transposes it.
+const ROOT_NOTE: i32 = 60;
+
+#[derive(Clone, Copy, Debug)]
+pub struct Step {
+    // Semitones above or below the root.
+    pub pitch: i32,
+    // How much of the step the note is held for, from 0.0 to 1.0, with 0.0
+    // meaning the step is a rest.
+    pub gate: f64,
+    pub velocity: u8,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct SequencerSettings {
+    pub steps: [Step; SEQUENCER_STEPS],
+    // How long each step is, in quarter notes.
+    pub step: f64,
+}
+
+// Plays a fixed pattern of notes while the host is playing, locked to its
+// playhead, so the sequence always lands on the same step at the same
+// place in the song.  Keys don't play notes of their own, they transpose
+// the sequence, with middle C leaving it where it is.
+pub struct Sequencer {
+    held: [bool; 128],
+    transpose: i32,
+    // Where we are, in quarter notes, and which step that was in.
+    beat: f64,
+    step: Option<i64>,
+    // The note sounding right now, if any.
+    playing: Option<u8>,
+}
+
+impl Default for Sequencer {
+    fn default() -> Self {
+        Self {
+            held: [false; 128],
+            transpose: 0,
+            beat: 0.0,
+            step: None,
+            playing: None,
+        }
+    }
+}
+
+impl Sequencer {
+    // Sequence `samples` samples' worth of notes, taking keys out of
+    // `input` and adding what should be played to `output`.  Anything that
+    // isn't a note goes straight through.
+    pub fn process(
+        &mut self,
+        settings: &SequencerSettings,
+        transport: &Transport,
+        beats_per_sample: f64,
+        samples: usize,
+        input: &[MidiEvent],
+        output: &mut Vec<MidiEvent>,
+    ) {
+        if !transport.playing {
+            self.release(0, output);
+            self.step = None;
+            self.beat = 0.0;
+        } else if let Some(position) = transport.position {
+            // The playhead going backwards means the host looped or was
+            // moved, and the step it lands on has to play again even if it
+            // happens to be the one we were on.
+            if position < self.beat - beats_per_sample {
+                self.step = None;
+            }
+            self.beat = position;
+        }
+
+        let mut next = 0;
+        for i in 0..samples {
+            while next < input.len() && input[next].delta_frames.max(0) as usize <= i {
+                self.event(&input[next], output);
+                next += 1;
+            }
+            if transport.playing {
+                self.tick(settings, i as i32, output);
+                self.beat += beats_per_sample;
+            }
+        }
+        // Events past the end of the buffer shouldn't happen, but if they
+        // do they still count.
+        for ev in &input[next..] {
+            self.event(ev, output);
+        }
+    }
+
+    // Which of the steps is playing, or none while the host is stopped.
+    pub fn current_step(&self) -> Option<usize> {
+        self.step
+            .map(|step| step.rem_euclid(SEQUENCER_STEPS as i64) as usize)
+    }
+
+    // Let go of the note playing, for when the sequencer is switched off.
+    pub fn stop(&mut self, output: &mut Vec<MidiEvent>) {
+        self.release(0, output);
+        self.held = [false; 128];
+        self.step = None;
+    }
+
+    fn event(&mut self, ev: &MidiEvent, output: &mut Vec<MidiEvent>) {
+        match MidiMessage::parse(&ev.data) {
+            Some(MidiMessage::NoteOn { note, .. }) => {
+                self.held[note as usize] = true;
+                self.transpose = i32::from(note) - ROOT_NOTE;
+            }
+            // Keys that went down before we were switched on are left for
+            // the synth to let go of.
+            Some(MidiMessage::NoteOff { note, .. }) if self.held[note as usize] => {
+                self.held[note as usize] = false
+            }
+            _ => output.push(*ev),
+        }
+    }
+
+    fn tick(
+        &mut self,
+        settings: &SequencerSettings,
+        delta_frames: i32,
+        output: &mut Vec<MidiEvent>,
+    ) {
+        let position = self.beat / settings.step;
+        let step = position.floor() as i64;
+        let current = settings.steps[step.rem_euclid(SEQUENCER_STEPS as i64) as usize];
+
+        if Some(step) != self.step {
+            self.release(delta_frames, output);
+            self.step = Some(step);
+            if current.gate > 0.0 {
+                let note = (ROOT_NOTE + self.transpose + current.pitch).max(0).min(127) as u8;
+                output.push(midi_event([0x90, note, current.velocity], delta_frames));
+                self.playing = Some(note);
+            }
+        } else if position - step as f64 >= current.gate {
+            self.release(delta_frames, output);
+        }
+    }
+
+    fn release(&mut self, delta_frames: i32, output: &mut Vec<MidiEvent>) {
+        if let Some(note) = self.playing.take() {
+            output.push(midi_event([0x80, note, 0], delta_frames));
+        }
+    }
+}
+
+// Map a 0.0 to 1.0 parameter onto two octaves down to two octaves up.
+pub fn step_pitch(value: f32) -> i32 {
+    ((value * 2.0 - 1.0) * 24.0).round() as i32
+}
+
+// Map a 0.0 to 1.0 parameter onto a MIDI velocity.
+pub fn step_velocity(value: f32) -> u8 {
+    ((value * 127.0).round() as u8).max(1)
+}
@@ -0,0 +1,81 @@
+// How the editor was last left, which goes with the user rather than with
+// any one project: settings.json, in the same folder as the presets.
+// Anything missing from the file, or the file itself, is left at its
+// default, and a setting that can't be saved is just forgotten.
+
+use crate::preset::whisper_directory;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+
+// The sizes the editor can be drawn at, in percent, for screens with so
+// many pixels that it's too small to read at its own size.
+pub const SCALES: [u32; 4] = [100, 125, 150, 200];
+
+// How long the mouse can rest on a knob before its tooltip comes up, in ms.
+pub const TOOLTIP_DELAYS: [u32; 4] = [250, 500, 1000, 2000];
+
+// The most times a second the editor is drawn.
+pub const FRAME_RATES: [u32; 2] = [30, 60];
+
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    // The page of the editor that's showing, as its place along the tabs.
+    pub tab: usize,
+    // One of `SCALES`.
+    pub scale: u32,
+    // The name of one of the themes in theme.rs.
+    pub theme: String,
+    // Any number of ms, though the settings page only has `TOOLTIP_DELAYS`.
+    pub tooltip_delay: u32,
+    // The name of one of the languages in language.rs.
+    pub language: String,
+    // One of `FRAME_RATES`, or any other number from editing the file.
+    pub frame_rate: u32,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            tab: 0,
+            scale: 100,
+            theme: "Dark".to_string(),
+            tooltip_delay: 500,
+            language: "English".to_string(),
+            frame_rate: 60,
+        }
+    }
+}
+
+fn settings_path() -> Option<PathBuf> {
+    Some(whisper_directory()?.join("settings.json"))
+}
+
+impl Settings {
+    pub fn load() -> Self {
+        settings_path()
+            .and_then(|path| fs::read(path).ok())
+            .and_then(|data| serde_json::from_slice(&data).ok())
+            .unwrap_or_default()
+    }
+
+    // How many times its own size the editor is drawn.  A scale that isn't
+    // one of the choices, from editing the file by hand, is left at 100%.
+    pub fn scale(&self) -> f64 {
+        if SCALES.contains(&self.scale) {
+            f64::from(self.scale) / 100.0
+        } else {
+            1.0
+        }
+    }
+
+    pub fn save(&self) {
+        if let (Some(path), Ok(data)) = (settings_path(), serde_json::to_vec_pretty(self)) {
+            if let Some(directory) = path.parent() {
+                let _ = fs::create_dir_all(directory);
+            }
+            let _ = fs::write(path, data);
+        }
+    }
+}
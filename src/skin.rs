@@ -0,0 +1,70 @@
+// A skin: pictures for the editor to be drawn with instead of its own
+// shapes.  It's whichever of these are in the Skin folder next to the
+// presets:
+//
+// - background.png, stretched over the whole window, behind everything.
+// - knob.png, a film strip of the knob turned from all the way down to all
+//   the way up, one square frame under another.
+//
+// Anything that's missing, or doesn't load, is drawn the usual way, in the
+// colours of the theme.
+
+use crate::preset::whisper_directory;
+use crate::widgets::FilmStrip;
+use conrod_core::image::{Id, Map};
+use glium::texture::{RawImage2d, Texture2d};
+use std::path::Path;
+
+#[derive(Default)]
+pub struct Skin {
+    pub background: Option<Id>,
+    pub knob: Option<FilmStrip>,
+}
+
+impl Skin {
+    pub fn load(display: &glium::Display, image_map: &mut Map<Texture2d>) -> Self {
+        let directory = match whisper_directory() {
+            Some(directory) => directory.join("Skin"),
+            None => return Self::default(),
+        };
+        let mut load = |name: &str| {
+            let texture = load_texture(display, &directory.join(name))?;
+            let size = (texture.get_width(), texture.get_height().unwrap_or(1));
+            Some((image_map.insert(texture), size))
+        };
+        Self {
+            background: load("background.png").map(|(image, _)| image),
+            // A strip that isn't at least a frame high can't be used.
+            knob: load("knob.png").and_then(|(image, (width, height))| {
+                let frames = (height / width.max(1)) as usize;
+                if frames > 0 {
+                    Some(FilmStrip {
+                        image,
+                        size: f64::from(width),
+                        frames,
+                    })
+                } else {
+                    None
+                }
+            }),
+        }
+    }
+}
+
+fn load_texture(display: &glium::Display, path: &Path) -> Option<Texture2d> {
+    texture(display, image::open(path).ok()?)
+}
+
+// The same for a picture that's built into the plugin.
+pub fn image_texture(display: &glium::Display, bytes: &[u8]) -> Option<Texture2d> {
+    texture(display, image::load_from_memory(bytes).ok()?)
+}
+
+// The image is turned upside down on the way in, because OpenGL counts up
+// from the bottom.
+fn texture(display: &glium::Display, picture: image::DynamicImage) -> Option<Texture2d> {
+    let picture = picture.to_rgba();
+    let dimensions = picture.dimensions();
+    let raw = RawImage2d::from_raw_rgba_reversed(&picture.into_raw(), dimensions);
+    Texture2d::new(display, raw).ok()
+}
@@ -0,0 +1,55 @@
+use crate::range::{Curve, Range};
+
+// A one-pole low-pass filter for parameter values.  Hosts and the GUI change
+// parameters in steps, once per buffer at best, and jumping straight to each
+// new value is audible as "zipper" noise.  Instead we glide towards it.
+pub struct Smoother {
+    value: f32,
+    coefficient: f32,
+}
+
+impl Smoother {
+    pub fn new(value: f32) -> Self {
+        Self {
+            value,
+            coefficient: 1.0,
+        }
+    }
+
+    // How long it takes to get most of the way (about 63%) to a new value.
+    pub fn set_time(&mut self, seconds: f32, sample_rate: f32) {
+        self.coefficient = one_pole_coefficient(seconds, sample_rate);
+    }
+
+    // Jump straight to a value, for when nothing is playing and gliding
+    // would only delay the change.
+    pub fn reset(&mut self, value: f32) {
+        self.value = value;
+    }
+
+    pub fn next(&mut self, target: f32) -> f32 {
+        self.value += (target - self.value) * self.coefficient;
+        self.value
+    }
+}
+
+// How much of the remaining distance a one-pole filter with a time constant
+// of `seconds` covers per sample.  Zero seconds means it jumps straight there.
+pub fn one_pole_coefficient(seconds: f32, sample_rate: f32) -> f32 {
+    if seconds > 0.0 {
+        1.0 - (-1.0 / (seconds * sample_rate)).exp()
+    } else {
+        1.0
+    }
+}
+
+// A smoothing time of 0 to 100 ms.
+pub const SMOOTHING_TIME: Range = Range {
+    min: 0.0,
+    max: 0.1,
+    curve: Curve::Linear,
+};
+
+pub fn smoothing_time(value: f32) -> f32 {
+    SMOOTHING_TIME.map(value)
+}
@@ -0,0 +1,58 @@
+// The audio thread's own copy of the parameter values, taken at the start of
+// every buffer so the whole buffer hears the same sound, however the host and
+// the GUI change them in the meantime.  The values are kept in the order of
+// the parameter table, and read by the shared parameter they're a copy of,
+// so the audio code still gets at them by name.
+
+use crate::WhisperParameters;
+use vst::util::AtomicFloat;
+
+pub struct Snapshot {
+    values: Vec<f32>,
+    // Where every parameter lives in the shared parameters, in order of
+    // that, and where its value is in `values`.
+    places: Vec<(usize, usize)>,
+}
+
+impl Snapshot {
+    // The parameters have to stay where they are for as long as the
+    // snapshot is used, which they do behind the `Arc` they're shared in.
+    pub fn new(params: &WhisperParameters) -> Self {
+        let mut places: Vec<_> = params
+            .defs
+            .iter()
+            .enumerate()
+            .map(|(index, def)| (address(def.value(params)), index))
+            .collect();
+        places.sort();
+        Self {
+            values: params.parameter_values(),
+            places,
+        }
+    }
+
+    // Copy the value of every parameter in.  Nothing is allocated, so this
+    // is fine to do on the audio thread.
+    pub fn take(&mut self, params: &WhisperParameters) {
+        for (value, def) in self.values.iter_mut().zip(params.defs.iter()) {
+            *value = def.value(params).get();
+        }
+    }
+
+    // What `parameter` was when the snapshot was taken.  Anything that
+    // isn't in the table is read as it is now.
+    pub fn get(&self, parameter: &AtomicFloat) -> f32 {
+        let address = address(parameter);
+        match self
+            .places
+            .binary_search_by_key(&address, |&(place, _)| place)
+        {
+            Ok(found) => self.values[self.places[found].1],
+            Err(_) => parameter.get(),
+        }
+    }
+}
+
+fn address(parameter: &AtomicFloat) -> usize {
+    parameter as *const AtomicFloat as usize
+}
@@ -0,0 +1,105 @@
+// How loud what we're playing is across the frequencies, for the GUI's
+// spectrum display.  It's worked out on the GUI thread, from the samples the
+// audio thread sends over, so the audio thread never has to do an FFT.
+//
+// The spectrum is shown in bands spaced evenly in octaves, the way we hear
+// pitch, each as loud as the loudest frequency in it.  Every band also has a
+// peak that holds the loudest it's been and falls back slowly.
+
+use rustfft::num_complex::Complex;
+use rustfft::{FFTplanner, FFT};
+use std::f32::consts::PI;
+use std::sync::Arc;
+
+// How many samples go into each FFT.  At 44.1kHz that's about 20Hz apart,
+// which is as close as the lowest bands are.
+const FFT_SIZE: usize = 2048;
+
+const BANDS: usize = 64;
+const LOWEST: f32 = 20.0;
+const HIGHEST: f32 = 20000.0;
+
+// The quietest level shown, in dB, and how far the peaks fall back every
+// time the spectrum is worked out.
+const FLOOR_DB: f32 = -90.0;
+const PEAK_FALL_DB: f32 = 0.5;
+
+pub struct Analyzer {
+    fft: Arc<dyn FFT<f32>>,
+    // A Hann window, so a frequency that doesn't fit the FFT exactly
+    // doesn't smear across the whole spectrum.
+    window: Vec<f32>,
+    input: Vec<Complex<f32>>,
+    output: Vec<Complex<f32>>,
+    // The level of every band and its peak, from 0.0 at `FLOOR_DB` to 1.0
+    // at full scale.
+    pub levels: Vec<f32>,
+    pub peaks: Vec<f32>,
+}
+
+impl Default for Analyzer {
+    fn default() -> Self {
+        let mut planner = FFTplanner::new(false);
+        Self {
+            fft: planner.plan_fft(FFT_SIZE),
+            window: (0..FFT_SIZE)
+                .map(|i| 0.5 - 0.5 * (2.0 * PI * i as f32 / FFT_SIZE as f32).cos())
+                .collect(),
+            input: vec![Complex::new(0.0, 0.0); FFT_SIZE],
+            output: vec![Complex::new(0.0, 0.0); FFT_SIZE],
+            levels: vec![0.0; BANDS],
+            peaks: vec![0.0; BANDS],
+        }
+    }
+}
+
+impl Analyzer {
+    // Whether the peaks have all fallen back to the levels, so there's
+    // nothing moving.
+    pub fn settled(&self) -> bool {
+        self.peaks
+            .iter()
+            .zip(&self.levels)
+            .all(|(peak, level)| peak <= level)
+    }
+
+    // Work out the spectrum of the newest `FFT_SIZE` of `samples`.  With
+    // fewer than that, it's left as it was.
+    pub fn analyze(&mut self, samples: &[f32], sample_rate: f32) {
+        if samples.len() < FFT_SIZE {
+            return;
+        }
+        let newest = &samples[samples.len() - FFT_SIZE..];
+        for ((input, &sample), &window) in self.input.iter_mut().zip(newest).zip(&self.window) {
+            *input = Complex::new(sample * window, 0.0);
+        }
+        self.fft.process(&mut self.input, &mut self.output);
+
+        let bin_width = sample_rate / FFT_SIZE as f32;
+        for band in 0..BANDS {
+            let first = ((band_start(band) / bin_width) as usize).max(1);
+            let last = ((band_start(band + 1) / bin_width) as usize)
+                .max(first + 1)
+                .min(FFT_SIZE / 2);
+            let magnitude = self
+                .output
+                .get(first..last)
+                .unwrap_or(&[])
+                .iter()
+                .map(|bin| bin.norm())
+                .fold(0.0, f32::max);
+            // A full scale sine comes out at a quarter of the FFT size,
+            // half of it lost to the window.
+            let gain = magnitude * 4.0 / FFT_SIZE as f32;
+            let db = 20.0 * gain.max(1e-9).log10();
+            let level = (1.0 - db / FLOOR_DB).max(0.0).min(1.0);
+            self.levels[band] = level;
+            self.peaks[band] = level.max(self.peaks[band] + PEAK_FALL_DB / FLOOR_DB);
+        }
+    }
+}
+
+// Where a band starts.  The one after the last ends at `HIGHEST`.
+fn band_start(band: usize) -> f32 {
+    LOWEST * (HIGHEST / LOWEST).powf(band as f32 / BANDS as f32)
+}
@@ -0,0 +1,444 @@
+// What we hand the host to save in its project, and get back when the
+// project is opened again.  Parameters alone can't hold everything, the
+// sampler's file path for one, so we save a chunk of our own.
+//
+// The chunk is JSON, with a version number in it.  Parameters are saved by
+// name rather than by where they are in the list, so adding parameters or
+// moving them around doesn't upset old projects: anything a chunk doesn't
+// have is left at its default, and anything we don't know is skipped, which
+// also means a project saved by a newer build still loads as much as it
+// can.  When a parameter is renamed, it goes in `RENAMED` so older chunks
+// still find it.
+//
+// Chunks from before there was a version are binary instead.  They start
+// with the tag "WSPR", then the number of parameters followed by each of
+// their values, then the sample path, the MIDI learn bindings as a count
+// followed by the controller and parameter number of each, the tuning
+// path, the name of the program, the programs in the bank, each with its
+// name and parameters like the ones above, and which of them is picked.
+// Every number is a little-endian 32-bit value, and every string is its
+// length followed by UTF-8.  The oldest chunks stop after the sample path,
+// and later ones after the bindings or the tuning path.  They never had
+// the A/B compare in them.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+const TAG: &[u8; 4] = b"WSPR";
+
+// The chunk format we write.  It goes up by one whenever something in it
+// changes in a way that needs migrating.
+const VERSION: u32 = 1;
+
+// Parameters that have been renamed: the version that renamed them, the old
+// name and the new one.
+const RENAMED: &[(u32, &str, &str)] = &[];
+
+// How many programs there are in the bank.
+pub const PROGRAM_COUNT: usize = 32;
+
+// One sound in the bank: a name, and the value of every parameter.
+#[derive(Clone)]
+pub struct Program {
+    pub name: String,
+    pub parameters: Vec<f32>,
+}
+
+pub struct State {
+    pub parameters: Vec<f32>,
+    pub sample_path: Option<String>,
+    // (controller, parameter)
+    pub bindings: Vec<(u8, i32)>,
+    pub tuning_path: Option<String>,
+    // The name of the program being played.
+    pub name: String,
+    // The whole bank and which program in it is picked, when the host saves
+    // everything.  Saving one preset leaves the bank empty.
+    pub programs: Vec<Program>,
+    pub program: usize,
+    // The A/B compare slot that isn't being played, if it's been used, and
+    // whether B is the one being played.  These go with the bank too.
+    pub other_slot: Option<Vec<f32>>,
+    pub on_b: bool,
+    // The parameters the GUI's XY pad moves, across and up.
+    pub xy_pad: Option<(i32, i32)>,
+}
+
+impl State {
+    // `names` are the names of all the parameters, in order.
+    pub fn encode(&self, names: &[String]) -> Vec<u8> {
+        serde_json::to_vec(&self.to_saved(names)).unwrap_or_default()
+    }
+
+    // Every parameter comes back, with `defaults` for the ones the chunk
+    // doesn't have.  Anything that doesn't look like a chunk we wrote gives
+    // `None`.
+    pub fn decode(data: &[u8], names: &[String], defaults: &[f32]) -> Option<Self> {
+        if data.starts_with(TAG) {
+            let mut state = decode_binary(data)?;
+            pad(&mut state.parameters, defaults);
+            for program in state.programs.iter_mut() {
+                pad(&mut program.parameters, defaults);
+            }
+            return Some(state);
+        }
+        let saved = serde_json::from_slice(data).ok()?;
+        Some(Self::from_saved(saved, names, defaults))
+    }
+
+    pub fn to_saved(&self, names: &[String]) -> Saved {
+        let named = |parameters: &[f32]| -> BTreeMap<String, f32> {
+            names
+                .iter()
+                .cloned()
+                .zip(parameters.iter().cloned())
+                .collect()
+        };
+        Saved {
+            version: VERSION,
+            parameters: named(&self.parameters),
+            sample_path: self.sample_path.clone(),
+            tuning_path: self.tuning_path.clone(),
+            bindings: self
+                .bindings
+                .iter()
+                .filter_map(|&(controller, parameter)| {
+                    Some((controller, names.get(parameter as usize)?.clone()))
+                })
+                .collect(),
+            name: self.name.clone(),
+            programs: self
+                .programs
+                .iter()
+                .map(|program| SavedProgram {
+                    name: program.name.clone(),
+                    parameters: named(&program.parameters),
+                })
+                .collect(),
+            program: self.program,
+            other_slot: self.other_slot.as_ref().map(|other| named(other)),
+            on_b: self.on_b,
+            xy_pad: self.xy_pad.and_then(|(x, y)| {
+                Some((
+                    names.get(x as usize)?.clone(),
+                    names.get(y as usize)?.clone(),
+                ))
+            }),
+        }
+    }
+
+    pub fn from_saved(mut saved: Saved, names: &[String], defaults: &[f32]) -> Self {
+        saved.migrate();
+        let values = |named: &BTreeMap<String, f32>| -> Vec<f32> {
+            names
+                .iter()
+                .zip(defaults.iter())
+                .map(|(name, &default)| named.get(name).cloned().unwrap_or(default))
+                .collect()
+        };
+        Self {
+            parameters: values(&saved.parameters),
+            sample_path: saved.sample_path,
+            bindings: saved
+                .bindings
+                .iter()
+                .filter_map(|(controller, name)| {
+                    let parameter = names.iter().position(|other| other == name)?;
+                    Some((*controller, parameter as i32))
+                })
+                .collect(),
+            tuning_path: saved.tuning_path,
+            name: saved.name,
+            programs: saved
+                .programs
+                .iter()
+                .take(PROGRAM_COUNT)
+                .map(|program| Program {
+                    name: program.name.clone(),
+                    parameters: values(&program.parameters),
+                })
+                .collect(),
+            program: saved.program,
+            other_slot: saved.other_slot.as_ref().map(values),
+            on_b: saved.on_b,
+            xy_pad: saved.xy_pad.as_ref().and_then(|(x, y)| {
+                let index = |name| names.iter().position(|other| other == name);
+                Some((index(x)? as i32, index(y)? as i32))
+            }),
+        }
+    }
+}
+
+// What actually goes in the chunk, and in preset files.  Everything has a
+// default, so fields added in later versions can be missing from older
+// chunks.
+#[derive(Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Saved {
+    version: u32,
+    parameters: BTreeMap<String, f32>,
+    sample_path: Option<String>,
+    tuning_path: Option<String>,
+    // Controllers and the names of the parameters they're bound to.
+    bindings: Vec<(u8, String)>,
+    name: String,
+    programs: Vec<SavedProgram>,
+    program: usize,
+    other_slot: Option<BTreeMap<String, f32>>,
+    on_b: bool,
+    xy_pad: Option<(String, String)>,
+}
+
+#[derive(Default, Serialize, Deserialize)]
+#[serde(default)]
+struct SavedProgram {
+    name: String,
+    parameters: BTreeMap<String, f32>,
+}
+
+impl Saved {
+    // Bring a chunk from an older version up to date.
+    fn migrate(&mut self) {
+        for &(version, old, new) in RENAMED {
+            if self.version >= version {
+                continue;
+            }
+            rename(&mut self.parameters, old, new);
+            for program in self.programs.iter_mut() {
+                rename(&mut program.parameters, old, new);
+            }
+            if let Some(other) = self.other_slot.as_mut() {
+                rename(other, old, new);
+            }
+            let names = self.bindings.iter_mut().map(|(_, name)| name);
+            let xy_pad = self.xy_pad.iter_mut().flat_map(|(x, y)| vec![x, y]);
+            for name in names.chain(xy_pad) {
+                if name == old {
+                    *name = new.to_string();
+                }
+            }
+        }
+        self.version = self.version.max(VERSION);
+    }
+}
+
+fn rename(parameters: &mut BTreeMap<String, f32>, old: &str, new: &str) {
+    if let Some(value) = parameters.remove(old) {
+        parameters.insert(new.to_string(), value);
+    }
+}
+
+// Binary chunks only ever had parameters added on the end, so the ones
+// missing from a short list are the newest.
+fn pad(parameters: &mut Vec<f32>, defaults: &[f32]) {
+    if parameters.len() < defaults.len() {
+        parameters.extend_from_slice(&defaults[parameters.len()..]);
+    }
+}
+
+fn decode_binary(data: &[u8]) -> Option<State> {
+    if data.get(0..4)? != TAG {
+        return None;
+    }
+    let mut reader = Reader { data, position: 4 };
+
+    let parameters = reader.parameters()?;
+
+    let sample_path = reader.path()?;
+
+    let mut bindings = Vec::new();
+    if !reader.is_empty() {
+        let count = reader.u32()? as usize;
+        for _ in 0..count.min(128) {
+            let controller = reader.u32()? as u8;
+            bindings.push((controller, reader.u32()? as i32));
+        }
+    }
+
+    let tuning_path = if reader.is_empty() {
+        None
+    } else {
+        reader.path()?
+    };
+
+    let mut name = String::new();
+    let mut programs = Vec::new();
+    let mut program = 0;
+    if !reader.is_empty() {
+        name = reader.string()?;
+        let count = reader.u32()? as usize;
+        for _ in 0..count.min(PROGRAM_COUNT) {
+            programs.push(Program {
+                name: reader.string()?,
+                parameters: reader.parameters()?,
+            });
+        }
+        program = reader.u32()? as usize;
+    }
+
+    Some(State {
+        parameters,
+        sample_path,
+        bindings,
+        tuning_path,
+        name,
+        programs,
+        program,
+        other_slot: None,
+        on_b: false,
+        xy_pad: None,
+    })
+}
+
+struct Reader<'a> {
+    data: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn is_empty(&self) -> bool {
+        self.position >= self.data.len()
+    }
+
+    fn bytes(&mut self, length: usize) -> Option<&'a [u8]> {
+        let bytes = self
+            .data
+            .get(self.position..self.position.checked_add(length)?)?;
+        self.position += length;
+        Some(bytes)
+    }
+
+    fn u32(&mut self) -> Option<u32> {
+        let bytes = self.bytes(4)?;
+        Some(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+
+    // `None` if it's cut short or isn't UTF-8.
+    fn string(&mut self) -> Option<String> {
+        let length = self.u32()? as usize;
+        String::from_utf8(self.bytes(length)?.to_vec()).ok()
+    }
+
+    // The same as a string, but with nothing meaning no path.
+    fn path(&mut self) -> Option<Option<String>> {
+        let path = self.string()?;
+        if path.is_empty() {
+            Some(None)
+        } else {
+            Some(Some(path))
+        }
+    }
+
+    fn parameters(&mut self) -> Option<Vec<f32>> {
+        let count = self.u32()? as usize;
+        let mut parameters = Vec::with_capacity(count.min(1024));
+        for _ in 0..count {
+            parameters.push(f32::from_bits(self.u32()?));
+        }
+        Some(parameters)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DEFAULTS: [f32; 3] = [0.8, 1.0, 0.0];
+
+    fn names() -> Vec<String> {
+        vec![
+            "volume".to_string(),
+            "cutoff".to_string(),
+            "resonance".to_string(),
+        ]
+    }
+
+    fn push_u32(data: &mut Vec<u8>, value: u32) {
+        data.extend_from_slice(&value.to_le_bytes());
+    }
+
+    fn push_string(data: &mut Vec<u8>, text: &str) {
+        push_u32(data, text.len() as u32);
+        data.extend_from_slice(text.as_bytes());
+    }
+
+    fn push_parameters(data: &mut Vec<u8>, parameters: &[f32]) {
+        push_u32(data, parameters.len() as u32);
+        for parameter in parameters {
+            push_u32(data, parameter.to_bits());
+        }
+    }
+
+    // A chunk the way the binary format's last version wrote them, from
+    // before the third parameter was added.
+    fn legacy_chunk() -> Vec<u8> {
+        let mut data = TAG.to_vec();
+        push_parameters(&mut data, &[0.5, 0.25]);
+        push_string(&mut data, "kick.wav");
+        push_u32(&mut data, 1);
+        push_u32(&mut data, 74);
+        push_u32(&mut data, 1);
+        push_string(&mut data, "");
+        push_string(&mut data, "Lead");
+        push_u32(&mut data, 2);
+        push_string(&mut data, "Lead");
+        push_parameters(&mut data, &[0.5, 0.25]);
+        push_string(&mut data, "Bass");
+        push_parameters(&mut data, &[0.1, 0.2]);
+        push_u32(&mut data, 1);
+        data
+    }
+
+    fn assert_legacy(state: &State) {
+        assert_eq!(state.parameters, vec![0.5, 0.25, 0.0]);
+        assert_eq!(state.sample_path, Some("kick.wav".to_string()));
+        assert_eq!(state.bindings, vec![(74, 1)]);
+        assert_eq!(state.tuning_path, None);
+        assert_eq!(state.name, "Lead");
+        assert_eq!(state.programs.len(), 2);
+        assert_eq!(state.programs[1].name, "Bass");
+        assert_eq!(state.programs[1].parameters, vec![0.1, 0.2, 0.0]);
+        assert_eq!(state.program, 1);
+        assert_eq!(state.other_slot, None);
+    }
+
+    #[test]
+    fn legacy_binary() {
+        let state = State::decode(&legacy_chunk(), &names(), &DEFAULTS).unwrap();
+        assert_legacy(&state);
+    }
+
+    #[test]
+    fn oldest_binary() {
+        let mut data = TAG.to_vec();
+        push_parameters(&mut data, &[0.5]);
+        push_string(&mut data, "");
+        let state = State::decode(&data, &names(), &DEFAULTS).unwrap();
+        assert_eq!(state.parameters, vec![0.5, 1.0, 0.0]);
+        assert_eq!(state.sample_path, None);
+        assert!(state.bindings.is_empty() && state.programs.is_empty());
+        // Cut short in the middle of something is no chunk at all.
+        assert!(State::decode(&data[..data.len() - 2], &names(), &DEFAULTS).is_none());
+    }
+
+    // Saving a legacy chunk again writes JSON, which reads back the same.
+    #[test]
+    fn legacy_to_json() {
+        let names = names();
+        let legacy = State::decode(&legacy_chunk(), &names, &DEFAULTS).unwrap();
+        let json = legacy.encode(&names);
+        assert!(json.starts_with(b"{"));
+        let state = State::decode(&json, &names, &DEFAULTS).unwrap();
+        assert_legacy(&state);
+    }
+
+    // Parameters are found by name, so a chunk from a build with them in
+    // another order, or with ones we don't have, still loads.
+    #[test]
+    fn json_by_name() {
+        let json = br#"{"version":1,"parameters":{"cutoff":0.5,"unknown":1.0,"volume":0.25},"bindings":[[1,"cutoff"],[2,"unknown"]]}"#;
+        let state = State::decode(json, &names(), &DEFAULTS).unwrap();
+        assert_eq!(state.parameters, vec![0.25, 0.5, 0.0]);
+        assert_eq!(state.bindings, vec![(1, 1)]);
+    }
+}
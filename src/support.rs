@@ -1,6 +1,14 @@
 #![allow(dead_code)]
 
-pub struct GliumDisplayWinitWrapper(pub glium::Display);
+use conrod_core::event::Input;
+use conrod_core::input::Motion;
+
+// The display, and how many times bigger than its own size the editor is
+// drawn, for the UI scale setting.  Conrod lays everything out at the
+// window's size divided by that, so drawing it as though the screen had
+// that many more pixels to the point makes all of it bigger together,
+// text included.
+pub struct GliumDisplayWinitWrapper(pub glium::Display, pub f64);
 
 impl conrod_winit::WinitWindow for GliumDisplayWinitWrapper {
     fn get_inner_size(&self) -> Option<(u32, u32)> {
@@ -11,6 +19,37 @@ impl conrod_winit::WinitWindow for GliumDisplayWinitWrapper {
     }
 }
 
+impl conrod_glium::Display for GliumDisplayWinitWrapper {
+    fn opengl_version(&self) -> &glium::Version {
+        self.0.get_opengl_version()
+    }
+    fn framebuffer_dimensions(&self) -> (u32, u32) {
+        self.0.get_framebuffer_dimensions()
+    }
+    // The monitor's own scale factor, which is asked every time it's drawn,
+    // so it's right again as soon as the window moves to another monitor.
+    fn hidpi_factor(&self) -> f64 {
+        self.0.gl_window().window().hidpi_factor() * self.1
+    }
+}
+
+// Input comes in the window's own units, from its middle, so it only has
+// to be scaled down the same way to line up with the widgets.
+pub fn scale_input(input: Input, scale: f64) -> Input {
+    match input {
+        Input::Motion(Motion::MouseCursor { x, y }) => Input::Motion(Motion::MouseCursor {
+            x: x / scale,
+            y: y / scale,
+        }),
+        Input::Motion(Motion::Scroll { x, y }) => Input::Motion(Motion::Scroll {
+            x: x / scale,
+            y: y / scale,
+        }),
+        Input::Resize(width, height) => Input::Resize(width / scale, height / scale),
+        input => input,
+    }
+}
+
 // Conversion functions for converting between types from glium's version of `winit` and
 // `conrod_core`.
 conrod_winit::conversion_fns!();
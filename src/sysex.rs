@@ -0,0 +1,111 @@
+// A tiny system exclusive protocol, so hardware-style librarians can back
+// up the whole state of the synth and send it back later.  Every message
+// looks like
+//
+//     F0 7D 57 53 <command> <data...> F7
+//
+// 7D is the manufacturer ID set aside for non-commercial use, and "WS"
+// tells our messages apart from anything else that uses it.  Command 01
+// asks for a dump and has no data.  Command 02 is a dump, either our reply
+// or one being sent back to restore, and its data is the same chunk the
+// host saves in its projects.
+
+const HEADER: [u8; 3] = [0x7d, b'W', b'S'];
+const DUMP_REQUEST: u8 = 0x01;
+const DUMP: u8 = 0x02;
+
+pub enum SysExCommand {
+    DumpRequest,
+    // The state chunk the dump carries, already unpacked.
+    Dump(Vec<u8>),
+}
+
+impl SysExCommand {
+    // `message` is what's between the F0 and F7.  Other people's messages
+    // give `None`.
+    pub fn parse(message: &[u8]) -> Option<Self> {
+        if message.get(0..HEADER.len())? != HEADER {
+            return None;
+        }
+        let command = *message.get(HEADER.len())?;
+        let data = &message[HEADER.len() + 1..];
+        match command {
+            DUMP_REQUEST => Some(SysExCommand::DumpRequest),
+            DUMP => Some(SysExCommand::Dump(unpack(data))),
+            _ => None,
+        }
+    }
+}
+
+// A complete dump message, F0 to F7, for a state chunk.
+pub fn dump(chunk: &[u8]) -> Vec<u8> {
+    let mut message = vec![0xf0];
+    message.extend_from_slice(&HEADER);
+    message.push(DUMP);
+    pack(chunk, &mut message);
+    message.push(0xf7);
+    message
+}
+
+// Only seven bits of every byte can be sent in SysEx, so the chunk goes in
+// groups of seven bytes: first a byte holding the top bit of each, then the
+// seven bytes with their top bits taken off.
+fn pack(data: &[u8], output: &mut Vec<u8>) {
+    for group in data.chunks(7) {
+        let top_bits = group
+            .iter()
+            .enumerate()
+            .fold(0, |bits, (i, byte)| bits | ((byte >> 7) << i));
+        output.push(top_bits);
+        output.extend(group.iter().map(|byte| byte & 0x7f));
+    }
+}
+
+fn unpack(data: &[u8]) -> Vec<u8> {
+    let mut output = Vec::with_capacity(data.len() * 7 / 8);
+    for group in data.chunks(8) {
+        let top_bits = group[0];
+        for (i, byte) in group[1..].iter().enumerate() {
+            output.push((byte & 0x7f) | (((top_bits >> i) & 1) << 7));
+        }
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pack_round_trip() {
+        // Every byte there is, and a length that doesn't fill the last group.
+        let data: Vec<u8> = (0..=255).chain(0..10).collect();
+        let mut packed = Vec::new();
+        pack(&data, &mut packed);
+        assert!(packed.iter().all(|&byte| byte < 0x80));
+        assert_eq!(unpack(&packed), data);
+    }
+
+    #[test]
+    fn dump_round_trip() {
+        let chunk = b"{\"version\":1}\xff\x80".to_vec();
+        let message = dump(&chunk);
+        assert_eq!(message.first(), Some(&0xf0));
+        assert_eq!(message.last(), Some(&0xf7));
+        match SysExCommand::parse(&message[1..message.len() - 1]) {
+            Some(SysExCommand::Dump(unpacked)) => assert_eq!(unpacked, chunk),
+            _ => panic!("a dump should parse as one"),
+        }
+    }
+
+    #[test]
+    fn requests_and_other_messages() {
+        match SysExCommand::parse(&[0x7d, b'W', b'S', DUMP_REQUEST]) {
+            Some(SysExCommand::DumpRequest) => (),
+            _ => panic!("a dump request should parse as one"),
+        }
+        assert!(SysExCommand::parse(&[0x7d, b'X', b'Y', DUMP_REQUEST]).is_none());
+        assert!(SysExCommand::parse(&[0x7d, b'W', b'S', 0x7f]).is_none());
+        assert!(SysExCommand::parse(&[0x7d, b'W']).is_none());
+    }
+}
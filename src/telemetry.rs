@@ -0,0 +1,64 @@
+// What the audio thread measured over a buffer, sent over to the GUI so it
+// can show what's playing without ever reaching into the audio thread's
+// state itself.
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Telemetry {
+    // The loudest sample and the RMS level of each channel, as gains.
+    pub peak: [f32; 2],
+    pub rms: [f32; 2],
+    // How many voices are sounding.
+    pub voices: usize,
+    // Where the amp and filter envelopes of the newest voice are, from 0.0
+    // to 1.0.
+    pub envelope: f32,
+    pub filter_envelope: f32,
+    // The sequencer's step, while it's playing.
+    pub step: Option<usize>,
+    // Whether any MIDI came in.
+    pub midi: bool,
+    // How long rendering the buffer took, as a share of how long it lasts
+    // when it's played: 1.0 is as much time as there is.
+    pub load: f32,
+}
+
+impl Telemetry {
+    // The levels of a rendered buffer, with everything else left at zero.
+    pub fn measure(left: &[f32], right: &[f32]) -> Self {
+        let mut telemetry = Self::default();
+        for (channel, samples) in [left, right].iter().enumerate() {
+            let mut sum = 0.0;
+            for &sample in samples.iter() {
+                telemetry.peak[channel] = telemetry.peak[channel].max(sample.abs());
+                sum += sample * sample;
+            }
+            if !samples.is_empty() {
+                telemetry.rms[channel] = (sum / samples.len() as f32).sqrt();
+            }
+        }
+        telemetry
+    }
+
+    // Nothing playing and nothing coming in, which looks the same from one
+    // buffer to the next.
+    pub fn is_silent(&self) -> bool {
+        self.peak == [0.0; 2] && self.voices == 0 && !self.midi
+    }
+
+    // Fold in the readings from a later buffer.  The peaks stay at the
+    // loudest of them, so none are missed between two frames of the GUI,
+    // and everything else is whatever it was last.
+    pub fn merge(&mut self, later: &Telemetry) {
+        for channel in 0..2 {
+            self.peak[channel] = self.peak[channel].max(later.peak[channel]);
+        }
+        self.rms = later.rms;
+        self.voices = later.voices;
+        self.envelope = later.envelope;
+        self.filter_envelope = later.filter_envelope;
+        self.step = later.step;
+        // And the same goes for the load, to show the spikes.
+        self.midi |= later.midi;
+        self.load = self.load.max(later.load);
+    }
+}
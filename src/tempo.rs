@@ -0,0 +1,141 @@
+use vst::api::TimeInfoFlags;
+use vst::host::Host;
+use vst::plugin::HostCallback;
+
+// What we assume when the host doesn't tell us its tempo.
+pub const DEFAULT_TEMPO: f64 = 120.0;
+
+// Note lengths that tempo-synced things can be set to.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Division {
+    Whole,
+    Half,
+    Quarter,
+    DottedQuarter,
+    QuarterTriplet,
+    Eighth,
+    DottedEighth,
+    EighthTriplet,
+    Sixteenth,
+    DottedSixteenth,
+    SixteenthTriplet,
+    ThirtySecond,
+}
+
+impl Division {
+    pub const ALL: [Division; 12] = [
+        Division::Whole,
+        Division::Half,
+        Division::Quarter,
+        Division::DottedQuarter,
+        Division::QuarterTriplet,
+        Division::Eighth,
+        Division::DottedEighth,
+        Division::EighthTriplet,
+        Division::Sixteenth,
+        Division::DottedSixteenth,
+        Division::SixteenthTriplet,
+        Division::ThirtySecond,
+    ];
+
+    pub fn from_parameter(value: f32) -> Self {
+        let index = (value * Self::ALL.len() as f32) as usize;
+        Self::ALL[index.min(Self::ALL.len() - 1)]
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Division::Whole => "1/1",
+            Division::Half => "1/2",
+            Division::Quarter => "1/4",
+            Division::DottedQuarter => "1/4.",
+            Division::QuarterTriplet => "1/4T",
+            Division::Eighth => "1/8",
+            Division::DottedEighth => "1/8.",
+            Division::EighthTriplet => "1/8T",
+            Division::Sixteenth => "1/16",
+            Division::DottedSixteenth => "1/16.",
+            Division::SixteenthTriplet => "1/16T",
+            Division::ThirtySecond => "1/32",
+        }
+    }
+
+    // The length of the division, counted in quarter notes.
+    pub fn beats(self) -> f64 {
+        match self {
+            Division::Whole => 4.0,
+            Division::Half => 2.0,
+            Division::Quarter => 1.0,
+            Division::DottedQuarter => 1.5,
+            Division::QuarterTriplet => 2.0 / 3.0,
+            Division::Eighth => 0.5,
+            Division::DottedEighth => 0.75,
+            Division::EighthTriplet => 1.0 / 3.0,
+            Division::Sixteenth => 0.25,
+            Division::DottedSixteenth => 0.375,
+            Division::SixteenthTriplet => 1.0 / 6.0,
+            Division::ThirtySecond => 0.125,
+        }
+    }
+
+    // How many times per second this division comes around at `tempo` BPM.
+    pub fn frequency(self, tempo: f64) -> f32 {
+        (tempo / 60.0 / self.beats()) as f32
+    }
+
+    // How long this division lasts at `tempo` BPM, in seconds.
+    pub fn seconds(self, tempo: f64) -> f32 {
+        (self.beats() * 60.0 / tempo) as f32
+    }
+}
+
+// What the host's transport was doing at the start of a buffer.
+#[derive(Clone, Copy, Debug)]
+pub struct Transport {
+    // In BPM.
+    pub tempo: f64,
+    // Where the playhead is, in quarter notes from the start of the song,
+    // if the host tells us.
+    pub position: Option<f64>,
+    pub playing: bool,
+}
+
+impl Default for Transport {
+    fn default() -> Self {
+        Self {
+            tempo: DEFAULT_TEMPO,
+            position: None,
+            playing: false,
+        }
+    }
+}
+
+impl Transport {
+    // How many beats go by every sample.
+    pub fn beats_per_sample(&self, sample_rate: f32) -> f64 {
+        self.tempo / 60.0 / f64::from(sample_rate)
+    }
+}
+
+// Ask the host for its tempo and where its playhead is.  Anything it
+// doesn't know is left at the default, and hosts with no time info at all
+// give `None`.
+pub fn host_transport(host: &HostCallback) -> Option<Transport> {
+    let mask = TimeInfoFlags::TEMPO_VALID.bits() | TimeInfoFlags::PPQ_POS_VALID.bits();
+    let info = host.get_time_info(mask)?;
+    let valid = |flag: TimeInfoFlags| info.flags & flag.bits() != 0;
+
+    Some(Transport {
+        tempo: if valid(TimeInfoFlags::TEMPO_VALID) && info.tempo > 0.0 {
+            info.tempo
+        } else {
+            DEFAULT_TEMPO
+        },
+        position: if valid(TimeInfoFlags::PPQ_POS_VALID) {
+            Some(info.ppq_pos)
+        } else {
+            None
+        },
+        playing: valid(TimeInfoFlags::TRANSPORT_PLAYING),
+    })
+}
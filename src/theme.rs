@@ -0,0 +1,78 @@
+// How the editor looks: the colours of everything in it and the sizes of
+// its text.  Conrod's own widgets get it through the `Ui`'s theme, for
+// whatever they're not given colours for, and our widgets take their
+// backgrounds and outlines from there too.  Which one is used goes in the
+// user's settings, by name.
+
+use conrod_core::{color, Color, FontSize};
+
+#[derive(Clone, Copy)]
+pub struct Theme {
+    pub name: &'static str,
+    // Behind everything.
+    pub background: Color,
+    // Behind the scope, the meters and the rest, and the knobs' middles.
+    pub panel: Color,
+    pub button: Color,
+    // The knobs and everything else that's the colour of a parameter,
+    // and whatever's picked, like the page that's showing.
+    pub accent: Color,
+    // What's heard: the scope and the spectrum.
+    pub signal: Color,
+    pub text: Color,
+    pub font_size: FontSize,
+    // For the knobs' readings and the lists.
+    pub small_font_size: FontSize,
+}
+
+pub const DARK: Theme = Theme {
+    name: "Dark",
+    background: color::BLACK,
+    panel: color::DARK_CHARCOAL,
+    button: Color::Rgba(0.3, 0.3, 0.32, 1.0),
+    accent: color::LIGHT_BLUE,
+    signal: color::LIGHT_GREEN,
+    text: color::WHITE,
+    font_size: 12,
+    small_font_size: 10,
+};
+
+pub const LIGHT: Theme = Theme {
+    name: "Light",
+    background: Color::Rgba(0.9, 0.9, 0.9, 1.0),
+    panel: color::WHITE,
+    button: Color::Rgba(0.78, 0.78, 0.8, 1.0),
+    accent: color::BLUE,
+    signal: color::DARK_GREEN,
+    text: color::BLACK,
+    font_size: 12,
+    small_font_size: 10,
+};
+
+pub const THEMES: [Theme; 2] = [DARK, LIGHT];
+
+impl Theme {
+    // A theme that isn't there any more is the first one.
+    pub fn named(name: &str) -> Self {
+        THEMES
+            .iter()
+            .find(|theme| theme.name == name)
+            .cloned()
+            .unwrap_or(THEMES[0])
+    }
+
+    // What conrod's widgets go by, and ours for their backgrounds, tracks
+    // and handles.
+    pub fn conrod(&self) -> conrod_core::Theme {
+        conrod_core::Theme {
+            name: self.name.to_string(),
+            background_color: self.panel,
+            shape_color: self.button,
+            label_color: self.text,
+            font_size_large: self.font_size + 4,
+            font_size_medium: self.font_size,
+            font_size_small: self.small_font_size,
+            ..conrod_core::Theme::default()
+        }
+    }
+}
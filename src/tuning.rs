@@ -0,0 +1,337 @@
+// Tunings other than the usual twelve equal steps to the octave, loaded
+// from the files other synths use for them.  Two formats are understood:
+//
+// Scala (.scl) files list the pitches of one octave, or whatever the scale
+// repeats at, and an optional keyboard mapping (.kbm) file says which key
+// plays which of them.  The mapping is looked for next to the scale, with
+// the same name.  When there isn't one, the scale starts on middle C at its
+// usual pitch and goes up one step per key.
+// http://www.huygens-fokker.org/scala/scl_format.html
+// http://www.huygens-fokker.org/scala/help.htm#mappings
+//
+// AnaMark (.tun) files give the pitch of every key directly, in cents above
+// a base frequency.
+// https://www.mark-henning.de/files/am/Tuning_File_V2_Doc.pdf
+
+use crate::oscillator::midi_pitch_to_freq;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+pub struct Tuning {
+    // Keys the mapping leaves out don't play at all.
+    frequencies: [Option<f32>; 128],
+}
+
+#[derive(Debug)]
+pub enum TuningError {
+    Io(io::Error),
+    Invalid(&'static str),
+}
+
+impl fmt::Display for TuningError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TuningError::Io(error) => write!(f, "{}", error),
+            TuningError::Invalid(error) => write!(f, "{}", error),
+        }
+    }
+}
+
+impl From<io::Error> for TuningError {
+    fn from(error: io::Error) -> Self {
+        TuningError::Io(error)
+    }
+}
+
+impl Tuning {
+    // Load a Scala or AnaMark file, going by its extension.
+    pub fn load(path: &Path) -> Result<Self, TuningError> {
+        let extension = path
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .unwrap_or("")
+            .to_lowercase();
+        match extension.as_str() {
+            "scl" => {
+                let scale = read_scl(&fs::read_to_string(path)?)?;
+                let mapping = match fs::read_to_string(path.with_extension("kbm")) {
+                    Ok(text) => read_kbm(&text)?,
+                    Err(_) => Mapping::linear(scale.len()),
+                };
+                mapping.tune(&scale)
+            }
+            "tun" => read_tun(&fs::read_to_string(path)?),
+            _ => Err(TuningError::Invalid(
+                "only .scl and .tun files can be loaded",
+            )),
+        }
+    }
+
+    pub fn frequency(&self, note: u8) -> Option<f32> {
+        self.frequencies[usize::from(note & 0x7f)]
+    }
+}
+
+// Cents above the root of every step in a Scala scale, past the root
+// itself, with the last one being where the scale repeats.
+fn read_scl(text: &str) -> Result<Vec<f64>, TuningError> {
+    // The first line is a description, which can be empty, so it's the
+    // only one that isn't skipped when there's nothing in it.
+    let mut lines = text.lines().filter(|line| !line.starts_with('!'));
+    lines.next();
+    let mut values = lines.filter_map(|line| line.split_whitespace().next());
+
+    let count: usize =
+        values
+            .next()
+            .and_then(|count| count.parse().ok())
+            .ok_or(TuningError::Invalid(
+                "the scale doesn't say how many notes it has",
+            ))?;
+    let scale = values
+        .take(count)
+        .map(|pitch| {
+            parse_pitch(pitch).ok_or(TuningError::Invalid("a pitch in the scale can't be read"))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    if scale.is_empty() || scale.len() < count {
+        return Err(TuningError::Invalid(
+            "the scale has fewer notes than it says",
+        ));
+    }
+    Ok(scale)
+}
+
+// Pitches with a decimal point are in cents, and anything else is a ratio,
+// like "3/2" or "2".
+fn parse_pitch(pitch: &str) -> Option<f64> {
+    if pitch.contains('.') {
+        return pitch.parse().ok();
+    }
+    let mut parts = pitch.splitn(2, '/');
+    let numerator: f64 = parts.next()?.parse().ok()?;
+    let denominator: f64 = parts.next().map_or(Some(1.0), |part| part.parse().ok())?;
+    if numerator <= 0.0 || denominator <= 0.0 {
+        return None;
+    }
+    Some(1200.0 * (numerator / denominator).log2())
+}
+
+// Which scale step each key plays, from a Scala keyboard mapping.
+struct Mapping {
+    first: i32,
+    last: i32,
+    // The key the scale's root is on.
+    middle: i32,
+    // The key tuned to `frequency`.
+    reference: i32,
+    frequency: f64,
+    // The step the mapping repeats at.
+    octave: i32,
+    // `None` for keys that are left out.  An empty map means every key is
+    // the next step up.
+    keys: Vec<Option<i32>>,
+}
+
+impl Mapping {
+    fn linear(steps: usize) -> Self {
+        Self {
+            first: 0,
+            last: 127,
+            middle: 60,
+            reference: 60,
+            frequency: f64::from(midi_pitch_to_freq(60)),
+            octave: steps as i32,
+            keys: Vec::new(),
+        }
+    }
+
+    // Which step of the scale a key plays, counting up from the root.
+    fn step(&self, key: i32) -> Option<i32> {
+        if key < self.first || key > self.last {
+            return None;
+        }
+        let offset = key - self.middle;
+        if self.keys.is_empty() {
+            return Some(offset);
+        }
+        let size = self.keys.len() as i32;
+        let step = self.keys[offset.rem_euclid(size) as usize]?;
+        Some(offset.div_euclid(size) * self.octave + step)
+    }
+
+    fn tune(&self, scale: &[f64]) -> Result<Tuning, TuningError> {
+        let reference = self
+            .step(self.reference)
+            .ok_or(TuningError::Invalid("the reference key isn't mapped"))?;
+        let reference = cents(scale, reference);
+        let mut frequencies = [None; 128];
+        for (key, frequency) in frequencies.iter_mut().enumerate() {
+            *frequency = self.step(key as i32).map(|step| {
+                (self.frequency * 2.0f64.powf((cents(scale, step) - reference) / 1200.0)) as f32
+            });
+        }
+        Ok(Tuning { frequencies })
+    }
+}
+
+// How far a step is above the scale's root, going round the scale as many
+// times as it takes.
+fn cents(scale: &[f64], step: i32) -> f64 {
+    let size = scale.len() as i32;
+    let period = scale[scale.len() - 1];
+    let degree = step.rem_euclid(size) as usize;
+    let within = if degree == 0 { 0.0 } else { scale[degree - 1] };
+    f64::from(step.div_euclid(size)) * period + within
+}
+
+fn read_kbm(text: &str) -> Result<Mapping, TuningError> {
+    let invalid = || TuningError::Invalid("the keyboard mapping can't be read");
+    let mut values = text
+        .lines()
+        .filter(|line| !line.starts_with('!'))
+        .filter_map(|line| line.split_whitespace().next());
+    let mut number = || values.next().ok_or_else(invalid);
+
+    let size: usize = number()?.parse().map_err(|_| invalid())?;
+    // A map repeats every `size` keys, so there's no use for one longer
+    // than the keyboard, and a silly size mustn't be allocated.
+    if size > 128 {
+        return Err(TuningError::Invalid("the keyboard mapping is too long"));
+    }
+    let mut key = || -> Result<i32, TuningError> { number()?.parse().map_err(|_| invalid()) };
+    let (first, last, middle, reference) = (key()?, key()?, key()?, key()?);
+    let frequency: f64 = number()?.parse().map_err(|_| invalid())?;
+    let octave = number()?.parse().map_err(|_| invalid())?;
+    // The map can end early, and the keys it doesn't reach are left out.
+    let keys = (0..size)
+        .map(|_| values.next().and_then(|step| step.parse().ok()))
+        .collect();
+
+    if frequency <= 0.0 {
+        return Err(invalid());
+    }
+    Ok(Mapping {
+        first,
+        last,
+        middle,
+        reference,
+        frequency,
+        octave,
+        keys,
+    })
+}
+
+fn read_tun(text: &str) -> Result<Tuning, TuningError> {
+    // Keys the file doesn't mention keep their usual pitch, which is 100
+    // cents a key above the base frequency of C-1.
+    let mut tuning = [None; 128];
+    let mut exact = [None; 128];
+    let mut base = f64::from(midi_pitch_to_freq(0));
+    let mut section = String::new();
+
+    for line in text.lines() {
+        let line = line.split(';').next().unwrap_or("").trim();
+        if line.starts_with('[') {
+            section = line.trim_matches(|c| c == '[' || c == ']').to_lowercase();
+            continue;
+        }
+        let mut parts = line.splitn(2, '=');
+        let (key, value) = match (parts.next(), parts.next()) {
+            (Some(key), Some(value)) => (key.trim().to_lowercase(), value.trim()),
+            _ => continue,
+        };
+        let value: f64 = match value.parse() {
+            Ok(value) => value,
+            Err(_) => continue,
+        };
+        let note = Some(key.as_str())
+            .filter(|key| key.starts_with("note"))
+            .and_then(|key| key[4..].trim().parse::<usize>().ok())
+            .filter(|&note| note < 128);
+        match (section.as_str(), note) {
+            ("tuning", Some(note)) => tuning[note] = Some(value),
+            ("exact tuning", Some(note)) => exact[note] = Some(value),
+            ("exact tuning", None) if key == "basefreq" && value > 0.0 => base = value,
+            _ => {}
+        }
+    }
+
+    if tuning.iter().chain(exact.iter()).all(Option::is_none) {
+        return Err(TuningError::Invalid("the file doesn't tune any keys"));
+    }
+    let mut frequencies = [None; 128];
+    for (note, frequency) in frequencies.iter_mut().enumerate() {
+        let cents = exact[note].or(tuning[note]).unwrap_or(note as f64 * 100.0);
+        *frequency = Some((base * 2.0f64.powf(cents / 1200.0)) as f32);
+    }
+    Ok(Tuning { frequencies })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(a: Option<f32>, b: f32) -> bool {
+        match a {
+            Some(a) => (a - b).abs() < b * 1e-4,
+            None => false,
+        }
+    }
+
+    #[test]
+    fn scl() {
+        let text = "! test.scl\n!\nA fifth and an octave\n 3\n!\n100.0\n3/2 the fifth\n2\n";
+        let scale = read_scl(text).unwrap();
+        assert_eq!(scale.len(), 3);
+        assert!((scale[0] - 100.0).abs() < 1e-9);
+        assert!((scale[1] - 701.955).abs() < 1e-3);
+        assert!((scale[2] - 1200.0).abs() < 1e-9);
+        assert!(read_scl("Short\n3\n100.0\n2\n").is_err());
+        assert!(read_scl("Bad\n1\nnot a pitch\n").is_err());
+    }
+
+    #[test]
+    fn equal_temperament() {
+        let text: String = (1..=12).map(|step| format!("{}.0\n", step * 100)).collect();
+        let scale = read_scl(&format!("Twelve\n12\n{}", text)).unwrap();
+        let tuning = Mapping::linear(scale.len()).tune(&scale).unwrap();
+        for &note in [0u8, 60, 69, 127].iter() {
+            assert!(close(tuning.frequency(note), midi_pitch_to_freq(note)));
+        }
+    }
+
+    #[test]
+    fn kbm() {
+        // Seven white keys to a twelve key octave, with the black keys left
+        // out, and A above middle C at 440Hz.
+        let text =
+            "! white.kbm\n12\n0\n127\n60\n69\n440.0\n7\n0\n x\n1\nx\n2\n3\nx\n4\nx\n5\nx\n6\n";
+        let mapping = read_kbm(text).unwrap();
+        assert_eq!(mapping.keys.len(), 12);
+        assert_eq!(mapping.step(60), Some(0));
+        assert_eq!(mapping.step(61), None);
+        assert_eq!(mapping.step(72), Some(7));
+        assert_eq!(mapping.step(71), Some(6));
+
+        assert!(read_kbm("129\n0\n127\n60\n69\n440.0\n12\n").is_err());
+        assert!(read_kbm("0\n0\n127\n60\n69\n0.0\n12\n").is_err());
+        assert!(read_kbm("12\n0\n127\n").is_err());
+    }
+
+    #[test]
+    fn tun() {
+        let text = "; A test\n[Tuning]\nnote 60 = 6050\n[Exact Tuning]\nBaseFreq = 8.1757989156\nnote 69 = 6900.0 ; A\n";
+        let tuning = read_tun(text).unwrap();
+        assert!(close(tuning.frequency(69), 440.0));
+        assert!(close(
+            tuning.frequency(60),
+            midi_pitch_to_freq(60) * 2.0f32.powf(50.0 / 1200.0)
+        ));
+        // The others keep their usual pitch.
+        assert!(close(tuning.frequency(72), midi_pitch_to_freq(72)));
+        assert!(read_tun("[Tuning]\n").is_err());
+    }
+}
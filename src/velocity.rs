@@ -0,0 +1,74 @@
+// How hard a key is struck turns into how loud the note is.  Keyboards
+// differ a lot in how hard you have to play to reach the top, so the
+// response can be bent to suit.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum VelocityCurve {
+    // Loud without much effort.
+    Soft,
+    Linear,
+    // Needs a firm touch to get loud.
+    Hard,
+    // Every note at full volume, however it was played.
+    Fixed,
+}
+
+impl VelocityCurve {
+    pub const ALL: [VelocityCurve; 4] = [
+        VelocityCurve::Soft,
+        VelocityCurve::Linear,
+        VelocityCurve::Hard,
+        VelocityCurve::Fixed,
+    ];
+
+    pub fn from_parameter(value: f32) -> Self {
+        let index = (value * Self::ALL.len() as f32) as usize;
+        Self::ALL[index.min(Self::ALL.len() - 1)]
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            VelocityCurve::Soft => "Soft",
+            VelocityCurve::Linear => "Linear",
+            VelocityCurve::Hard => "Hard",
+            VelocityCurve::Fixed => "Fixed",
+        }
+    }
+
+    // Turn a MIDI velocity into a gain from 0.0 to 1.0.
+    pub fn apply(self, velocity: u8) -> f32 {
+        let velocity = f32::from(velocity.min(127)) / 127.0;
+        match self {
+            VelocityCurve::Soft => velocity.sqrt(),
+            VelocityCurve::Linear => velocity,
+            VelocityCurve::Hard => velocity * velocity,
+            VelocityCurve::Fixed => 1.0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn curves() {
+        for &curve in VelocityCurve::ALL.iter() {
+            assert_eq!(curve.apply(127), 1.0);
+        }
+        assert_eq!(VelocityCurve::Fixed.apply(0), 1.0);
+        assert_eq!(VelocityCurve::Linear.apply(0), 0.0);
+        // Half way, soft is louder than linear and hard is quieter.
+        let linear = VelocityCurve::Linear.apply(64);
+        assert!(VelocityCurve::Soft.apply(64) > linear);
+        assert!(VelocityCurve::Hard.apply(64) < linear);
+        // Nothing goes over the top, whatever comes in.
+        assert_eq!(VelocityCurve::Soft.apply(255), 1.0);
+    }
+
+    #[test]
+    fn from_parameter() {
+        assert_eq!(VelocityCurve::from_parameter(0.0), VelocityCurve::Soft);
+        assert_eq!(VelocityCurve::from_parameter(0.3), VelocityCurve::Linear);
+        assert_eq!(VelocityCurve::from_parameter(1.0), VelocityCurve::Fixed);
+    }
+}
@@ -0,0 +1,774 @@
+use crate::drive::Drive;
+use crate::drum::{Drum, DrumKind, DrumSettings};
+use crate::envelope::{Envelope, EnvelopeSettings};
+use crate::filter::{cutoff_frequency, Filter, FilterMode};
+use crate::granular::{GrainCloud, GrainSettings};
+use crate::matrix::{ModAmounts, ModSlot, ModSources, MATRIX_SLOTS};
+use crate::mpe::Expression;
+use crate::noise::Noise;
+use crate::oscillator::{midi_pitch_to_freq, semitones_to_ratio, Oscillator, Waveform};
+use crate::pan::equal_power;
+use crate::range::{Curve, Range};
+use crate::sample::{Sample, SamplerSettings};
+use crate::tuning::Tuning;
+use rand::random;
+use std::f32::consts::SQRT_2;
+use std::mem;
+use std::sync::Arc;
+
+// The most notes we will ever play at once.  The polyphony parameter picks
+// how many of these are actually used.
+pub const MAX_VOICES: usize = 16;
+
+// The most detuned copies of its oscillator a single note can play.
+pub const MAX_UNISON: usize = 8;
+
+// How far humanizing can detune a note, in semitones either way.
+const HUMANIZE_DETUNE: f32 = 0.1;
+
+// What makes one note-on a little different from the last: a seed for all
+// the voice's randomness, and how far to stray, from 0.0 to 1.0.
+#[derive(Clone, Copy, Debug)]
+struct Humanize {
+    seed: u32,
+    amount: f32,
+}
+
+// How each unison copy is detuned and panned.  This only changes when the
+// parameters do, so it's worked out once per buffer rather than per sample.
+#[derive(Clone, Copy, Debug)]
+pub struct Unison {
+    pub voices: usize,
+    pitch: [f32; MAX_UNISON],
+    left: [f32; MAX_UNISON],
+    right: [f32; MAX_UNISON],
+}
+
+impl Unison {
+    // `detune` is how many semitones apart the outermost copies are, and
+    // `spread` how far towards the left and right they are panned.
+    pub fn new(voices: usize, detune: f32, spread: f32) -> Self {
+        let voices = voices.max(1).min(MAX_UNISON);
+        let mut unison = Self {
+            voices,
+            pitch: [1.0; MAX_UNISON],
+            left: [0.0; MAX_UNISON],
+            right: [0.0; MAX_UNISON],
+        };
+
+        // Keep the overall level about the same however many copies there are,
+        // with a single centred copy at exactly unity gain.
+        let level = SQRT_2 / (voices as f32).sqrt();
+        for i in 0..voices {
+            // Where this copy sits from -1.0 to 1.0.
+            let position = if voices == 1 {
+                0.0
+            } else {
+                i as f32 / (voices - 1) as f32 * 2.0 - 1.0
+            };
+            let (left, right) = equal_power(position * spread);
+            unison.pitch[i] = semitones_to_ratio(position * detune * 0.5);
+            unison.left[i] = left * level;
+            unison.right[i] = right * level;
+        }
+        unison
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum VoiceMode {
+    Poly,
+    // One voice, restarting its envelopes for every note.
+    Mono,
+    // One voice, only restarting its envelopes when no other key was held.
+    Legato,
+}
+
+impl VoiceMode {
+    pub const ALL: [VoiceMode; 3] = [VoiceMode::Poly, VoiceMode::Mono, VoiceMode::Legato];
+
+    pub fn from_parameter(value: f32) -> Self {
+        let index = (value * Self::ALL.len() as f32) as usize;
+        Self::ALL[index.min(Self::ALL.len() - 1)]
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            VoiceMode::Poly => "Poly",
+            VoiceMode::Mono => "Mono",
+            VoiceMode::Legato => "Legato",
+        }
+    }
+}
+
+// How a voice makes its sound before the filter.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SynthesisMode {
+    // The chosen waveform, shaped by the filter.
+    Subtractive,
+    // A sine carrier with its phase modulated by a second sine.
+    Fm,
+    // A sound loaded from disk, played back faster or slower for each note.
+    Sampler,
+    // A cloud of short grains of the sample, or of noise without one.
+    Granular,
+    // One-shot kicks, snares and hi-hats, picked by which note is played.
+    Drum,
+}
+
+impl SynthesisMode {
+    pub const ALL: [SynthesisMode; 5] = [
+        SynthesisMode::Subtractive,
+        SynthesisMode::Fm,
+        SynthesisMode::Sampler,
+        SynthesisMode::Granular,
+        SynthesisMode::Drum,
+    ];
+
+    pub fn from_parameter(value: f32) -> Self {
+        let index = (value * Self::ALL.len() as f32) as usize;
+        Self::ALL[index.min(Self::ALL.len() - 1)]
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            SynthesisMode::Subtractive => "Subtractive",
+            SynthesisMode::Fm => "FM",
+            SynthesisMode::Sampler => "Sampler",
+            SynthesisMode::Granular => "Granular",
+            SynthesisMode::Drum => "Drum",
+        }
+    }
+}
+
+// Everything a voice needs to know that's shared by all voices.
+pub struct VoiceSettings {
+    pub mode: SynthesisMode,
+    pub waveform: Waveform,
+    // The FM modulator's frequency as a multiple of the note's, and how
+    // far it moves the carrier's phase.
+    pub fm_ratio: f32,
+    pub fm_index: f32,
+    pub envelope: EnvelopeSettings,
+    pub filter_mode: FilterMode,
+    pub filter_envelope: EnvelopeSettings,
+    // How far the filter envelope moves the cutoff, from -1.0 to 1.0.
+    pub filter_envelope_amount: f32,
+    // The third envelope, which only goes to the modulation matrix.
+    pub aux_envelope: EnvelopeSettings,
+    // How much of the way to its new pitch a sliding voice gets each sample.
+    pub glide: f32,
+    pub unison: Unison,
+    pub sub_waveform: Waveform,
+    // How loud the sub-oscillator is mixed in, from 0.0 to 1.0.
+    pub sub_level: f32,
+    // How much of the oscillator is replaced by it multiplied with noise.
+    pub ring_mod: f32,
+    // What the sampler plays, if anything has been loaded.
+    pub sample: Option<Arc<Sample>>,
+    pub sampler: SamplerSettings,
+    pub grains: GrainSettings,
+    pub drum: DrumSettings,
+    pub matrix: [ModSlot; MATRIX_SLOTS],
+    // Set when the drive section runs on each voice instead of the mix.
+    pub drive: Option<Drive>,
+    pub sample_rate: f32,
+}
+
+// Modulation that changes from sample to sample and applies to every voice.
+#[derive(Clone, Copy, Debug)]
+pub struct Modulation {
+    // A ratio every voice's frequency is multiplied by.
+    pub pitch: f32,
+    // Cutoff is still on the 0.0 to 1.0 parameter scale here, so that
+    // modulation can be added to it before it's turned into Hz.
+    pub cutoff: f32,
+    pub resonance: f32,
+    // The modulation matrix sources that are the same for every voice.
+    pub sources: ModSources,
+}
+
+#[derive(Default)]
+pub struct Voice {
+    pub note: u8,
+    // Worked out once when the note starts rather than on every sample.
+    frequency: f32,
+    // How far the oscillator moves each sample, which depends on the sample
+    // rate, and how far it should be moving once it's done sliding.
+    increment: f32,
+    target: f32,
+    // How loud the key was struck, from 0.0 to 1.0, after the velocity curve.
+    velocity: f32,
+    // Pressure, from poly aftertouch or MPE, plus MPE's bend and timbre.
+    expression: Expression,
+    // Whether the key for this voice is still held down.
+    held: bool,
+    // When this voice was started, used to find the oldest one to steal.
+    started: u64,
+    oscillators: [Oscillator; MAX_UNISON],
+    // In FM mode, each unison copy gets its own modulator.
+    modulators: [Oscillator; MAX_UNISON],
+    // In sampler mode, where each unison copy is in the sample, in frames.
+    positions: [f64; MAX_UNISON],
+    // Set when the note (re)starts, so the sample is played from the top.
+    restart: bool,
+    grains: GrainCloud,
+    drum: Drum,
+    // Plays an octave below the note, in the middle of the stereo field.
+    sub: Oscillator,
+    envelope: Envelope,
+    // One filter for each side, since unison can make a voice stereo.
+    filters: [Filter; 2],
+    filter_envelope: Envelope,
+    aux_envelope: Envelope,
+    // For ring modulating with noise.
+    noise: Noise,
+}
+
+impl Voice {
+    // A drum hit keeps ringing after its key is let go.
+    pub fn is_active(&self) -> bool {
+        self.envelope.is_active() || self.drum.is_active()
+    }
+
+    fn start(
+        &mut self,
+        note: u8,
+        frequency: f32,
+        velocity: f32,
+        expression: Expression,
+        humanize: Humanize,
+        sample_rate: f32,
+    ) {
+        self.note = note;
+        self.frequency = frequency;
+        self.set_sample_rate(sample_rate);
+        self.increment = self.target;
+        self.velocity = velocity;
+        self.expression = expression;
+        self.held = true;
+
+        // Every bit of noise the voice makes comes from the note's seed, so
+        // the same seed always sounds the same.
+        let mut random = Noise::new(humanize.seed);
+        for oscillator in self.oscillators.iter_mut() {
+            oscillator.seed(random.next_u32());
+        }
+        self.grains.seed(random.next_u32());
+        self.drum.seed(random.next_u32());
+        self.noise.seed(random.next_u32());
+
+        // A voice coming back from silence shouldn't ring with whatever was
+        // left in the filter from its last note.  The unison copies start at
+        // random points in their cycle, or they would all be in phase and
+        // sound like one loud oscillator until they drift apart.  Everything
+        // else starts at the top of its cycle, unless it's being humanized.
+        if !self.is_active() {
+            for filter in self.filters.iter_mut() {
+                filter.reset();
+            }
+            for oscillator in self.oscillators.iter_mut() {
+                oscillator.set_phase(random.unit());
+            }
+            for modulator in self.modulators.iter_mut() {
+                modulator.set_phase(random.unit() * humanize.amount);
+            }
+            self.sub.set_phase(random.unit() * humanize.amount);
+            self.grains.reset();
+        }
+        self.retrigger();
+    }
+
+    // Move to another note without starting over, gliding there over
+    // the glide time.
+    fn slide_to(&mut self, note: u8, frequency: f32, sample_rate: f32) {
+        self.note = note;
+        self.frequency = frequency;
+        self.set_sample_rate(sample_rate);
+        self.held = true;
+    }
+
+    fn retrigger(&mut self) {
+        self.restart = true;
+        self.envelope.note_on();
+        self.filter_envelope.note_on();
+        self.aux_envelope.note_on();
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f32) {
+        self.target = self.frequency / sample_rate;
+    }
+
+    fn release(&mut self) {
+        self.held = false;
+        self.envelope.note_off();
+        self.filter_envelope.note_off();
+        self.aux_envelope.note_off();
+    }
+
+    // Go silent straight away, release and all.
+    fn kill(&mut self) {
+        self.held = false;
+        self.envelope.reset();
+        self.filter_envelope.reset();
+        self.aux_envelope.reset();
+        self.drum.stop();
+    }
+
+    // Render one stereo sample.
+    fn render(&mut self, settings: &VoiceSettings, modulation: &Modulation) -> (f32, f32) {
+        self.increment += (self.target - self.increment) * settings.glide;
+        let envelope = self.envelope.next(&settings.envelope);
+        // Drums have their own envelopes built in.
+        let amplitude = if settings.mode == SynthesisMode::Drum {
+            self.velocity
+        } else {
+            envelope * self.velocity
+        };
+        let filter_envelope = self.filter_envelope.next(&settings.filter_envelope);
+        let aux_envelope = self.aux_envelope.next(&settings.aux_envelope);
+
+        let sources = ModSources {
+            envelope2: filter_envelope,
+            envelope3: aux_envelope,
+            velocity: self.velocity,
+            poly_pressure: self.expression.pressure,
+            timbre: self.expression.timbre,
+            ..modulation.sources
+        };
+        let matrix = ModAmounts::new(&settings.matrix, &sources);
+        let amplitude = amplitude * matrix.gain();
+
+        let unison = &settings.unison;
+        let mut increment = self.increment * modulation.pitch * matrix.pitch_ratio();
+        if self.expression.bend != 0.0 {
+            increment *= semitones_to_ratio(self.expression.bend);
+        }
+        if self.restart {
+            self.restart = false;
+            let start = match &settings.sample {
+                Some(sample) => settings.sampler.region(sample).0,
+                None => 0.0,
+            };
+            self.positions = [start; MAX_UNISON];
+            if settings.mode == SynthesisMode::Drum {
+                self.drum.trigger(
+                    DrumKind::for_note(self.note),
+                    &settings.drum,
+                    settings.sample_rate,
+                );
+            }
+        }
+        // One noise sample for every copy, and none at all if it isn't used.
+        let noise = if settings.ring_mod > 0.0 {
+            self.noise.white()
+        } else {
+            0.0
+        };
+        let mut left = 0.0;
+        let mut right = 0.0;
+        if settings.mode == SynthesisMode::Granular {
+            // The cloud is its own kind of unison, so it's only played once.
+            let source = settings
+                .sample
+                .as_ref()
+                .map(|sample| (&**sample, &settings.sampler));
+            let step = match &settings.sample {
+                Some(sample) => settings.sampler.step(sample, increment),
+                None => 1.0,
+            };
+            let (l, r) = self
+                .grains
+                .next(&settings.grains, source, step, settings.sample_rate);
+            left += l;
+            right += r;
+        } else if settings.mode == SynthesisMode::Drum {
+            let sample = self.drum.next();
+            left += sample;
+            right += sample;
+        } else {
+            for (i, oscillator) in self.oscillators[..unison.voices].iter_mut().enumerate() {
+                let increment = increment * unison.pitch[i];
+                let (sample_left, sample_right) = match settings.mode {
+                    SynthesisMode::Subtractive => {
+                        let sample = oscillator.next(settings.waveform, increment);
+                        (sample, sample)
+                    }
+                    SynthesisMode::Fm => {
+                        let modulator =
+                            self.modulators[i].next(Waveform::Sine, increment * settings.fm_ratio);
+                        let sample = oscillator
+                            .next_phase_modulated(increment, modulator * settings.fm_index);
+                        (sample, sample)
+                    }
+                    SynthesisMode::Sampler => match &settings.sample {
+                        Some(sample) => {
+                            let step = settings.sampler.step(sample, increment);
+                            settings.sampler.play(sample, &mut self.positions[i], step)
+                        }
+                        None => (0.0, 0.0),
+                    },
+                    // These don't use unison, and are played above instead.
+                    SynthesisMode::Granular | SynthesisMode::Drum => (0.0, 0.0),
+                };
+                let ring = |sample: f32| sample + (sample * noise - sample) * settings.ring_mod;
+                left += ring(sample_left) * unison.left[i];
+                right += ring(sample_right) * unison.right[i];
+            }
+        }
+        // A drum has no envelope of its own to end the sub with, so it'd
+        // click when the hit's over.
+        if settings.mode != SynthesisMode::Drum {
+            let sub = self.sub.next(settings.sub_waveform, increment * 0.5) * settings.sub_level;
+            left += sub;
+            right += sub;
+        }
+
+        let cutoff = cutoff_frequency(
+            modulation.cutoff + filter_envelope * settings.filter_envelope_amount + matrix.cutoff,
+        );
+        let [left_filter, right_filter] = &mut self.filters;
+        let left = left_filter.process(
+            left,
+            settings.filter_mode,
+            cutoff,
+            modulation.resonance,
+            settings.sample_rate,
+        );
+        let right = right_filter.process(
+            right,
+            settings.filter_mode,
+            cutoff,
+            modulation.resonance,
+            settings.sample_rate,
+        );
+        let (left, right) = (left * amplitude, right * amplitude);
+        // Panned the same way as the whole mix, with the middle at unity gain.
+        let (left, right) = if matrix.pan == 0.0 {
+            (left, right)
+        } else {
+            let (pan_left, pan_right) = equal_power(matrix.pan);
+            (left * pan_left * SQRT_2, right * pan_right * SQRT_2)
+        };
+        match settings.drive {
+            Some(drive) => (drive.process(left), drive.process(right)),
+            None => (left, right),
+        }
+    }
+}
+
+pub struct VoiceAllocator {
+    voices: Vec<Voice>,
+    counter: u64,
+    sample_rate: f32,
+    // Twelve equal steps to the octave when there's no tuning loaded.
+    tuning: Option<Arc<Tuning>>,
+    humanize: f32,
+    // Where every note's seed comes from: `None` for a fresh one each time,
+    // or a fixed seed that the seeds follow on from, so a render comes out
+    // the same every time.
+    seed: Option<u32>,
+    seeds: Noise,
+}
+
+impl Default for VoiceAllocator {
+    fn default() -> Self {
+        Self {
+            voices: (0..MAX_VOICES).map(|_| Voice::default()).collect(),
+            counter: 0,
+            sample_rate: 44100.0,
+            tuning: None,
+            humanize: 0.0,
+            seed: None,
+            seeds: Noise::default(),
+        }
+    }
+}
+
+impl VoiceAllocator {
+    pub fn is_active(&self) -> bool {
+        self.voices.iter().any(Voice::is_active)
+    }
+
+    pub fn active_voices(&self) -> usize {
+        self.voices.iter().filter(|voice| voice.is_active()).count()
+    }
+
+    // Where the amp and filter envelopes are of the voice that was started
+    // last, or zero when nothing's sounding.
+    pub fn newest_envelopes(&self) -> (f32, f32) {
+        self.voices
+            .iter()
+            .filter(|voice| voice.is_active())
+            .max_by_key(|voice| voice.started)
+            .map_or((0.0, 0.0), |voice| {
+                (voice.envelope.level(), voice.filter_envelope.level())
+            })
+    }
+
+    // Notes that are already playing keep their pitch when the rate changes.
+    pub fn set_sample_rate(&mut self, sample_rate: f32) {
+        self.sample_rate = sample_rate;
+        for voice in self.voices.iter_mut() {
+            voice.set_sample_rate(sample_rate);
+            voice.increment = voice.target;
+        }
+    }
+
+    // Notes that are already playing keep their pitch until they're played
+    // again.  The tuning being replaced is handed back, so it needn't be
+    // freed on the audio thread.
+    pub fn set_tuning(&mut self, tuning: Option<Arc<Tuning>>) -> Option<Arc<Tuning>> {
+        mem::replace(&mut self.tuning, tuning)
+    }
+
+    // `amount` is from 0.0 to 1.0.
+    pub fn set_humanize(&mut self, amount: f32, seed: Option<u32>) {
+        self.humanize = amount;
+        if seed != self.seed {
+            self.seed = seed;
+            self.restart_seed();
+        }
+    }
+
+    // Go back to the first seed in the fixed seed's sequence, so notes
+    // played from here on get the same seeds as the last time.
+    pub fn restart_seed(&mut self) {
+        if let Some(seed) = self.seed {
+            self.seeds.seed(seed);
+        }
+    }
+
+    // The seed for the next note, and how far it's randomly detuned.
+    fn humanize(&mut self) -> (Humanize, f32) {
+        let seed = match self.seed {
+            Some(_) => self.seeds.next_u32(),
+            None => random(),
+        };
+        let mut random = Noise::new(seed);
+        let detune = semitones_to_ratio(random.white() * self.humanize * HUMANIZE_DETUNE);
+        let humanize = Humanize {
+            seed: random.next_u32(),
+            amount: self.humanize,
+        };
+        (humanize, detune)
+    }
+
+    // Keys the tuning leaves out give `None`.
+    fn frequency(&self, note: u8) -> Option<f32> {
+        match &self.tuning {
+            Some(tuning) => tuning.frequency(note),
+            None => Some(midi_pitch_to_freq(note)),
+        }
+    }
+
+    pub fn note_on(
+        &mut self,
+        note: u8,
+        velocity: f32,
+        expression: Expression,
+        polyphony: usize,
+        mode: VoiceMode,
+    ) {
+        let frequency = match self.frequency(note) {
+            Some(frequency) => frequency,
+            None => return,
+        };
+        let (humanize, detune) = self.humanize();
+        let frequency = frequency * detune;
+        match mode {
+            VoiceMode::Poly => {
+                self.poly_note_on(note, frequency, velocity, expression, humanize, polyphony)
+            }
+            VoiceMode::Mono | VoiceMode::Legato => {
+                self.mono_note_on(note, frequency, velocity, expression, humanize, mode)
+            }
+        }
+    }
+
+    // Mono and legato only ever use the first voice.
+    fn mono_note_on(
+        &mut self,
+        note: u8,
+        frequency: f32,
+        velocity: f32,
+        expression: Expression,
+        humanize: Humanize,
+        mode: VoiceMode,
+    ) {
+        self.counter += 1;
+
+        // Anything left over from poly mode is let go.
+        for voice in self.voices[1..].iter_mut() {
+            if voice.held {
+                voice.release();
+            }
+        }
+
+        let voice = &mut self.voices[0];
+        if !voice.is_active() {
+            voice.start(
+                note,
+                frequency,
+                velocity,
+                expression,
+                humanize,
+                self.sample_rate,
+            );
+            voice.started = self.counter;
+            return;
+        }
+
+        // Legato only starts the envelopes over if this is the only key down.
+        let retrigger = mode == VoiceMode::Mono || !voice.held;
+        voice.slide_to(note, frequency, self.sample_rate);
+        voice.started = self.counter;
+        voice.expression = expression;
+        if retrigger {
+            voice.velocity = velocity;
+            voice.retrigger();
+        }
+    }
+
+    fn poly_note_on(
+        &mut self,
+        note: u8,
+        frequency: f32,
+        velocity: f32,
+        expression: Expression,
+        humanize: Humanize,
+        polyphony: usize,
+    ) {
+        let polyphony = polyphony.max(1).min(MAX_VOICES);
+        self.counter += 1;
+
+        // Voices above the polyphony limit are left to finish their release.
+        for voice in self.voices[polyphony..].iter_mut() {
+            if voice.held {
+                voice.release();
+            }
+        }
+
+        let voices = &mut self.voices[..polyphony];
+        let index = match voices.iter().position(|voice| !voice.is_active()) {
+            Some(index) => index,
+            // Every voice is busy, so steal one.  Voices that are only fading
+            // out are the least noticeable to lose, then the oldest held note.
+            None => {
+                let (index, _) = voices
+                    .iter()
+                    .enumerate()
+                    .min_by_key(|(_, voice)| (voice.held, voice.started))
+                    .unwrap();
+                index
+            }
+        };
+        let voice = &mut voices[index];
+        voice.start(
+            note,
+            frequency,
+            velocity,
+            expression,
+            humanize,
+            self.sample_rate,
+        );
+        voice.started = self.counter;
+    }
+
+    // In mono and legato, letting go of the sounding key goes back to
+    // `fallback`, another key that is still held, rather than going quiet.
+    pub fn note_off(&mut self, note: u8, mode: VoiceMode, fallback: Option<u8>) {
+        let fallback = fallback.and_then(|fallback| Some((fallback, self.frequency(fallback)?)));
+        if let (VoiceMode::Mono, Some((fallback, frequency)))
+        | (VoiceMode::Legato, Some((fallback, frequency))) = (mode, fallback)
+        {
+            let voice = &mut self.voices[0];
+            if voice.held && voice.note == note {
+                voice.slide_to(fallback, frequency, self.sample_rate);
+                if mode == VoiceMode::Mono {
+                    voice.retrigger();
+                }
+            }
+            return;
+        }
+
+        for voice in self.voices.iter_mut() {
+            if voice.held && voice.note == note {
+                voice.release();
+            }
+        }
+    }
+
+    // Poly aftertouch only reaches the voice playing that key.
+    pub fn pressure(&mut self, note: u8, pressure: f32) {
+        for voice in self.voices.iter_mut() {
+            if voice.held && voice.note == note {
+                voice.expression.pressure = pressure;
+            }
+        }
+    }
+
+    // MPE expression reaches the voice playing on its channel.
+    pub fn set_expression(&mut self, expression: Expression) {
+        for voice in self.voices.iter_mut() {
+            if voice.held && voice.expression.channel == expression.channel {
+                voice.expression = expression;
+            }
+        }
+    }
+
+    // Let go of every held note at once.
+    pub fn release_all(&mut self) {
+        for voice in self.voices.iter_mut() {
+            if voice.held {
+                voice.release();
+            }
+        }
+    }
+
+    // Silence every voice at once, even the ones only fading out.
+    pub fn kill_all(&mut self) {
+        for voice in self.voices.iter_mut() {
+            voice.kill();
+        }
+    }
+
+    // Mix every sounding voice into a single stereo sample.
+    pub fn render(&mut self, settings: &VoiceSettings, modulation: &Modulation) -> (f32, f32) {
+        self.voices
+            .iter_mut()
+            .filter(|voice| voice.is_active())
+            .map(|voice| voice.render(settings, modulation))
+            .fold((0.0, 0.0), |(left, right), (l, r)| (left + l, right + r))
+    }
+}
+
+// Map a 0.0 to 1.0 parameter onto a fixed seed from 1 to 100, or `None` at
+// the bottom for a fresh seed every note.
+pub fn humanize_seed(value: f32) -> Option<u32> {
+    match (value * 100.0).round() as u32 {
+        0 => None,
+        seed => Some(seed),
+    }
+}
+
+// Map a 0.0 to 1.0 parameter onto 1 to `MAX_UNISON` copies.
+pub fn unison_voices(value: f32) -> usize {
+    1 + (value * (MAX_UNISON - 1) as f32).round() as usize
+}
+
+// A glide time of 0 to 2 seconds.
+pub const GLIDE_TIME: Range = Range {
+    min: 0.0,
+    max: 2.0,
+    curve: Curve::Power(2.0),
+};
+
+pub fn glide_time(value: f32) -> f32 {
+    GLIDE_TIME.map(value)
+}
+
+// Map a 0.0 to 1.0 parameter onto 1 to `MAX_VOICES` voices.
+pub fn polyphony(value: f32) -> usize {
+    1 + (value * (MAX_VOICES - 1) as f32).round() as usize
+}
@@ -0,0 +1,124 @@
+// A single cycle of the oscillator's waveform, drawn from one end to the
+// other, so it's plain to see what the waveform and FM knobs do to it.
+// Unlike the scope it shows what the oscillator would make, rather than
+// what's being heard.
+
+use conrod_core::{widget, Color, Colorable, Point, Positionable, Widget};
+
+pub struct Cycle<'a> {
+    common: widget::CommonBuilder,
+    // Empty when the sound doesn't come from the oscillator.
+    samples: &'a [f32],
+    style: Style,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Style {
+    color: Option<Color>,
+}
+
+widget_ids! {
+    struct Ids {
+        background,
+        centre,
+        trace,
+    }
+}
+
+pub struct State {
+    ids: Ids,
+}
+
+impl<'a> Cycle<'a> {
+    pub fn new(samples: &'a [f32]) -> Self {
+        Self {
+            common: widget::CommonBuilder::default(),
+            samples,
+            style: Style::default(),
+        }
+    }
+}
+
+impl<'a> widget::Common for Cycle<'a> {
+    fn common(&self) -> &widget::CommonBuilder {
+        &self.common
+    }
+
+    fn common_mut(&mut self) -> &mut widget::CommonBuilder {
+        &mut self.common
+    }
+}
+
+impl<'a> Colorable for Cycle<'a> {
+    fn color(mut self, color: Color) -> Self {
+        self.style.color = Some(color);
+        self
+    }
+}
+
+impl<'a> Widget for Cycle<'a> {
+    type State = State;
+    type Style = Style;
+    type Event = ();
+
+    fn init_state(&self, id_gen: widget::id::Generator) -> Self::State {
+        State {
+            ids: Ids::new(id_gen),
+        }
+    }
+
+    fn style(&self) -> Self::Style {
+        self.style
+    }
+
+    fn update(self, args: widget::UpdateArgs<Self>) -> Self::Event {
+        let widget::UpdateArgs {
+            id,
+            state,
+            rect,
+            style,
+            ui,
+            ..
+        } = args;
+
+        widget::Rectangle::fill(rect.dim())
+            .xy(rect.xy())
+            .color(ui.theme().background_color)
+            .parent(id)
+            .graphics_for(id)
+            .set(state.ids.background, ui);
+
+        // Zero, for seeing how far above and below it the wave goes.
+        let (left, centre) = (rect.left(), rect.y());
+        widget::Line::abs([left, centre], [rect.right(), centre])
+            .thickness(1.0)
+            .color(ui.theme().shape_color)
+            .parent(id)
+            .graphics_for(id)
+            .set(state.ids.centre, ui);
+
+        let length = self.samples.len();
+        if length < 2 {
+            return;
+        }
+        let points: Vec<Point> = self
+            .samples
+            .iter()
+            .enumerate()
+            .map(|(i, &sample)| {
+                let sample = f64::from(sample.max(-1.0).min(1.0));
+                [
+                    left + rect.w() * i as f64 / (length - 1) as f64,
+                    centre + sample * rect.h() / 2.0,
+                ]
+            })
+            .collect();
+
+        widget::PointPath::abs(points)
+            .thickness(1.0)
+            .color(style.color.unwrap_or(ui.theme().label_color))
+            .parent(id)
+            .graphics_for(id)
+            .set(state.ids.trace, ui);
+    }
+}
@@ -0,0 +1,219 @@
+// The shape of an envelope, drawn the way it sounds, with handles on its
+// corners to drag around: the peak for attack, the end of the decay for it
+// and the sustain level, and the end of the release.  Each of the attack,
+// decay and release gets a quarter of the width at most, and the last
+// quarter shows the note being held.
+
+use conrod_core::{widget, Color, Colorable, Point, Positionable, Widget};
+
+const HANDLE_RADIUS: f64 = 4.0;
+
+// How close the mouse has to be to a handle to grab it.
+const GRAB_DISTANCE: f64 = 8.0;
+
+// The parameters, all from 0.0 to 1.0.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Adsr {
+    pub attack: f32,
+    pub decay: f32,
+    pub sustain: f32,
+    pub release: f32,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Handle {
+    Attack,
+    Decay,
+    Release,
+}
+
+pub struct EnvelopeEditor {
+    common: widget::CommonBuilder,
+    adsr: Adsr,
+    style: Style,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Style {
+    color: Option<Color>,
+}
+
+widget_ids! {
+    struct Ids {
+        background,
+        shape,
+        attack,
+        decay,
+        release,
+    }
+}
+
+pub struct State {
+    ids: Ids,
+    // The handle being dragged.
+    grabbed: Option<Handle>,
+}
+
+impl EnvelopeEditor {
+    pub fn new(adsr: Adsr) -> Self {
+        Self {
+            common: widget::CommonBuilder::default(),
+            adsr,
+            style: Style::default(),
+        }
+    }
+}
+
+impl widget::Common for EnvelopeEditor {
+    fn common(&self) -> &widget::CommonBuilder {
+        &self.common
+    }
+
+    fn common_mut(&mut self) -> &mut widget::CommonBuilder {
+        &mut self.common
+    }
+}
+
+impl Colorable for EnvelopeEditor {
+    fn color(mut self, color: Color) -> Self {
+        self.style.color = Some(color);
+        self
+    }
+}
+
+// Where the corners are, from the widget's centre, given its size.  The
+// first is where the note starts, the third where the sustain starts and the
+// fourth where it's let go.
+fn corners(adsr: Adsr, width: f64, height: f64) -> [Point; 5] {
+    let (left, bottom) = (-width / 2.0, -height / 2.0);
+    let quarter = width / 4.0;
+    let peak = left + quarter * f64::from(adsr.attack);
+    let decayed = peak + quarter * f64::from(adsr.decay);
+    let sustain = bottom + height * f64::from(adsr.sustain);
+    let released = decayed + quarter;
+    [
+        [left, bottom],
+        [peak, -bottom],
+        [decayed, sustain],
+        [released, sustain],
+        [released + quarter * f64::from(adsr.release), bottom],
+    ]
+}
+
+fn distance(a: Point, b: Point) -> f64 {
+    ((a[0] - b[0]).powi(2) + (a[1] - b[1]).powi(2)).sqrt()
+}
+
+fn unit(value: f64) -> f32 {
+    value.max(0.0).min(1.0) as f32
+}
+
+impl Widget for EnvelopeEditor {
+    type State = State;
+    type Style = Style;
+    // The new values, whenever a handle has been dragged.
+    type Event = Option<Adsr>;
+
+    fn init_state(&self, id_gen: widget::id::Generator) -> Self::State {
+        State {
+            ids: Ids::new(id_gen),
+            grabbed: None,
+        }
+    }
+
+    fn style(&self) -> Self::Style {
+        self.style
+    }
+
+    fn update(self, args: widget::UpdateArgs<Self>) -> Self::Event {
+        let widget::UpdateArgs {
+            id,
+            state,
+            rect,
+            style,
+            ui,
+            ..
+        } = args;
+
+        let (width, height) = (rect.w(), rect.h());
+        let quarter = width / 4.0;
+        let mut adsr = self.adsr;
+
+        // Where the mouse is goes straight into the parameters, so the drag
+        // is given as positions from the widget's centre, like the corners.
+        for drag in ui.widget_input(id).drags().left() {
+            let grabbed = match state.grabbed {
+                Some(handle) => Some(handle),
+                None => {
+                    let corners = corners(adsr, width, height);
+                    [
+                        (Handle::Attack, corners[1]),
+                        (Handle::Decay, corners[2]),
+                        (Handle::Release, corners[4]),
+                    ]
+                    .iter()
+                    .find(|(_, corner)| distance(*corner, drag.origin) < GRAB_DISTANCE)
+                    .map(|&(handle, _)| handle)
+                }
+            };
+            state.update(|state| state.grabbed = grabbed);
+            let along = (drag.to[0] + width / 2.0) / quarter;
+            match grabbed {
+                Some(Handle::Attack) => adsr.attack = unit(along),
+                Some(Handle::Decay) => {
+                    adsr.decay = unit(along - f64::from(adsr.attack));
+                    adsr.sustain = unit((drag.to[1] + height / 2.0) / height);
+                }
+                Some(Handle::Release) => {
+                    let released = f64::from(adsr.attack + adsr.decay) + 1.0;
+                    adsr.release = unit(along - released);
+                }
+                None => {}
+            }
+        }
+        if ui.global_input().current.mouse.buttons.left().is_up() {
+            state.update(|state| state.grabbed = None);
+        }
+
+        let color = style.color.unwrap_or(ui.theme().shape_color);
+        let centre = rect.xy();
+        let corners: Vec<Point> = corners(adsr, width, height)
+            .iter()
+            .map(|corner| [centre[0] + corner[0], centre[1] + corner[1]])
+            .collect();
+
+        widget::Rectangle::fill([width, height])
+            .xy(centre)
+            .color(ui.theme().background_color)
+            .parent(id)
+            .graphics_for(id)
+            .set(state.ids.background, ui);
+
+        widget::PointPath::abs(corners.clone())
+            .thickness(2.0)
+            .color(color)
+            .parent(id)
+            .graphics_for(id)
+            .set(state.ids.shape, ui);
+
+        let handles = [
+            (state.ids.attack, corners[1]),
+            (state.ids.decay, corners[2]),
+            (state.ids.release, corners[4]),
+        ];
+        for &(handle, corner) in handles.iter() {
+            widget::Circle::fill(HANDLE_RADIUS)
+                .xy(corner)
+                .color(ui.theme().label_color)
+                .parent(id)
+                .graphics_for(id)
+                .set(handle, ui);
+        }
+
+        if adsr != self.adsr {
+            Some(adsr)
+        } else {
+            None
+        }
+    }
+}
@@ -0,0 +1,109 @@
+// The three little lines in the bottom right corner that are dragged to
+// resize the editor.  It only says what size the window should be; making
+// it that size, and telling the host, is up to the editor.
+
+use conrod_core::{widget, Point, Positionable, Widget};
+
+const LINES: usize = 3;
+
+pub struct Grip {
+    common: widget::CommonBuilder,
+}
+
+widget_ids! {
+    struct Ids {
+        lines[],
+    }
+}
+
+pub struct State {
+    ids: Ids,
+    // How far the window's corner was from the mouse when it was grabbed,
+    // so it keeps that far away however the mouse goes.
+    grabbed: Option<Point>,
+}
+
+impl Grip {
+    pub fn new() -> Self {
+        Self {
+            common: widget::CommonBuilder::default(),
+        }
+    }
+}
+
+impl widget::Common for Grip {
+    fn common(&self) -> &widget::CommonBuilder {
+        &self.common
+    }
+
+    fn common_mut(&mut self) -> &mut widget::CommonBuilder {
+        &mut self.common
+    }
+}
+
+impl Widget for Grip {
+    type State = State;
+    type Style = ();
+    // The window's new width and height, whenever it's been dragged.
+    type Event = Option<[f64; 2]>;
+
+    fn init_state(&self, id_gen: widget::id::Generator) -> Self::State {
+        State {
+            ids: Ids::new(id_gen),
+            grabbed: None,
+        }
+    }
+
+    fn style(&self) -> Self::Style {}
+
+    fn update(self, args: widget::UpdateArgs<Self>) -> Self::Event {
+        let widget::UpdateArgs {
+            id,
+            state,
+            rect,
+            ui,
+            ..
+        } = args;
+
+        if state.ids.lines.len() < LINES {
+            let generator = &mut ui.widget_id_generator();
+            state.update(|state| state.ids.lines.resize(LINES, generator));
+        }
+
+        // The window moves under the mouse as it's resized, which throws
+        // the drag's own positions out, so this goes by where the mouse is
+        // in the window instead.  Its middle is at (0, 0), with y going up.
+        let mouse = ui.global_input().current.mouse;
+        let (half_width, half_height) = (ui.win_w / 2.0, ui.win_h / 2.0);
+        let mut size = None;
+        if mouse.buttons.left().is_up() {
+            state.update(|state| state.grabbed = None);
+        } else if ui.widget_input(id).drags().left().next().is_some() || state.grabbed.is_some() {
+            let grabbed = state
+                .grabbed
+                .unwrap_or([half_width - mouse.xy[0], -half_height - mouse.xy[1]]);
+            size = Some([
+                mouse.xy[0] + grabbed[0] + half_width,
+                half_height - (mouse.xy[1] + grabbed[1]),
+            ]);
+            state.update(|state| state.grabbed = Some(grabbed));
+        }
+
+        // Lines across the corner, each further out than the last.
+        let corner = [rect.right(), rect.bottom()];
+        for line in 0..LINES {
+            let distance = rect.w() * (line + 1) as f64 / LINES as f64;
+            widget::Line::abs(
+                [corner[0] - distance, corner[1]],
+                [corner[0], corner[1] + distance],
+            )
+            .thickness(1.0)
+            .color(ui.theme().shape_color)
+            .parent(id)
+            .graphics_for(id)
+            .set(state.ids.lines[line], ui);
+        }
+
+        size
+    }
+}
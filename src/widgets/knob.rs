@@ -0,0 +1,263 @@
+// A round knob, turned by dragging up and down, with an arc around it
+// showing how far it's turned.  It fits a lot more parameters into a small
+// window than sliders do.  Double-clicking puts it back where it started,
+// dragging with shift held turns it more finely, and dragging with ctrl
+// held keeps a parameter with steps on them.
+// With a film strip from a skin, it's drawn as the frame of the strip for
+// how far it's turned instead.
+
+use conrod_core::input::keyboard::ModifierKey;
+use conrod_core::{
+    event, image, input, widget, Color, Colorable, Point, Positionable, Rect, Sizeable, Widget,
+};
+use std::f64::consts::PI;
+
+// How far the mouse has to go for the knob to turn all the way, and how
+// many times further with shift held.
+const DRAG_DISTANCE: f64 = 200.0;
+const FINE: f64 = 10.0;
+
+// The knob turns through three quarters of a circle, starting at the bottom
+// left, and its arcs are drawn as this many straight lines.
+const START: f64 = 1.25 * PI;
+const SWEEP: f64 = 1.5 * PI;
+const ARC_SEGMENTS: usize = 32;
+
+// A picture of a knob at every step of its turn, from all the way down at
+// the top to all the way up at the bottom, each frame `size` pixels square.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct FilmStrip {
+    pub image: image::Id,
+    pub size: f64,
+    pub frames: usize,
+}
+
+pub struct Knob {
+    common: widget::CommonBuilder,
+    value: f32,
+    min: f32,
+    max: f32,
+    default: Option<f32>,
+    steps: Option<usize>,
+    film_strip: Option<FilmStrip>,
+    style: Style,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Style {
+    color: Option<Color>,
+}
+
+widget_ids! {
+    struct Ids {
+        body,
+        track,
+        arc,
+        pointer,
+        frame,
+    }
+}
+
+pub struct State {
+    ids: Ids,
+    // Where a drag has turned it to so far.  Parameters with only a few
+    // choices snap back to the nearest one after every step of a drag, so
+    // the drag has to be followed here, or it would never get anywhere.
+    dragged: Option<f32>,
+}
+
+impl Knob {
+    // `min` has to be below `max`.
+    pub fn new(value: f32, min: f32, max: f32) -> Self {
+        Self {
+            common: widget::CommonBuilder::default(),
+            value,
+            min,
+            max,
+            default: None,
+            steps: None,
+            film_strip: None,
+            style: Style::default(),
+        }
+    }
+
+    // What double-clicking goes back to.
+    pub fn default_value(mut self, default: f32) -> Self {
+        self.default = Some(default);
+        self
+    }
+
+    // How many choices there are, for parameters like the waveform, with
+    // the first at `min` and the last at `max`.
+    pub fn steps(mut self, steps: Option<usize>) -> Self {
+        self.steps = steps;
+        self
+    }
+
+    // Drawn from this, if there is one.
+    pub fn film_strip(mut self, film_strip: Option<FilmStrip>) -> Self {
+        self.film_strip = film_strip;
+        self
+    }
+}
+
+impl widget::Common for Knob {
+    fn common(&self) -> &widget::CommonBuilder {
+        &self.common
+    }
+
+    fn common_mut(&mut self) -> &mut widget::CommonBuilder {
+        &mut self.common
+    }
+}
+
+impl Colorable for Knob {
+    fn color(mut self, color: Color) -> Self {
+        self.style.color = Some(color);
+        self
+    }
+}
+
+impl Widget for Knob {
+    type State = State;
+    type Style = Style;
+    // The new value, whenever it's been turned.
+    type Event = Option<f32>;
+
+    fn init_state(&self, id_gen: widget::id::Generator) -> Self::State {
+        State {
+            ids: Ids::new(id_gen),
+            dragged: None,
+        }
+    }
+
+    fn style(&self) -> Self::Style {
+        self.style
+    }
+
+    fn update(self, args: widget::UpdateArgs<Self>) -> Self::Event {
+        let widget::UpdateArgs {
+            id,
+            state,
+            rect,
+            style,
+            ui,
+            ..
+        } = args;
+
+        let range = self.max - self.min;
+        let mut value = self.value;
+        let mut dragged = false;
+        let mut snap = false;
+        for drag in ui.widget_input(id).drags().left() {
+            let distance = if drag.modifiers.contains(ModifierKey::SHIFT) {
+                DRAG_DISTANCE * FINE
+            } else {
+                DRAG_DISTANCE
+            };
+            value = state.dragged.unwrap_or(value);
+            value += (drag.delta_xy[1] / distance) as f32 * range;
+            value = value.max(self.min).min(self.max);
+            state.update(|state| state.dragged = Some(value));
+            dragged = true;
+            snap = drag.modifiers.contains(ModifierKey::CTRL);
+        }
+        if !dragged && ui.global_input().current.mouse.buttons.left().is_up() {
+            state.update(|state| state.dragged = None);
+        }
+        if let Some(default) = self.default {
+            for event in ui.widget_input(id).events() {
+                if let event::Widget::DoubleClick(click) = event {
+                    if click.button == input::MouseButton::Left {
+                        value = default;
+                    }
+                }
+            }
+        }
+        let mut value = value.max(self.min).min(self.max);
+        // The drag itself goes on smoothly, so that it gets from one step to
+        // the next.
+        match self.steps {
+            Some(steps) if snap && steps > 1 => {
+                let last = (steps - 1) as f32;
+                let choice = ((value - self.min) / range * last).round();
+                value = self.min + range * choice / last;
+            }
+            _ => {}
+        }
+
+        let color = style.color.unwrap_or(ui.theme().shape_color);
+        let centre = rect.xy();
+        let radius = rect.w().min(rect.h()) / 2.0;
+        let turned = f64::from((value - self.min) / range);
+        let event = if value != self.value {
+            Some(value)
+        } else {
+            None
+        };
+
+        if let Some(strip) = self.film_strip {
+            // The frames are counted down from the top, and the image up
+            // from the bottom.
+            let frame = (turned * (strip.frames - 1) as f64).round();
+            let top = strip.size * (strip.frames as f64 - frame);
+            widget::Image::new(strip.image)
+                .source_rectangle(Rect::from_corners(
+                    [0.0, top - strip.size],
+                    [strip.size, top],
+                ))
+                .w_h(radius * 2.0, radius * 2.0)
+                .xy(centre)
+                .parent(id)
+                .graphics_for(id)
+                .set(state.ids.frame, ui);
+            return event;
+        }
+
+        let point = |angle: f64, distance: f64| -> Point {
+            [
+                centre[0] + distance * angle.cos(),
+                centre[1] + distance * angle.sin(),
+            ]
+        };
+        let arc = |to: f64| -> Vec<Point> {
+            (0..=ARC_SEGMENTS)
+                .map(|segment| {
+                    let along = to * segment as f64 / ARC_SEGMENTS as f64;
+                    point(START - SWEEP * along, radius - 2.0)
+                })
+                .collect()
+        };
+
+        widget::Circle::fill(radius * 0.7)
+            .xy(centre)
+            .color(ui.theme().background_color)
+            .parent(id)
+            .graphics_for(id)
+            .set(state.ids.body, ui);
+
+        widget::PointPath::abs(arc(1.0))
+            .thickness(3.0)
+            .color(ui.theme().shape_color)
+            .parent(id)
+            .graphics_for(id)
+            .set(state.ids.track, ui);
+
+        widget::PointPath::abs(arc(turned))
+            .thickness(3.0)
+            .color(color)
+            .parent(id)
+            .graphics_for(id)
+            .set(state.ids.arc, ui);
+
+        let angle = START - SWEEP * turned;
+        widget::Line::abs(point(angle, radius * 0.2), point(angle, radius * 0.7))
+            .thickness(2.0)
+            .color(ui.theme().label_color)
+            .parent(id)
+            .graphics_for(id)
+            .set(state.ids.pointer, ui);
+
+        event
+    }
+}
@@ -0,0 +1,169 @@
+// A level meter for each channel: a bar for the RMS level, the peak over it,
+// and a line that holds the highest peak and falls back slowly.  Above them
+// is a clip light, which comes on when either channel reaches full scale and
+// stays on until it's clicked.
+
+use crate::gain::gain_to_db;
+use conrod_core::{color, widget, Colorable, Positionable, Widget};
+
+// The quietest level shown, in dB, and how far the held peaks fall back
+// every time the meter is drawn.
+const FLOOR_DB: f32 = -60.0;
+const HOLD_FALL_DB: f32 = 0.3;
+
+const CLIP_LIGHT_HEIGHT: f64 = 8.0;
+const GAP: f64 = 2.0;
+
+pub struct Meter {
+    common: widget::CommonBuilder,
+    // Gains, for the left and right channels.
+    rms: [f32; 2],
+    peak: [f32; 2],
+}
+
+widget_ids! {
+    struct Ids {
+        backgrounds[],
+        rms[],
+        peaks[],
+        held[],
+        clip,
+    }
+}
+
+pub struct State {
+    ids: Ids,
+    // Where the held peaks are, from 0.0 to 1.0 of the way up.
+    held: [f32; 2],
+    clipped: bool,
+}
+
+impl Meter {
+    pub fn new(rms: [f32; 2], peak: [f32; 2]) -> Self {
+        Self {
+            common: widget::CommonBuilder::default(),
+            rms,
+            peak,
+        }
+    }
+}
+
+impl widget::Common for Meter {
+    fn common(&self) -> &widget::CommonBuilder {
+        &self.common
+    }
+
+    fn common_mut(&mut self) -> &mut widget::CommonBuilder {
+        &mut self.common
+    }
+}
+
+// How far up the meter a gain goes.
+fn height(gain: f32) -> f32 {
+    (1.0 - gain_to_db(gain) / FLOOR_DB).max(0.0).min(1.0)
+}
+
+impl Widget for Meter {
+    type State = State;
+    type Style = ();
+    type Event = ();
+
+    fn init_state(&self, id_gen: widget::id::Generator) -> Self::State {
+        State {
+            ids: Ids::new(id_gen),
+            held: [0.0; 2],
+            clipped: false,
+        }
+    }
+
+    fn style(&self) -> Self::Style {}
+
+    fn update(self, args: widget::UpdateArgs<Self>) -> Self::Event {
+        let widget::UpdateArgs {
+            id,
+            state,
+            rect,
+            ui,
+            ..
+        } = args;
+
+        if state.ids.backgrounds.len() < 2 {
+            let generator = &mut ui.widget_id_generator();
+            state.update(|state| {
+                state.ids.backgrounds.resize(2, generator);
+                state.ids.rms.resize(2, generator);
+                state.ids.peaks.resize(2, generator);
+                state.ids.held.resize(2, generator);
+            });
+        }
+
+        let clicked = ui
+            .widget_input(state.ids.clip)
+            .clicks()
+            .left()
+            .next()
+            .is_some();
+        let clipped = !clicked && (state.clipped || self.peak.iter().any(|&peak| peak >= 1.0));
+        let mut held = state.held;
+        for (held, &peak) in held.iter_mut().zip(self.peak.iter()) {
+            *held = height(peak).max(*held + HOLD_FALL_DB / FLOOR_DB);
+        }
+        state.update(|state| {
+            state.held = held;
+            state.clipped = clipped;
+        });
+
+        widget::Rectangle::fill([rect.w(), CLIP_LIGHT_HEIGHT])
+            .mid_top_of(id)
+            .color(if clipped {
+                color::RED
+            } else {
+                ui.theme().background_color
+            })
+            .parent(id)
+            .set(state.ids.clip, ui);
+
+        let width = (rect.w() - GAP) / 2.0;
+        let full = rect.h() - CLIP_LIGHT_HEIGHT - GAP;
+        for channel in 0..2 {
+            let left = rect.left() + channel as f64 * (width + GAP);
+
+            widget::Rectangle::fill([width, full])
+                .x_y(left + width / 2.0, rect.bottom() + full / 2.0)
+                .color(ui.theme().background_color)
+                .parent(id)
+                .graphics_for(id)
+                .set(state.ids.backgrounds[channel], ui);
+
+            let bars = [
+                (
+                    state.ids.peaks[channel],
+                    height(self.peak[channel]),
+                    color::DARK_GREEN,
+                ),
+                (
+                    state.ids.rms[channel],
+                    height(self.rms[channel]),
+                    color::LIGHT_GREEN,
+                ),
+            ];
+            for &(bar, level, color) in bars.iter() {
+                let top = (full * f64::from(level)).max(1.0);
+                widget::Rectangle::fill([width, top])
+                    .x_y(left + width / 2.0, rect.bottom() + top / 2.0)
+                    .color(color)
+                    .parent(id)
+                    .graphics_for(id)
+                    .set(bar, ui);
+            }
+
+            let held = rect.bottom() + full * f64::from(state.held[channel]);
+            widget::Rectangle::fill([width, 1.0])
+                .x_y(left + width / 2.0, held)
+                .color(ui.theme().label_color)
+                .parent(id)
+                .graphics_for(id)
+                .set(state.ids.held[channel], ui);
+        }
+    }
+}
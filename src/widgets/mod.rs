@@ -0,0 +1,21 @@
+// Widgets of our own for the editor, for things conrod doesn't have.  Each
+// one is built the way conrod's own are: a builder that's set like any other
+// widget, and that returns what the user did with it.
+
+mod cycle;
+mod envelope;
+mod grip;
+mod knob;
+mod meter;
+mod scope;
+mod spectrum;
+mod steps;
+
+pub use cycle::Cycle;
+pub use envelope::{Adsr, EnvelopeEditor};
+pub use grip::Grip;
+pub use knob::{FilmStrip, Knob};
+pub use meter::Meter;
+pub use scope::Scope;
+pub use spectrum::Spectrum;
+pub use steps::{GridStep, StepEdit, StepGrid};
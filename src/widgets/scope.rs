@@ -0,0 +1,116 @@
+// The output's waveform, the way an oscilloscope shows it.  It starts where
+// the signal rises through zero, so a steady note stays still rather than
+// sliding across from one frame to the next.
+
+use conrod_core::{widget, Color, Colorable, Point, Positionable, Widget};
+
+pub struct Scope<'a> {
+    common: widget::CommonBuilder,
+    // Twice as many as get drawn, to leave room for finding a start.
+    samples: &'a [f32],
+    style: Style,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Style {
+    color: Option<Color>,
+}
+
+widget_ids! {
+    struct Ids {
+        background,
+        trace,
+    }
+}
+
+pub struct State {
+    ids: Ids,
+}
+
+impl<'a> Scope<'a> {
+    pub fn new(samples: &'a [f32]) -> Self {
+        Self {
+            common: widget::CommonBuilder::default(),
+            samples,
+            style: Style::default(),
+        }
+    }
+}
+
+impl<'a> widget::Common for Scope<'a> {
+    fn common(&self) -> &widget::CommonBuilder {
+        &self.common
+    }
+
+    fn common_mut(&mut self) -> &mut widget::CommonBuilder {
+        &mut self.common
+    }
+}
+
+impl<'a> Colorable for Scope<'a> {
+    fn color(mut self, color: Color) -> Self {
+        self.style.color = Some(color);
+        self
+    }
+}
+
+impl<'a> Widget for Scope<'a> {
+    type State = State;
+    type Style = Style;
+    type Event = ();
+
+    fn init_state(&self, id_gen: widget::id::Generator) -> Self::State {
+        State {
+            ids: Ids::new(id_gen),
+        }
+    }
+
+    fn style(&self) -> Self::Style {
+        self.style
+    }
+
+    fn update(self, args: widget::UpdateArgs<Self>) -> Self::Event {
+        let widget::UpdateArgs {
+            id,
+            state,
+            rect,
+            style,
+            ui,
+            ..
+        } = args;
+
+        widget::Rectangle::fill(rect.dim())
+            .xy(rect.xy())
+            .color(ui.theme().background_color)
+            .parent(id)
+            .graphics_for(id)
+            .set(state.ids.background, ui);
+
+        let length = self.samples.len() / 2;
+        if length < 2 {
+            return;
+        }
+        let start = (1..length)
+            .find(|&i| self.samples[i - 1] < 0.0 && self.samples[i] >= 0.0)
+            .unwrap_or(0);
+        let (left, centre) = (rect.left(), rect.y());
+        let points: Vec<Point> = self.samples[start..start + length]
+            .iter()
+            .enumerate()
+            .map(|(i, &sample)| {
+                let sample = f64::from(sample.max(-1.0).min(1.0));
+                [
+                    left + rect.w() * i as f64 / (length - 1) as f64,
+                    centre + sample * rect.h() / 2.0,
+                ]
+            })
+            .collect();
+
+        widget::PointPath::abs(points)
+            .thickness(1.0)
+            .color(style.color.unwrap_or(ui.theme().shape_color))
+            .parent(id)
+            .graphics_for(id)
+            .set(state.ids.trace, ui);
+    }
+}
@@ -0,0 +1,119 @@
+// The levels of the spectrum, lowest frequencies on the left, with the peak
+// each band has held drawn over them.
+
+use conrod_core::{widget, Color, Colorable, Point, Positionable, Widget};
+
+pub struct Spectrum<'a> {
+    common: widget::CommonBuilder,
+    // Both from 0.0 to 1.0, one for every band.
+    levels: &'a [f32],
+    peaks: &'a [f32],
+    style: Style,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Style {
+    color: Option<Color>,
+}
+
+widget_ids! {
+    struct Ids {
+        background,
+        levels,
+        peaks,
+    }
+}
+
+pub struct State {
+    ids: Ids,
+}
+
+impl<'a> Spectrum<'a> {
+    pub fn new(levels: &'a [f32], peaks: &'a [f32]) -> Self {
+        Self {
+            common: widget::CommonBuilder::default(),
+            levels,
+            peaks,
+            style: Style::default(),
+        }
+    }
+}
+
+impl<'a> widget::Common for Spectrum<'a> {
+    fn common(&self) -> &widget::CommonBuilder {
+        &self.common
+    }
+
+    fn common_mut(&mut self) -> &mut widget::CommonBuilder {
+        &mut self.common
+    }
+}
+
+impl<'a> Colorable for Spectrum<'a> {
+    fn color(mut self, color: Color) -> Self {
+        self.style.color = Some(color);
+        self
+    }
+}
+
+impl<'a> Widget for Spectrum<'a> {
+    type State = State;
+    type Style = Style;
+    type Event = ();
+
+    fn init_state(&self, id_gen: widget::id::Generator) -> Self::State {
+        State {
+            ids: Ids::new(id_gen),
+        }
+    }
+
+    fn style(&self) -> Self::Style {
+        self.style
+    }
+
+    fn update(self, args: widget::UpdateArgs<Self>) -> Self::Event {
+        let widget::UpdateArgs {
+            id,
+            state,
+            rect,
+            style,
+            ui,
+            ..
+        } = args;
+
+        widget::Rectangle::fill(rect.dim())
+            .xy(rect.xy())
+            .color(ui.theme().background_color)
+            .parent(id)
+            .graphics_for(id)
+            .set(state.ids.background, ui);
+
+        let points = |levels: &[f32]| -> Vec<Point> {
+            let last = levels.len().max(2) - 1;
+            levels
+                .iter()
+                .enumerate()
+                .map(|(band, &level)| {
+                    [
+                        rect.left() + rect.w() * band as f64 / last as f64,
+                        rect.bottom() + rect.h() * f64::from(level),
+                    ]
+                })
+                .collect()
+        };
+
+        widget::PointPath::abs(points(self.levels))
+            .thickness(1.0)
+            .color(style.color.unwrap_or(ui.theme().shape_color))
+            .parent(id)
+            .graphics_for(id)
+            .set(state.ids.levels, ui);
+
+        widget::PointPath::abs(points(self.peaks))
+            .thickness(1.0)
+            .color(ui.theme().label_color.alpha(0.5))
+            .parent(id)
+            .graphics_for(id)
+            .set(state.ids.peaks, ui);
+    }
+}
@@ -0,0 +1,244 @@
+// The step sequencer's pattern as a grid, a column for each step.  The top
+// of each column is a bar for its pitch, up or down from the middle, and
+// the bottom a bar for its velocity, up from the bottom.  Clicking or
+// dragging over the bars sets them to where the mouse is, and a right
+// click turns the step into a rest or back again.
+
+use conrod_core::{widget, Color, Colorable, Positionable, Widget};
+
+// How much of the height the pitch bars get, with the rest going to the
+// velocities under them.
+const PITCH_SHARE: f64 = 0.65;
+const GAP: f64 = 4.0;
+
+// A step's parameters, from 0.0 to 1.0, and whether it plays at all.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct GridStep {
+    pub pitch: f32,
+    pub velocity: f32,
+    pub gate: bool,
+}
+
+// What was done to which step.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum StepEdit {
+    Pitch(usize, f32),
+    Velocity(usize, f32),
+    Gate(usize),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Row {
+    Pitch,
+    Velocity,
+}
+
+pub struct StepGrid<'a> {
+    common: widget::CommonBuilder,
+    steps: &'a [GridStep],
+    playing: Option<usize>,
+    style: Style,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Style {
+    color: Option<Color>,
+}
+
+widget_ids! {
+    struct Ids {
+        background,
+        playing,
+        middle,
+        pitches[],
+        velocities[],
+    }
+}
+
+pub struct State {
+    ids: Ids,
+    // The row the drag started in, which it keeps to even when the mouse
+    // strays into the other one.
+    grabbed: Option<Row>,
+}
+
+impl<'a> StepGrid<'a> {
+    pub fn new(steps: &'a [GridStep]) -> Self {
+        Self {
+            common: widget::CommonBuilder::default(),
+            steps,
+            playing: None,
+            style: Style::default(),
+        }
+    }
+
+    // The step the sequencer is on, to light up its column.
+    pub fn playing(mut self, playing: Option<usize>) -> Self {
+        self.playing = playing;
+        self
+    }
+}
+
+impl<'a> widget::Common for StepGrid<'a> {
+    fn common(&self) -> &widget::CommonBuilder {
+        &self.common
+    }
+
+    fn common_mut(&mut self) -> &mut widget::CommonBuilder {
+        &mut self.common
+    }
+}
+
+impl<'a> Colorable for StepGrid<'a> {
+    fn color(mut self, color: Color) -> Self {
+        self.style.color = Some(color);
+        self
+    }
+}
+
+fn unit(value: f64) -> f32 {
+    value.max(0.0).min(1.0) as f32
+}
+
+impl<'a> Widget for StepGrid<'a> {
+    type State = State;
+    type Style = Style;
+    // Everything done to the steps since the last time.
+    type Event = Vec<StepEdit>;
+
+    fn init_state(&self, id_gen: widget::id::Generator) -> Self::State {
+        State {
+            ids: Ids::new(id_gen),
+            grabbed: None,
+        }
+    }
+
+    fn style(&self) -> Self::Style {
+        self.style
+    }
+
+    fn update(self, args: widget::UpdateArgs<Self>) -> Self::Event {
+        let widget::UpdateArgs {
+            id,
+            state,
+            rect,
+            style,
+            ui,
+            ..
+        } = args;
+
+        let count = self.steps.len();
+        if state.ids.pitches.len() < count {
+            let generator = &mut ui.widget_id_generator();
+            state.update(|state| {
+                state.ids.pitches.resize(count, generator);
+                state.ids.velocities.resize(count, generator);
+            });
+        }
+        let mut edits = Vec::new();
+        if count == 0 {
+            return edits;
+        }
+
+        // Measured down from the top, and along from the left, to match the
+        // mouse's positions from the widget's centre.
+        let (width, height) = (rect.w(), rect.h());
+        let column = width / count as f64;
+        let pitch_height = (height - GAP) * PITCH_SHARE;
+        let velocity_height = height - GAP - pitch_height;
+        let row_of = |y: f64| {
+            if height / 2.0 - y < pitch_height + GAP / 2.0 {
+                Row::Pitch
+            } else {
+                Row::Velocity
+            }
+        };
+
+        // The press sets the step under it, and a drag every step it goes
+        // over on the way.
+        let mut points = Vec::new();
+        for press in ui.widget_input(id).presses().mouse().left() {
+            let row = row_of(press[1]);
+            state.update(|state| state.grabbed = Some(row));
+            points.push(press);
+        }
+        for drag in ui.widget_input(id).drags().left() {
+            points.push(drag.to);
+        }
+        if let Some(row) = state.grabbed {
+            for point in points {
+                let step = ((point[0] + width / 2.0) / column).max(0.0) as usize;
+                let step = step.min(count - 1);
+                let down = height / 2.0 - point[1];
+                edits.push(match row {
+                    Row::Pitch => StepEdit::Pitch(step, unit(1.0 - down / pitch_height)),
+                    Row::Velocity => {
+                        StepEdit::Velocity(step, unit((height - down) / velocity_height))
+                    }
+                });
+            }
+        }
+        for click in ui.widget_input(id).clicks().right() {
+            let step = ((click.xy[0] + width / 2.0) / column).max(0.0) as usize;
+            edits.push(StepEdit::Gate(step.min(count - 1)));
+        }
+        if ui.global_input().current.mouse.buttons.left().is_up() {
+            state.update(|state| state.grabbed = None);
+        }
+
+        widget::Rectangle::fill(rect.dim())
+            .xy(rect.xy())
+            .color(ui.theme().background_color)
+            .parent(id)
+            .graphics_for(id)
+            .set(state.ids.background, ui);
+
+        if let Some(playing) = self.playing.filter(|&playing| playing < count) {
+            widget::Rectangle::fill([column, height])
+                .x_y(rect.left() + (playing as f64 + 0.5) * column, rect.y())
+                .color(ui.theme().shape_color)
+                .parent(id)
+                .graphics_for(id)
+                .set(state.ids.playing, ui);
+        }
+
+        // No pitch change, for the pitch bars to go up and down from.
+        let middle = rect.top() - pitch_height / 2.0;
+        widget::Line::abs([rect.left(), middle], [rect.right(), middle])
+            .thickness(1.0)
+            .color(ui.theme().shape_color)
+            .parent(id)
+            .graphics_for(id)
+            .set(state.ids.middle, ui);
+
+        // Rests are drawn in the colour of their labels, to stand back.
+        let color = style.color.unwrap_or(ui.theme().shape_color);
+        let bar_width = (column - 2.0).max(1.0);
+        for (i, step) in self.steps.iter().enumerate() {
+            let x = rect.left() + (i as f64 + 0.5) * column;
+            let step_color = if step.gate {
+                color
+            } else {
+                ui.theme().label_color.alpha(0.3)
+            };
+
+            let pitch = (f64::from(step.pitch) - 0.5) * pitch_height;
+            widget::Rectangle::fill([bar_width, pitch.abs().max(1.0)])
+                .x_y(x, middle + pitch / 2.0)
+                .color(step_color)
+                .parent(id)
+                .graphics_for(id)
+                .set(state.ids.pitches[i], ui);
+
+            let velocity = f64::from(step.velocity) * velocity_height;
+            widget::Rectangle::fill([bar_width, velocity.max(1.0)])
+                .x_y(x, rect.bottom() + velocity / 2.0)
+                .color(step_color)
+                .parent(id)
+                .graphics_for(id)
+                .set(state.ids.velocities[i], ui);
+        }
+
+        edits
+    }
+}
@@ -0,0 +1,44 @@
+// A thread of our own for what the audio thread gets asked to do but
+// mustn't do itself, because it reads files, allocates or waits on locks.
+// The audio thread only ever hands it work through a queue, and it looks
+// for some every few milliseconds until it's dropped.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+// Short enough that whoever asked for something hardly notices the wait.
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+pub struct Worker {
+    running: Arc<AtomicBool>,
+    thread: Option<thread::JoinHandle<()>>,
+}
+
+impl Worker {
+    // `poll` does whatever's waiting, every time the thread wakes up.
+    pub fn spawn<F: FnMut() + Send + 'static>(mut poll: F) -> Self {
+        let running = Arc::new(AtomicBool::new(true));
+        let still_running = Arc::clone(&running);
+        let thread = thread::spawn(move || {
+            while still_running.load(Ordering::Relaxed) {
+                poll();
+                thread::sleep(POLL_INTERVAL);
+            }
+        });
+        Self {
+            running,
+            thread: Some(thread),
+        }
+    }
+}
+
+impl Drop for Worker {
+    fn drop(&mut self) {
+        self.running.store(false, Ordering::Relaxed);
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}